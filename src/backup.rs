@@ -0,0 +1,440 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use chrono::{Datelike, Local, NaiveDateTime};
+use rusqlite::Connection;
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::notes::notes_db_path;
+use crate::settings::{Settings, save_settings};
+
+const BACKUP_PREFIX: &str = "note-";
+/// 恢复备份前留下的快照，轮换时不删除
+const PRE_RESTORE_PREFIX: &str = "pre-restore-";
+const BACKUP_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// 距上次备份保存笔记的次数，以及触发备份的次数（由设置决定）
+static SAVES_SINCE_BACKUP: AtomicU32 = AtomicU32::new(0);
+static BACKUP_EVERY_SAVES: AtomicU32 = AtomicU32::new(0);
+/// 自动备份时保留的每日、每周份数
+static KEEP_DAILY: AtomicU32 = AtomicU32::new(7);
+static KEEP_WEEKLY: AtomicU32 = AtomicU32::new(4);
+
+/// 一份备份文件
+pub struct BackupInfo {
+	pub path: PathBuf,
+	pub time: NaiveDateTime,
+	pub size: u64,
+	/// 恢复备份前自动留下的快照
+	pub pre_restore: bool,
+}
+
+/// 备份目录：notes/backups/（不存在时自动创建）
+pub fn backups_dir() -> Option<PathBuf> {
+	let dir = notes_db_path()?.parent()?.join("backups");
+	if let Err(e) = fs::create_dir_all(&dir) {
+		eprintln!("无法创建备份目录 {:?}: {:?}", dir, e);
+		return None;
+	}
+	Some(dir)
+}
+
+/// 按设置更新自动备份的频率与保留份数
+pub fn apply_backup_settings(settings: &Settings) {
+	BACKUP_EVERY_SAVES.store(settings.backup_every_saves, Ordering::Relaxed);
+	KEEP_DAILY.store(settings.backup_keep_daily as u32, Ordering::Relaxed);
+	KEEP_WEEKLY.store(settings.backup_keep_weekly as u32, Ordering::Relaxed);
+}
+
+/// 用 `PRAGMA integrity_check` 检查数据库，返回发现的问题
+pub fn integrity_check(db_path: &Path) -> Result<(), String> {
+	let conn = Connection::open(db_path).map_err(|e| format!("无法打开数据库：{}", e))?;
+	let mut stmt = conn.prepare("PRAGMA integrity_check").map_err(|e| e.to_string())?;
+	let problems: Vec<String> = stmt
+		.query_map([], |row| row.get::<_, String>(0))
+		.map_err(|e| e.to_string())?
+		.flatten()
+		.filter(|line| line != "ok")
+		.collect();
+	if problems.is_empty() {
+		Ok(())
+	} else {
+		Err(problems.join("; "))
+	}
+}
+
+/// 列出所有备份（新的在前）
+pub fn list_backups() -> Vec<BackupInfo> {
+	let Some(dir) = backups_dir() else { return Vec::new() };
+	let Ok(entries) = fs::read_dir(&dir) else { return Vec::new() };
+
+	let mut backups: Vec<BackupInfo> = entries
+		.flatten()
+		.filter_map(|e| {
+			let path = e.path();
+			let stem = path.file_stem()?.to_str()?;
+			let (time, pre_restore) = match stem.strip_prefix(PRE_RESTORE_PREFIX) {
+				Some(time) => (time, true),
+				None => (stem.strip_prefix(BACKUP_PREFIX)?, false),
+			};
+			let time = NaiveDateTime::parse_from_str(time, BACKUP_TIME_FORMAT).ok()?;
+			let size = e.metadata().map(|m| m.len()).unwrap_or(0);
+			Some(BackupInfo { path, time, size, pre_restore })
+		})
+		.collect();
+	backups.sort_by_key(|b| std::cmp::Reverse(b.time));
+	backups
+}
+
+/// 备份 note.db 并轮换旧备份；数据库已损坏时不备份，以免覆盖掉好的备份
+pub fn backup_notes() -> Result<PathBuf, String> {
+	let target = write_snapshot(BACKUP_PREFIX)?;
+	SAVES_SINCE_BACKUP.store(0, Ordering::Relaxed);
+	rotate_backups(
+		KEEP_DAILY.load(Ordering::Relaxed) as usize,
+		KEEP_WEEKLY.load(Ordering::Relaxed) as usize,
+	);
+	Ok(target)
+}
+
+/// 把 note.db 写到备份目录中以 prefix 开头的文件，不轮换
+fn write_snapshot(prefix: &str) -> Result<PathBuf, String> {
+	let db_path = notes_db_path().ok_or("找不到笔记数据库")?;
+	if !db_path.exists() {
+		return Err("还没有笔记数据库".to_string());
+	}
+	integrity_check(&db_path).map_err(|e| format!("笔记数据库完整性检查未通过：{}", e))?;
+
+	let dir = backups_dir().ok_or("无法创建备份目录")?;
+	let target = dir.join(format!("{}{}.db", prefix, Local::now().format(BACKUP_TIME_FORMAT)));
+	if target.exists() {
+		return Ok(target);
+	}
+
+	let conn = Connection::open(&db_path).map_err(|e| format!("无法打开数据库：{}", e))?;
+	conn.execute("VACUUM INTO ?1", [target.to_string_lossy()])
+		.map_err(|e| format!("备份失败：{}", e))?;
+	println!("已备份笔记数据库到 {:?}", target);
+	Ok(target)
+}
+
+/// 保存或删除笔记后调用；达到设定次数时自动备份
+pub fn note_changed() {
+	let every = BACKUP_EVERY_SAVES.load(Ordering::Relaxed);
+	if every == 0 {
+		return;
+	}
+	if SAVES_SINCE_BACKUP.fetch_add(1, Ordering::Relaxed) + 1 >= every
+		&& let Err(e) = backup_notes()
+	{
+		eprintln!("自动备份失败: {}", e);
+	}
+}
+
+/// 要保留的备份：最近 keep_daily 天每天最新的一份、最近 keep_weekly 周每周最新的一份（backups 须新的在前）
+fn backups_to_keep(backups: &[BackupInfo], keep_daily: usize, keep_weekly: usize) -> HashSet<PathBuf> {
+	let mut keep: HashSet<PathBuf> = HashSet::new();
+	// 最新的一份总是保留
+	if let Some(latest) = backups.first() {
+		keep.insert(latest.path.clone());
+	}
+
+	let mut days = HashSet::new();
+	let mut weeks = HashSet::new();
+	for b in backups {
+		let day = b.time.date();
+		if days.len() < keep_daily && days.insert(day) {
+			keep.insert(b.path.clone());
+		}
+		let week = day.iso_week();
+		if weeks.len() < keep_weekly && weeks.insert((week.year(), week.week())) {
+			keep.insert(b.path.clone());
+		}
+	}
+	keep
+}
+
+/// 轮换备份：按 backups_to_keep 保留，其余删除；恢复前的快照不参与
+pub fn rotate_backups(keep_daily: usize, keep_weekly: usize) {
+	let mut backups = list_backups();
+	backups.retain(|b| !b.pre_restore);
+	let keep = backups_to_keep(&backups, keep_daily, keep_weekly);
+	for b in backups.iter().filter(|b| !keep.contains(&b.path)) {
+		match fs::remove_file(&b.path) {
+			Ok(_) => println!("已删除旧备份 {:?}", b.path),
+			Err(e) => eprintln!("删除旧备份失败 {:?}: {:?}", b.path, e),
+		}
+	}
+}
+
+/// 用备份替换 note.db；替换前先检查备份并留一份当前数据库的快照（不轮换，以免删掉要恢复的备份）
+pub fn restore_backup(backup: &Path) -> Result<(), String> {
+	integrity_check(backup).map_err(|e| format!("备份文件已损坏：{}", e))?;
+	let db_path = notes_db_path().ok_or("找不到笔记数据库")?;
+	if db_path.exists()
+		&& let Err(e) = write_snapshot(PRE_RESTORE_PREFIX)
+	{
+		eprintln!("恢复前备份当前数据库失败: {}", e);
+	}
+
+	fs::copy(backup, &db_path).map_err(|e| format!("恢复失败：{}", e))?;
+	// 恢复的数据库可能与原来的加密状态不同
+	crate::crypto::reload("notes");
+
+	println!("已从备份恢复笔记数据库 {:?}", backup);
+	Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+	if bytes >= 1024 * 1024 {
+		format!("{:.1} MB", bytes as f64 / 1024.0 / 1024.0)
+	} else {
+		format!("{:.1} KB", bytes as f64 / 1024.0)
+	}
+}
+
+/// 备份窗口的状态
+#[derive(Default)]
+pub struct BackupDialog {
+	backups: Vec<BackupInfo>,
+	status: Option<String>,
+	confirm_restore: Option<PathBuf>,
+}
+
+impl BackupDialog {
+	pub fn new() -> Self {
+		Self { backups: list_backups(), ..Default::default() }
+	}
+}
+
+//备份窗口
+impl BibleApp {
+	pub fn show_backup_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(mut dialog) = self.backup_dialog.take() else { return };
+
+		let mut open = true;
+		let mut backup_now = false;
+		let mut check_now = false;
+		let mut restore: Option<PathBuf> = None;
+		let mut settings_changed = false;
+
+		egui::Window::new(egui::RichText::new("💾 笔记备份").size(14.0))
+			.open(&mut open)
+			.resizable(true)
+			.default_width(440.0)
+			.show(ctx, |ui| {
+				egui::Grid::new("backup_settings").num_columns(2).spacing([12.0, 6.0]).show(ui, |ui| {
+					ui.label("自动备份：");
+					ui.horizontal(|ui| {
+						ui.label("启动时，以及每保存");
+						settings_changed |= ui.add(egui::DragValue::new(&mut self.settings.backup_every_saves).range(0..=1000)).changed();
+						ui.label("次笔记");
+					});
+					ui.end_row();
+
+					ui.label("保留：");
+					ui.horizontal(|ui| {
+						ui.label("最近");
+						settings_changed |= ui.add(egui::DragValue::new(&mut self.settings.backup_keep_daily).range(1..=60)).changed();
+						ui.label("天每天一份，最近");
+						settings_changed |= ui.add(egui::DragValue::new(&mut self.settings.backup_keep_weekly).range(0..=52)).changed();
+						ui.label("周每周一份");
+					});
+					ui.end_row();
+				});
+
+				ui.horizontal(|ui| {
+					if ui.button("立即备份").clicked() {
+						backup_now = true;
+					}
+					if ui.button("检查数据库").clicked() {
+						check_now = true;
+					}
+				});
+				if let Some(status) = &dialog.status {
+					ui.label(egui::RichText::new(status).size(12.0).color(colors.comment_text_color));
+				}
+
+				ui.separator();
+				if dialog.backups.is_empty() {
+					ui.label(egui::RichText::new("还没有备份").color(colors.comment_text_color));
+				}
+				egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+					for b in &dialog.backups {
+						ui.horizontal(|ui| {
+							ui.label(b.time.format("%Y-%m-%d %H:%M:%S").to_string());
+							if b.pre_restore {
+								ui.label(egui::RichText::new("恢复前").size(11.0).color(colors.comment_text_color));
+							}
+							ui.label(egui::RichText::new(format_size(b.size)).size(11.0).color(colors.comment_text_color));
+							ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+								if ui.button("恢复").clicked() {
+									dialog.confirm_restore = Some(b.path.clone());
+								}
+							});
+						});
+					}
+				});
+
+				if let Some(path) = dialog.confirm_restore.clone() {
+					ui.separator();
+					ui.label(egui::RichText::new("用这份备份替换当前的全部笔记？（当前数据库会先备份一份）").color(colors.text_color));
+					ui.horizontal(|ui| {
+						if ui.button("恢复").clicked() {
+							restore = Some(path);
+							dialog.confirm_restore = None;
+						}
+						if ui.button("取消").clicked() {
+							dialog.confirm_restore = None;
+						}
+					});
+				}
+			});
+
+		if settings_changed {
+			apply_backup_settings(&self.settings);
+			save_settings(&self.settings);
+		}
+
+		if backup_now {
+			dialog.status = Some(match backup_notes() {
+				Ok(path) => format!("已备份到 {}", path.display()),
+				Err(e) => {
+					eprintln!("备份失败: {}", e);
+					e
+				}
+			});
+			dialog.backups = list_backups();
+		}
+
+		if check_now {
+			dialog.status = Some(match notes_db_path() {
+				Some(path) => match integrity_check(&path) {
+					Ok(()) => "数据库完整性检查通过".to_string(),
+					Err(e) => format!("数据库有问题：{}（可从备份恢复）", e),
+				},
+				None => "找不到笔记数据库".to_string(),
+			});
+		}
+
+		if let Some(path) = restore {
+			dialog.status = Some(match restore_backup(&path) {
+				Ok(()) => {
+					self.last_appended_notes_chapter = None;
+					self.reload_notes_list();
+					if crate::crypto::is_locked() {
+						self.unlock_prompt = true;
+						"已从备份恢复；这份备份已加密，请输入它的密码解锁".to_string()
+					} else {
+						"已从备份恢复".to_string()
+					}
+				}
+				Err(e) => {
+					eprintln!("恢复备份失败 {:?}: {}", path, e);
+					e
+				}
+			});
+			dialog.backups = list_backups();
+		}
+
+		if open {
+			self.backup_dialog = Some(dialog);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// 按时间生成备份列表，新的在前（与 list_backups 一致）
+	fn backups(times: &[&str]) -> Vec<BackupInfo> {
+		let mut list: Vec<BackupInfo> = times.iter()
+			.map(|t| BackupInfo {
+				path: PathBuf::from(format!("{}{}.db", BACKUP_PREFIX, t)),
+				time: NaiveDateTime::parse_from_str(t, BACKUP_TIME_FORMAT).unwrap(),
+				size: 0,
+				pre_restore: false,
+			})
+			.collect();
+		list.sort_by_key(|b| std::cmp::Reverse(b.time));
+		list
+	}
+
+	fn kept(list: &[BackupInfo], keep_daily: usize, keep_weekly: usize) -> Vec<String> {
+		let keep = backups_to_keep(list, keep_daily, keep_weekly);
+		let mut names: Vec<String> = list.iter()
+			.filter(|b| keep.contains(&b.path))
+			.map(|b| b.time.format(BACKUP_TIME_FORMAT).to_string())
+			.collect();
+		names.sort();
+		names
+	}
+
+	#[test]
+	fn keeps_newest_backup_of_each_recent_day() {
+		let list = backups(&[
+			"20240506-090000", "20240506-180000",
+			"20240507-090000", "20240507-180000",
+			"20240508-120000",
+		]);
+		assert_eq!(kept(&list, 2, 0), vec!["20240507-180000", "20240508-120000"]);
+	}
+
+	#[test]
+	fn keeps_newest_backup_of_each_recent_week() {
+		// 2024-04-22、04-29、05-06 各是一周的周一
+		let list = backups(&[
+			"20240422-100000", "20240424-100000",
+			"20240429-100000", "20240501-100000",
+			"20240506-100000",
+		]);
+		assert_eq!(kept(&list, 1, 2), vec!["20240501-100000", "20240506-100000"]);
+		assert_eq!(kept(&list, 1, 3), vec!["20240424-100000", "20240501-100000", "20240506-100000"]);
+	}
+
+	#[test]
+	fn always_keeps_latest_backup() {
+		let list = backups(&["20240501-100000", "20240502-100000"]);
+		assert_eq!(kept(&list, 0, 0), vec!["20240502-100000"]);
+		assert!(backups_to_keep(&[], 7, 4).is_empty());
+	}
+
+	#[test]
+	fn restore_keeps_chosen_backup_and_snapshot() {
+		let _encryption = crate::crypto::test_encryption(None);
+		let root = std::env::temp_dir().join(format!("backup-test-{}", uuid::Uuid::new_v4()));
+		fs::create_dir_all(&root).unwrap();
+		let db_path = root.join("note.db");
+		crate::notes::set_test_db_path(Some(db_path.clone()));
+		let write_db = |path: &Path, value: &str| {
+			let conn = Connection::open(path).unwrap();
+			conn.execute_batch("CREATE TABLE IF NOT EXISTS t (v TEXT); DELETE FROM t;").unwrap();
+			conn.execute("INSERT INTO t (v) VALUES (?1)", [value]).unwrap();
+		};
+		let read_db = |path: &Path| -> String {
+			Connection::open(path).unwrap().query_row("SELECT v FROM t", [], |row| row.get(0)).unwrap()
+		};
+
+		// 要恢复的是较旧的一份，轮换时会被删掉
+		let dir = backups_dir().unwrap();
+		let chosen = dir.join(format!("{}20200101-000000.db", BACKUP_PREFIX));
+		write_db(&chosen, "备份");
+		write_db(&dir.join(format!("{}20240101-000000.db", BACKUP_PREFIX)), "较新的备份");
+		write_db(&db_path, "当前");
+		restore_backup(&chosen).unwrap();
+		assert_eq!(read_db(&db_path), "备份");
+		assert!(chosen.exists());
+
+		// 恢复前的快照不会被轮换删掉
+		rotate_backups(0, 0);
+		assert!(!chosen.exists());
+		let snapshots: Vec<BackupInfo> = list_backups().into_iter().filter(|b| b.pre_restore).collect();
+		assert_eq!(snapshots.len(), 1);
+		assert_eq!(read_db(&snapshots[0].path), "当前");
+
+		crate::notes::set_test_db_path(None);
+		let _ = fs::remove_dir_all(&root);
+	}
+}
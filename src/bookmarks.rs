@@ -0,0 +1,413 @@
+use rusqlite::Connection;
+use uuid::Uuid;
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::reference::{ScriptureRef, book_full_name};
+use crate::userdata::open_user_db;
+use crate::utils::now_timestamp;
+
+/// 书签：一章或一节，可放在文件夹中
+#[derive(Debug, Clone)]
+pub struct Bookmark {
+	pub id: String,
+	pub name: String,
+	/// 所在文件夹，空字符串表示未归类
+	pub folder: String,
+	pub book_num: i32,
+	pub chapter: i32,
+	pub verse: Option<i32>,
+}
+
+impl Bookmark {
+	pub fn reference(&self) -> ScriptureRef {
+		ScriptureRef {
+			book_num: self.book_num,
+			chapter: self.chapter,
+			verse_start: self.verse,
+			verse_end: None,
+		}
+	}
+}
+
+/// 正在重命名 / 移动的书签及输入框内容
+#[derive(Debug, Clone)]
+pub enum BookmarkEdit {
+	Rename { id: String, name: String },
+	Move { id: String, folder: String },
+	RenameFolder { folder: String, name: String },
+}
+
+/// 书签栏的操作结果
+enum BookmarkAction {
+	None,
+	Open(ScriptureRef),
+	Rename(String, String),
+	Move(String, String),
+	RenameFolder(String, String),
+	Delete(String),
+}
+
+/// 书签按书卷、章、节保存，与译本无关
+pub fn ensure_bookmark_schema(conn: &Connection) -> rusqlite::Result<()> {
+	conn.execute_batch(
+		"CREATE TABLE IF NOT EXISTS bookmarks (
+			id TEXT PRIMARY KEY,
+			name TEXT NOT NULL,
+			folder TEXT NOT NULL DEFAULT '',
+			book_num INTEGER NOT NULL,
+			chapter INTEGER NOT NULL,
+			verse INTEGER,
+			created_at TEXT
+		);",
+	)
+}
+
+/// 全部书签，按文件夹和经文顺序
+pub fn load_bookmarks() -> Vec<Bookmark> {
+	let Ok(Some(conn)) = open_user_db() else { return Vec::new() };
+	let Ok(mut stmt) = conn.prepare(
+		"SELECT id, name, folder, book_num, chapter, verse FROM bookmarks
+		 ORDER BY folder, book_num, chapter, COALESCE(verse, 0)",
+	) else {
+		return Vec::new();
+	};
+	let rows = stmt.query_map([], |row| {
+		Ok(Bookmark {
+			id: row.get(0)?,
+			name: row.get(1)?,
+			folder: row.get(2)?,
+			book_num: row.get(3)?,
+			chapter: row.get(4)?,
+			verse: row.get(5)?,
+		})
+	});
+	match rows {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
+/// 书签的默认名字，例如「约翰福音 3:16」
+pub fn default_bookmark_name(r: &ScriptureRef) -> String {
+	match r.verse_start {
+		Some(v) => format!("{} {}:{}", book_full_name(r.book_num), r.chapter, v),
+		None => format!("{} {} 章", book_full_name(r.book_num), r.chapter),
+	}
+}
+
+pub fn add_bookmark(r: &ScriptureRef, folder: &str) -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	conn.execute(
+		"INSERT INTO bookmarks (id, name, folder, book_num, chapter, verse, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+		rusqlite::params![
+			Uuid::new_v4().to_string(),
+			default_bookmark_name(r),
+			folder.trim(),
+			r.book_num,
+			r.chapter,
+			r.verse_start,
+			now_timestamp(),
+		],
+	)?;
+	Ok(())
+}
+
+pub fn rename_bookmark(id: &str, name: &str) -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	conn.execute("UPDATE bookmarks SET name = ?1 WHERE id = ?2", [name.trim(), id])?;
+	Ok(())
+}
+
+pub fn move_bookmark(id: &str, folder: &str) -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	conn.execute("UPDATE bookmarks SET folder = ?1 WHERE id = ?2", [folder.trim(), id])?;
+	Ok(())
+}
+
+/// 重命名文件夹；新名字为空时其中的书签变为未归类
+pub fn rename_folder(folder: &str, name: &str) -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	conn.execute("UPDATE bookmarks SET folder = ?1 WHERE folder = ?2", [name.trim(), folder])?;
+	Ok(())
+}
+
+pub fn delete_bookmark(id: &str) -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	conn.execute("DELETE FROM bookmarks WHERE id = ?1", [id])?;
+	Ok(())
+}
+
+/// 当前章有整章书签时删除，否则添加；返回是否添加了书签
+pub fn toggle_chapter_bookmark(book_num: i32, chapter: i32) -> rusqlite::Result<bool> {
+	let Some(conn) = open_user_db()? else { return Ok(false) };
+	let removed = conn.execute(
+		"DELETE FROM bookmarks WHERE book_num = ?1 AND chapter = ?2 AND verse IS NULL",
+		[book_num, chapter],
+	)?;
+	if removed > 0 {
+		return Ok(false);
+	}
+	drop(conn);
+	add_bookmark(&ScriptureRef { book_num, chapter, verse_start: None, verse_end: None }, "")?;
+	Ok(true)
+}
+
+/// 所有文件夹名（不含未归类）
+fn folder_names(bookmarks: &[Bookmark]) -> Vec<String> {
+	let mut folders: Vec<String> = bookmarks.iter().map(|b| b.folder.clone()).filter(|f| !f.is_empty()).collect();
+	folders.dedup();
+	folders
+}
+
+fn draw_bookmark_item(ui: &mut egui::Ui, colors: &ThemeColors, b: &Bookmark, current: Option<(i32, i32)>, folders: &[String], edit_state: &mut Option<BookmarkEdit>, action: &mut BookmarkAction) {
+	let is_current = current == Some((b.book_num, b.chapter));
+	let icon = if b.verse.is_some() { "📌" } else { "🔖" };
+	let resp = ui.selectable_label(is_current, format!("{} {}", icon, b.name))
+		.on_hover_text(b.reference().display());
+	if resp.clicked() {
+		*action = BookmarkAction::Open(b.reference());
+	}
+	resp.context_menu(|ui| {
+		if ui.button("✏ 重命名").clicked() {
+			*edit_state = Some(BookmarkEdit::Rename { id: b.id.clone(), name: b.name.clone() });
+			ui.close_kind(egui::UiKind::Menu);
+		}
+		ui.menu_button("📁 移到文件夹", |ui| {
+			if !b.folder.is_empty() && ui.button("未归类").clicked() {
+				*action = BookmarkAction::Move(b.id.clone(), String::new());
+				ui.close_kind(egui::UiKind::Menu);
+			}
+			for f in folders.iter().filter(|f| **f != b.folder) {
+				if ui.button(f).clicked() {
+					*action = BookmarkAction::Move(b.id.clone(), f.clone());
+					ui.close_kind(egui::UiKind::Menu);
+				}
+			}
+			if ui.button(egui::RichText::new("新文件夹…").color(colors.comment_text_color)).clicked() {
+				*edit_state = Some(BookmarkEdit::Move { id: b.id.clone(), folder: String::new() });
+				ui.close_kind(egui::UiKind::Menu);
+			}
+		});
+		if ui.button("🗑 删除").clicked() {
+			*action = BookmarkAction::Delete(b.id.clone());
+			ui.close_kind(egui::UiKind::Menu);
+		}
+	});
+}
+
+//书签栏
+impl BibleApp {
+	pub fn reload_bookmarks(&mut self) {
+		self.bookmarks = load_bookmarks();
+	}
+
+	/// 当前章是否有整章书签
+	pub fn current_chapter_bookmarked(&self) -> bool {
+		let (Some(book), Some(chap)) = (self.current_book, self.current_chapter.as_deref()) else { return false };
+		let chapter: i32 = chap.parse().unwrap_or(0);
+		self.bookmarks.iter().any(|b| b.book_num == book && b.chapter == chapter && b.verse.is_none())
+	}
+
+	/// 为当前章添加 / 取消书签（Ctrl+B）
+	pub fn toggle_current_bookmark(&mut self) {
+		let (Some(book), Some(chap)) = (self.current_book, self.current_chapter.as_deref()) else { return };
+		let chapter: i32 = chap.parse().unwrap_or(1);
+		match toggle_chapter_bookmark(book, chapter) {
+			Ok(true) => println!("已添加书签 {} {}", book_full_name(book), chapter),
+			Ok(false) => println!("已删除书签 {} {}", book_full_name(book), chapter),
+			Err(e) => eprintln!("切换书签失败: {:?}", e),
+		}
+		self.reload_bookmarks();
+	}
+
+	pub fn add_verse_bookmark(&mut self, r: &ScriptureRef) {
+		if let Err(e) = add_bookmark(r, "") {
+			eprintln!("添加书签失败: {:?}", e);
+		}
+		self.reload_bookmarks();
+		self.show_bookmarks_panel = true;
+	}
+
+	pub fn ui_left_bookmarks_panel(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		if !self.show_bookmarks_panel {
+			return;
+		}
+		let current = match (self.current_book, self.current_chapter.as_deref()) {
+			(Some(book), Some(chap)) => Some((book, chap.parse().unwrap_or(0))),
+			_ => None,
+		};
+		let folders = folder_names(&self.bookmarks);
+		let mut action = BookmarkAction::None;
+		let mut toggle_current = false;
+		let hint = format!(
+			"{}{}",
+			if self.current_chapter_bookmarked() { "删除本章书签" } else { "为本章添加书签" },
+			self.shortcut_hint(crate::shortcuts::ShortcutAction::ToggleBookmark),
+		);
+
+		egui::SidePanel::left("bookmarks_panel")
+			.resizable(true)
+			.default_width(150.0)
+			.show(ctx, |ui| {
+				ui.horizontal(|ui| {
+					ui.label(egui::RichText::new("书签").strong());
+					ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+						if ui.small_button("✖").on_hover_text("关闭书签栏").clicked() {
+							self.show_bookmarks_panel = false;
+						}
+						if ui.small_button("➕").on_hover_text(&hint).clicked() {
+							toggle_current = true;
+						}
+					});
+				});
+				ui.separator();
+
+				egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+					if self.bookmarks.is_empty() {
+						ui.label(egui::RichText::new("还没有书签").size(12.0).color(colors.comment_text_color));
+					}
+					for folder in &folders {
+						let count = self.bookmarks.iter().filter(|b| b.folder == *folder).count();
+						let id = ui.make_persistent_id(("bookmark_folder", folder));
+						egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+							.show_header(ui, |ui| {
+								ui.label(format!("📁 {} ({})", folder, count)).context_menu(|ui| {
+									if ui.button("✏ 重命名文件夹").clicked() {
+										self.bookmark_edit = Some(BookmarkEdit::RenameFolder { folder: folder.clone(), name: folder.clone() });
+										ui.close_kind(egui::UiKind::Menu);
+									}
+									if ui.button("📂 取消文件夹（书签变为未归类）").clicked() {
+										action = BookmarkAction::RenameFolder(folder.clone(), String::new());
+										ui.close_kind(egui::UiKind::Menu);
+									}
+								});
+							})
+							.body(|ui| {
+								for b in self.bookmarks.iter().filter(|b| b.folder == *folder) {
+									draw_bookmark_item(ui, colors, b, current, &folders, &mut self.bookmark_edit, &mut action);
+								}
+							});
+					}
+					for b in self.bookmarks.iter().filter(|b| b.folder.is_empty()) {
+						draw_bookmark_item(ui, colors, b, current, &folders, &mut self.bookmark_edit, &mut action);
+					}
+
+					if let Some(edit) = &mut self.bookmark_edit {
+						ui.separator();
+						let (title, text) = match edit {
+							BookmarkEdit::Rename { name, .. } => ("重命名书签", name),
+							BookmarkEdit::Move { folder, .. } => ("移到新文件夹", folder),
+							BookmarkEdit::RenameFolder { name, .. } => ("重命名文件夹", name),
+						};
+						ui.label(egui::RichText::new(title).size(12.0).color(colors.comment_text_color));
+						ui.add(egui::TextEdit::singleline(text).desired_width(f32::INFINITY));
+
+						let mut finished = false;
+						ui.horizontal(|ui| {
+							if ui.button("确定").clicked() {
+								action = match edit {
+									BookmarkEdit::Rename { id, name } => BookmarkAction::Rename(id.clone(), name.clone()),
+									BookmarkEdit::Move { id, folder } => BookmarkAction::Move(id.clone(), folder.clone()),
+									BookmarkEdit::RenameFolder { folder, name } => BookmarkAction::RenameFolder(folder.clone(), name.clone()),
+								};
+								finished = true;
+							}
+							if ui.button("取消").clicked() {
+								finished = true;
+							}
+						});
+						if finished {
+							self.bookmark_edit = None;
+						}
+					}
+				});
+			});
+
+		if toggle_current {
+			self.toggle_current_bookmark();
+		}
+
+		let result = match action {
+			BookmarkAction::None => return,
+			// 走正常的跳转流程，跳转记录保持一致
+			BookmarkAction::Open(r) => {
+				self.go_to_reference(&r);
+				return;
+			}
+			BookmarkAction::Rename(id, name) if !name.trim().is_empty() => rename_bookmark(&id, &name),
+			BookmarkAction::Rename(..) => return,
+			BookmarkAction::Move(id, folder) => move_bookmark(&id, &folder),
+			BookmarkAction::RenameFolder(folder, name) => rename_folder(&folder, &name),
+			BookmarkAction::Delete(id) => delete_bookmark(&id),
+		};
+		if let Err(e) = result {
+			eprintln!("修改书签失败: {:?}", e);
+		}
+		self.reload_bookmarks();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::userdata::test_user_db;
+
+	fn verse(book_num: i32, chapter: i32, verse: Option<i32>) -> ScriptureRef {
+		ScriptureRef { book_num, chapter, verse_start: verse, verse_end: None }
+	}
+
+	fn listed() -> Vec<(String, String)> {
+		load_bookmarks().into_iter().map(|b| (b.folder, b.name)).collect()
+	}
+
+	#[test]
+	fn names_bookmarks_by_reference() {
+		assert_eq!(default_bookmark_name(&verse(43, 3, Some(16))), "约翰福音 3:16");
+		assert_eq!(default_bookmark_name(&verse(43, 3, None)), "约翰福音 3 章");
+	}
+
+	#[test]
+	fn lists_by_folder_then_scripture_order() {
+		let _db = test_user_db();
+		add_bookmark(&verse(43, 3, Some(16)), " 福音 ").unwrap();
+		add_bookmark(&verse(1, 1, None), "").unwrap();
+		add_bookmark(&verse(40, 5, Some(3)), "福音").unwrap();
+		assert_eq!(listed(), vec![
+			(String::new(), "创世记 1 章".to_string()),
+			("福音".to_string(), "马太福音 5:3".to_string()),
+			("福音".to_string(), "约翰福音 3:16".to_string()),
+		]);
+		assert_eq!(folder_names(&load_bookmarks()), vec!["福音"]);
+	}
+
+	#[test]
+	fn renames_moves_and_deletes() {
+		let _db = test_user_db();
+		add_bookmark(&verse(43, 3, Some(16)), "福音").unwrap();
+		add_bookmark(&verse(40, 5, Some(3)), "福音").unwrap();
+		let id = load_bookmarks()[1].id.clone();
+
+		rename_bookmark(&id, " 神爱世人 ").unwrap();
+		move_bookmark(&id, "金句").unwrap();
+		assert_eq!(listed(), vec![
+			("福音".to_string(), "马太福音 5:3".to_string()),
+			("金句".to_string(), "神爱世人".to_string()),
+		]);
+
+		// 文件夹改名为空时书签变为未归类
+		rename_folder("福音", " ").unwrap();
+		delete_bookmark(&id).unwrap();
+		assert_eq!(listed(), vec![(String::new(), "马太福音 5:3".to_string())]);
+	}
+
+	#[test]
+	fn toggles_whole_chapter_bookmarks_only() {
+		let _db = test_user_db();
+		add_bookmark(&verse(43, 3, Some(16)), "").unwrap();
+		assert!(toggle_chapter_bookmark(43, 3).unwrap());
+		assert_eq!(load_bookmarks().len(), 2);
+		assert!(!toggle_chapter_bookmark(43, 3).unwrap());
+		let left: Vec<Option<i32>> = load_bookmarks().iter().map(|b| b.verse).collect();
+		assert_eq!(left, vec![Some(16)]);
+	}
+}
@@ -0,0 +1,544 @@
+use std::path::Path;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use argon2::Argon2;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use chacha20poly1305::aead::{Aead, AeadCore, OsRng, rand_core::RngCore};
+use rusqlite::{Connection, OptionalExtension};
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::notes::{notes_db_path, ensure_notes_schema, table_exists, load_note};
+use crate::note_app::NoteApp;
+
+/// 加密后的正文以此开头，后接 base64(nonce + 密文)
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+/// 用来验证密码是否正确的明文
+const VERIFIER_PLAINTEXT: &str = "bible_reader";
+/// 未解锁时显示在正文位置的提示
+pub const LOCKED_PLACEHOLDER: &str = "🔒 笔记已加密，解锁后可查看";
+
+/// 笔记库是否启用了加密；解锁后的密钥只保存在内存中
+static ENCRYPTED: AtomicBool = AtomicBool::new(false);
+static NOTE_KEY: RwLock<Option<Key>> = RwLock::new(None);
+
+fn crypto_table(category: &str) -> String {
+	format!("{}_crypto", category)
+}
+
+fn ensure_crypto_schema(conn: &Connection, category: &str) -> rusqlite::Result<()> {
+	conn.execute_batch(&format!(
+		"CREATE TABLE IF NOT EXISTS {} (
+			id INTEGER PRIMARY KEY CHECK (id = 1),
+			salt TEXT NOT NULL,
+			verifier TEXT NOT NULL
+		);",
+		crypto_table(category)
+	))
+}
+
+/// 读取盐和密码校验值；未启用加密时返回 None
+pub fn load_crypto_params(conn: &Connection, category: &str) -> rusqlite::Result<Option<(String, String)>> {
+	if !table_exists(conn, &crypto_table(category)) {
+		return Ok(None);
+	}
+	conn.query_row(
+		&format!("SELECT salt, verifier FROM {} WHERE id = 1", crypto_table(category)),
+		[],
+		|row| Ok((row.get(0)?, row.get(1)?)),
+	)
+	.optional()
+}
+
+/// 启动时检查笔记库是否加密
+pub fn init(category: &str) {
+	let Some(db_path) = notes_db_path() else { return };
+	if !db_path.exists() {
+		return;
+	}
+	let encrypted = Connection::open(&db_path)
+		.ok()
+		.and_then(|conn| load_crypto_params(&conn, category).ok().flatten())
+		.is_some();
+	ENCRYPTED.store(encrypted, Ordering::Relaxed);
+}
+
+/// 笔记数据库被整个替换（从备份恢复）后，按新文件重新判断是否加密；
+/// 内存中的密钥属于原来的数据库，一律清掉，已加密时需重新解锁
+pub fn reload(category: &str) {
+	if let Ok(mut key) = NOTE_KEY.write() {
+		*key = None;
+	}
+	ENCRYPTED.store(false, Ordering::Relaxed);
+	init(category);
+}
+
+pub fn is_encrypted() -> bool {
+	ENCRYPTED.load(Ordering::Relaxed)
+}
+
+/// 已加密但还没有输入密码
+pub fn is_locked() -> bool {
+	is_encrypted() && NOTE_KEY.read().map(|k| k.is_none()).unwrap_or(true)
+}
+
+/// 测试用：独占全局加密状态；传入密码时视为已加密并已解锁，守卫释放时恢复为未加密
+#[cfg(test)]
+pub fn test_encryption(passphrase: Option<&str>) -> TestEncryption {
+	static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+	let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+	let key = passphrase.map(|p| derive_key(p, b"0123456789abcdef").unwrap());
+	*NOTE_KEY.write().unwrap() = key;
+	ENCRYPTED.store(key.is_some(), Ordering::Relaxed);
+	TestEncryption { _guard: guard }
+}
+
+#[cfg(test)]
+pub struct TestEncryption {
+	_guard: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+impl Drop for TestEncryption {
+	fn drop(&mut self) {
+		*NOTE_KEY.write().unwrap() = None;
+		ENCRYPTED.store(false, Ordering::Relaxed);
+	}
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+	let mut key = [0u8; 32];
+	Argon2::default()
+		.hash_password_into(passphrase.as_bytes(), salt, &mut key)
+		.map_err(|e| format!("生成密钥失败：{}", e))?;
+	Ok(Key::from(key))
+}
+
+/// 加密一个字段；失败时返回错误，调用方不得改写明文
+fn encrypt_with(key: &Key, text: &str) -> Result<String, String> {
+	let cipher = ChaCha20Poly1305::new(key);
+	let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+	let ciphertext = cipher.encrypt(&nonce, text.as_bytes()).map_err(|e| {
+		eprintln!("加密失败: {:?}", e);
+		format!("加密失败：{}", e)
+	})?;
+	let mut data = nonce.to_vec();
+	data.extend(ciphertext);
+	Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(data)))
+}
+
+/// 解密一个字段；不是密文时原样返回，密钥不对时返回 None
+fn decrypt_with(key: Option<&Key>, stored: &str) -> Option<String> {
+	let Some(encoded) = stored.strip_prefix(ENCRYPTED_PREFIX) else { return Some(stored.to_string()) };
+	let data = BASE64.decode(encoded).ok()?;
+	if data.len() < 12 {
+		return None;
+	}
+	let (nonce, ciphertext) = data.split_at(12);
+	let plaintext = ChaCha20Poly1305::new(key?).decrypt(Nonce::from_slice(nonce), ciphertext).ok()?;
+	String::from_utf8(plaintext).ok()
+}
+
+/// 写入数据库前加密（未启用加密时原样返回）；加密失败时不返回明文
+pub fn encrypt_field(text: &str) -> Result<String, String> {
+	match NOTE_KEY.read().ok().and_then(|k| *k) {
+		Some(key) if is_encrypted() => encrypt_with(&key, text),
+		_ => Ok(text.to_string()),
+	}
+}
+
+/// 从数据库读出后解密；未解锁时返回提示文字
+pub fn decrypt_field(stored: &str) -> String {
+	let key = NOTE_KEY.read().ok().and_then(|k| *k);
+	decrypt_with(key.as_ref(), stored).unwrap_or_else(|| LOCKED_PLACEHOLDER.to_string())
+}
+
+/// 输入密码解锁笔记库
+pub fn unlock(category: &str, passphrase: &str) -> Result<(), String> {
+	let db_path = notes_db_path().ok_or("找不到笔记数据库")?;
+	let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+	let (salt, verifier) = load_crypto_params(&conn, category)
+		.map_err(|e| e.to_string())?
+		.ok_or("笔记库没有加密")?;
+	let salt = BASE64.decode(salt).map_err(|e| e.to_string())?;
+	let key = derive_key(passphrase, &salt)?;
+	if decrypt_with(Some(&key), &verifier).as_deref() != Some(VERIFIER_PLAINTEXT) {
+		return Err("密码不正确".to_string());
+	}
+	*NOTE_KEY.write().map_err(|e| e.to_string())? = Some(key);
+	Ok(())
+}
+
+/// 用新密钥重写正文、历史版本和草稿（old 为 None 表示原来是明文，new 为 None 表示改回明文）
+fn rewrite_encrypted_fields(conn: &Connection, category: &str, old: Option<&Key>, new: Option<&Key>) -> Result<(), String> {
+	let convert = |stored: &str| -> Result<String, String> {
+		let plain = decrypt_with(old, stored).ok_or("有笔记无法用原密码解密")?;
+		Ok(match new {
+			Some(key) => encrypt_with(key, &plain)?,
+			None => plain,
+		})
+	};
+
+	let tables = [
+		(category.to_string(), "id", "body"),
+		(format!("{}_revisions", category), "rev_id", "body"),
+		(format!("{}_drafts", category), "note_id", "note_json"),
+	];
+	for (table, key_column, column) in &tables {
+		if !table_exists(conn, table) {
+			continue;
+		}
+		let rows: Vec<(rusqlite::types::Value, String)> = {
+			let mut stmt = conn
+				.prepare(&format!("SELECT {}, {} FROM {} WHERE {} IS NOT NULL", key_column, column, table, column))
+				.map_err(|e| e.to_string())?;
+			let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).map_err(|e| e.to_string())?;
+			rows.flatten().collect()
+		};
+		for (id, stored) in rows {
+			conn.execute(
+				&format!("UPDATE {} SET {} = ?1 WHERE {} = ?2", table, column, key_column),
+				rusqlite::params![convert(&stored)?, id],
+			)
+			.map_err(|e| e.to_string())?;
+		}
+	}
+	Ok(())
+}
+
+/// 启用加密或修改密码；passphrase 为 None 表示关闭加密
+pub fn set_passphrase(category: &str, passphrase: Option<&str>) -> Result<(), String> {
+	if is_locked() {
+		return Err("请先解锁笔记".to_string());
+	}
+	let db_path = notes_db_path().ok_or("找不到笔记数据库")?;
+	let mut conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+	ensure_notes_schema(&conn, category).map_err(|e| e.to_string())?;
+	ensure_crypto_schema(&conn, category).map_err(|e| e.to_string())?;
+
+	let old_key = NOTE_KEY.read().ok().and_then(|k| *k).filter(|_| is_encrypted());
+	let new_key = match passphrase {
+		Some(p) => {
+			let mut salt = [0u8; 16];
+			OsRng.fill_bytes(&mut salt);
+			Some((derive_key(p, &salt)?, BASE64.encode(salt)))
+		}
+		None => None,
+	};
+
+	let tx = conn.transaction().map_err(|e| e.to_string())?;
+	rewrite_encrypted_fields(&tx, category, old_key.as_ref(), new_key.as_ref().map(|(k, _)| k))?;
+	match &new_key {
+		Some((key, salt)) => tx.execute(
+			&format!("INSERT OR REPLACE INTO {} (id, salt, verifier) VALUES (1, ?1, ?2)", crypto_table(category)),
+			[salt, &encrypt_with(key, VERIFIER_PLAINTEXT)?],
+		),
+		None => tx.execute(&format!("DELETE FROM {}", crypto_table(category)), []),
+	}
+	.map_err(|e| e.to_string())?;
+	tx.commit().map_err(|e| e.to_string())?;
+
+	// 清掉已删除页面中残留的明文
+	if let Err(e) = conn.execute_batch("VACUUM") {
+		eprintln!("整理数据库失败: {:?}", e);
+	}
+
+	ENCRYPTED.store(new_key.is_some(), Ordering::Relaxed);
+	*NOTE_KEY.write().map_err(|e| e.to_string())? = new_key.map(|(k, _)| k);
+	Ok(())
+}
+
+/// 把一份明文备份用当前密钥加密；已加密的备份返回 false
+fn encrypt_backup(path: &Path, category: &str, key: &Key, params: &(String, String)) -> Result<bool, String> {
+	let mut conn = Connection::open(path).map_err(|e| e.to_string())?;
+	if load_crypto_params(&conn, category).map_err(|e| e.to_string())?.is_some() {
+		return Ok(false);
+	}
+	ensure_crypto_schema(&conn, category).map_err(|e| e.to_string())?;
+	let tx = conn.transaction().map_err(|e| e.to_string())?;
+	rewrite_encrypted_fields(&tx, category, None, Some(key))?;
+	tx.execute(
+		&format!("INSERT OR REPLACE INTO {} (id, salt, verifier) VALUES (1, ?1, ?2)", crypto_table(category)),
+		[&params.0, &params.1],
+	)
+	.map_err(|e| e.to_string())?;
+	tx.commit().map_err(|e| e.to_string())?;
+	conn.execute_batch("VACUUM").map_err(|e| e.to_string())?;
+	Ok(true)
+}
+
+/// 启用加密后处理之前留下的明文备份：用同一密钥加密，无法加密的删除。
+/// 返回（加密的份数, 删除的份数）
+pub fn encrypt_plain_backups(category: &str) -> (usize, usize) {
+	let Some(key) = NOTE_KEY.read().ok().and_then(|k| *k) else { return (0, 0) };
+	let params = notes_db_path()
+		.and_then(|p| Connection::open(p).ok())
+		.and_then(|conn| load_crypto_params(&conn, category).ok().flatten());
+	let Some(params) = params else { return (0, 0) };
+
+	let (mut encrypted, mut removed) = (0, 0);
+	for backup in crate::backup::list_backups() {
+		match encrypt_backup(&backup.path, category, &key, &params) {
+			Ok(true) => encrypted += 1,
+			Ok(false) => {}
+			Err(e) => {
+				eprintln!("加密备份失败 {:?}: {}，删除这份明文备份", backup.path, e);
+				match std::fs::remove_file(&backup.path) {
+					Ok(()) => removed += 1,
+					Err(e) => eprintln!("删除备份失败 {:?}: {:?}", backup.path, e),
+				}
+			}
+		}
+	}
+	(encrypted, removed)
+}
+
+/// 加密设置窗口与启动时的解锁窗口
+#[derive(Default)]
+pub struct CryptoDialog {
+	passphrase: String,
+	new_passphrase: String,
+	confirm_passphrase: String,
+	status: Option<String>,
+}
+
+//笔记加密
+impl BibleApp {
+	/// 启动时笔记库已加密，请求输入密码
+	pub fn show_unlock_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		if !self.unlock_prompt || !is_locked() {
+			return;
+		}
+		let dialog = self.crypto_dialog.get_or_insert_with(CryptoDialog::default);
+		let mut submit = false;
+		let mut later = false;
+
+		egui::Window::new(egui::RichText::new("🔒 解锁笔记").size(14.0))
+			.collapsible(false)
+			.resizable(false)
+			.anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+			.show(ctx, |ui| {
+				ui.label("笔记已加密，请输入密码：");
+				let response = ui.add(egui::TextEdit::singleline(&mut dialog.passphrase).password(true).desired_width(240.0));
+				response.request_focus();
+				if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+					submit = true;
+				}
+				if let Some(status) = &dialog.status {
+					ui.label(egui::RichText::new(status).size(12.0).color(colors.comment_text_color));
+				}
+				ui.horizontal(|ui| {
+					if ui.button("解锁").clicked() {
+						submit = true;
+					}
+					if ui.button("稍后").clicked() {
+						later = true;
+					}
+				});
+			});
+
+		if submit {
+			match unlock("notes", &dialog.passphrase) {
+				Ok(()) => {
+					println!("笔记已解锁");
+					self.crypto_dialog = None;
+					self.unlock_prompt = false;
+					self.on_notes_unlocked();
+				}
+				Err(e) => {
+					eprintln!("解锁笔记失败: {}", e);
+					dialog.status = Some(e);
+					dialog.passphrase.clear();
+				}
+			}
+		} else if later {
+			self.crypto_dialog = None;
+			self.unlock_prompt = false;
+		}
+	}
+
+	/// 解锁后重新读取笔记
+	fn on_notes_unlocked(&mut self) {
+		// 已打开的编辑窗口中可能是锁定时的占位文字，换成解密后的笔记
+		if !self.note_editors.is_empty()
+			&& let Some(conn) = notes_db_path().and_then(|p| Connection::open(p).ok())
+		{
+			let bible = self.bible_source();
+			for editor in &mut self.note_editors {
+				match load_note(&conn, "notes", &editor.note.id) {
+					Ok(Some(note)) => *editor = NoteApp::new(note, bible.clone()),
+					Ok(None) => {}
+					Err(e) => eprintln!("重新读取笔记失败 id={}: {:?}", editor.note.id, e),
+				}
+			}
+		}
+		self.last_appended_notes_chapter = None;
+		if self.show_notes_list_window {
+			self.reload_notes_list();
+		}
+		self.pending_drafts = crate::drafts::load_drafts("notes");
+	}
+
+	pub fn show_crypto_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		if !self.show_crypto_window {
+			return;
+		}
+		let mut open = true;
+		let mut dialog = self.crypto_dialog.take().unwrap_or_default();
+		let mut unlock_now = false;
+		let mut change: Option<Option<String>> = None;
+
+		egui::Window::new(egui::RichText::new("🔐 笔记加密").size(14.0))
+			.open(&mut open)
+			.resizable(false)
+			.default_width(380.0)
+			.show(ctx, |ui| {
+				ui.label("加密后笔记正文、历史版本和草稿以密文保存，每次启动需输入密码。搜索加密的正文时逐条解密比对，笔记多时较慢；启用加密时，之前的备份也会用新密码加密；加密期间不进行笔记同步，导出文件仍是明文。忘记密码将无法找回笔记。");
+				ui.add_space(4.0);
+
+				if is_locked() {
+					ui.label("笔记已加密，尚未解锁：");
+					ui.add(egui::TextEdit::singleline(&mut dialog.passphrase).password(true).hint_text("密码"));
+					if ui.button("解锁").clicked() {
+						unlock_now = true;
+					}
+				} else {
+					ui.label(if is_encrypted() { "笔记已加密。" } else { "笔记未加密。" });
+					egui::Grid::new("crypto_grid").num_columns(2).spacing([12.0, 6.0]).show(ui, |ui| {
+						ui.label("新密码：");
+						ui.add(egui::TextEdit::singleline(&mut dialog.new_passphrase).password(true));
+						ui.end_row();
+						ui.label("再输入一次：");
+						ui.add(egui::TextEdit::singleline(&mut dialog.confirm_passphrase).password(true));
+						ui.end_row();
+					});
+					ui.horizontal(|ui| {
+						let label = if is_encrypted() { "修改密码" } else { "启用加密" };
+						if ui.button(label).clicked() {
+							if dialog.new_passphrase.is_empty() {
+								dialog.status = Some("密码不能为空".to_string());
+							} else if dialog.new_passphrase != dialog.confirm_passphrase {
+								dialog.status = Some("两次输入的密码不一致".to_string());
+							} else {
+								change = Some(Some(dialog.new_passphrase.clone()));
+							}
+						}
+						if is_encrypted() && ui.button("关闭加密").clicked() {
+							change = Some(None);
+						}
+					});
+				}
+				if let Some(status) = &dialog.status {
+					ui.label(egui::RichText::new(status).size(12.0).color(colors.comment_text_color));
+				}
+			});
+
+		if unlock_now {
+			match unlock("notes", &dialog.passphrase) {
+				Ok(()) => {
+					dialog = CryptoDialog { status: Some("已解锁".to_string()), ..Default::default() };
+					self.on_notes_unlocked();
+				}
+				Err(e) => {
+					dialog.status = Some(e);
+					dialog.passphrase.clear();
+				}
+			}
+		}
+
+		if let Some(passphrase) = change {
+			let was_encrypted = is_encrypted();
+			dialog.status = Some(match set_passphrase("notes", passphrase.as_deref()) {
+				Ok(()) => {
+					dialog.new_passphrase.clear();
+					dialog.confirm_passphrase.clear();
+					let mut msg = match passphrase {
+						Some(_) if is_encrypted() => "已设置新密码".to_string(),
+						_ => "已关闭加密".to_string(),
+					};
+					// 刚启用加密：之前的明文备份不能留在磁盘上
+					if !was_encrypted && is_encrypted() {
+						let (encrypted, removed) = encrypt_plain_backups("notes");
+						if encrypted > 0 {
+							msg.push_str(&format!("；之前的 {} 份备份已用新密码加密", encrypted));
+						}
+						if removed > 0 {
+							msg.push_str(&format!("；{} 份备份无法加密，已删除", removed));
+						}
+					}
+					// 改动后备份一次（加密后的备份同样是密文）
+					if let Err(e) = crate::backup::backup_notes() {
+						eprintln!("设置密码后备份失败: {}", e);
+					}
+					println!("{}", msg);
+					msg
+				}
+				Err(e) => {
+					eprintln!("设置笔记密码失败: {}", e);
+					e
+				}
+			});
+		}
+		if open {
+			self.crypto_dialog = Some(dialog);
+		} else {
+			self.show_crypto_window = false;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SALT: &[u8] = b"0123456789abcdef";
+
+	#[test]
+	fn round_trips_with_the_same_passphrase() {
+		let key = derive_key("正确的密码", SALT).unwrap();
+		let stored = encrypt_with(&key, "起初，神创造天地。").unwrap();
+		assert!(stored.starts_with(ENCRYPTED_PREFIX));
+		assert!(!stored.contains("神创造"));
+
+		let again = derive_key("正确的密码", SALT).unwrap();
+		assert_eq!(decrypt_with(Some(&again), &stored).as_deref(), Some("起初，神创造天地。"));
+	}
+
+	#[test]
+	fn same_text_encrypts_differently_each_time() {
+		let key = derive_key("pw", SALT).unwrap();
+		assert_ne!(encrypt_with(&key, "text").unwrap(), encrypt_with(&key, "text").unwrap());
+	}
+
+	#[test]
+	fn rejects_wrong_passphrase_and_missing_key() {
+		let key = derive_key("正确的密码", SALT).unwrap();
+		let stored = encrypt_with(&key, VERIFIER_PLAINTEXT).unwrap();
+
+		let wrong = derive_key("错误的密码", SALT).unwrap();
+		assert_eq!(decrypt_with(Some(&wrong), &stored), None);
+		// 同一密码、不同盐得到的也是另一把钥匙
+		let other_salt = derive_key("正确的密码", b"fedcba9876543210").unwrap();
+		assert_eq!(decrypt_with(Some(&other_salt), &stored), None);
+		assert_eq!(decrypt_with(None, &stored), None);
+	}
+
+	#[test]
+	fn rejects_damaged_ciphertext() {
+		let key = derive_key("pw", SALT).unwrap();
+		let stored = encrypt_with(&key, "text").unwrap();
+		// 改动密文中间的一个字符
+		let mut chars: Vec<char> = stored.chars().collect();
+		let mid = ENCRYPTED_PREFIX.len() + 20;
+		chars[mid] = if chars[mid] == 'A' { 'B' } else { 'A' };
+		let damaged: String = chars.into_iter().collect();
+		assert_eq!(decrypt_with(Some(&key), &damaged), None);
+		assert_eq!(decrypt_with(Some(&key), &format!("{}short", ENCRYPTED_PREFIX)), None);
+	}
+
+	#[test]
+	fn plain_text_passes_through() {
+		assert_eq!(decrypt_with(None, "明文笔记").as_deref(), Some("明文笔记"));
+	}
+}
@@ -0,0 +1,159 @@
+use rusqlite::Connection;
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::notes::{Notedb, notes_db_path, ensure_notes_schema, table_exists};
+use crate::crypto;
+use crate::utils::display_timestamp;
+
+/// 编辑中的笔记自动保存草稿的间隔（秒）
+pub const AUTOSAVE_INTERVAL: f64 = 5.0;
+
+fn drafts_table(category: &str) -> String {
+	format!("{}_drafts", category)
+}
+
+/// 建立草稿表：每条笔记（按 Notedb::id）最多一份草稿
+pub fn ensure_draft_schema(conn: &Connection, category: &str) -> rusqlite::Result<()> {
+	conn.execute_batch(&format!(
+		"CREATE TABLE IF NOT EXISTS {} (
+			note_id TEXT PRIMARY KEY,
+			note_json TEXT NOT NULL,
+			saved_at TEXT
+		);",
+		drafts_table(category)
+	))
+}
+
+/// 写入（覆盖）笔记草稿
+pub fn save_draft(category: &str, note: &Notedb, saved_at: &str) -> rusqlite::Result<()> {
+	if crypto::is_locked() {
+		return Ok(());
+	}
+	let Some(db_path) = notes_db_path() else { return Ok(()) };
+	let conn = Connection::open(&db_path)?;
+	ensure_notes_schema(&conn, category)?;
+
+	let json = serde_json::to_string(note)
+		.map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+	let json = crypto::encrypt_field(&json).map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+	conn.execute(
+		&format!(
+			"INSERT OR REPLACE INTO {} (note_id, note_json, saved_at) VALUES (?1, ?2, ?3)",
+			drafts_table(category)
+		),
+		[&note.id, &json, &saved_at.to_string()],
+	)?;
+	Ok(())
+}
+
+/// 笔记已保存或放弃修改后删除草稿
+pub fn remove_draft(category: &str, note_id: &str) -> rusqlite::Result<()> {
+	let Some(db_path) = notes_db_path() else { return Ok(()) };
+	let conn = Connection::open(&db_path)?;
+	if !table_exists(&conn, &drafts_table(category)) {
+		return Ok(());
+	}
+	conn.execute(&format!("DELETE FROM {} WHERE note_id = ?1", drafts_table(category)), [note_id])?;
+	Ok(())
+}
+
+/// 读取所有遗留草稿及其保存时间（新的在前）；笔记未解锁时不读取
+pub fn load_drafts(category: &str) -> Vec<(Notedb, String)> {
+	if crypto::is_locked() {
+		return Vec::new();
+	}
+	let Some(db_path) = notes_db_path() else { return Vec::new() };
+	let conn = match Connection::open(&db_path) {
+		Ok(c) => c,
+		Err(_) => return Vec::new(),
+	};
+	if !table_exists(&conn, &drafts_table(category)) {
+		return Vec::new();
+	}
+
+	let sql = format!(
+		"SELECT note_id, note_json, COALESCE(saved_at, '') FROM {} ORDER BY saved_at DESC",
+		drafts_table(category)
+	);
+	let mut stmt = match conn.prepare(&sql) {
+		Ok(s) => s,
+		Err(_) => return Vec::new(),
+	};
+	let rows = match stmt.query_map([], |row| {
+		Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+	}) {
+		Ok(rows) => rows,
+		Err(_) => return Vec::new(),
+	};
+
+	rows.flatten()
+		.filter_map(|(note_id, json, saved_at)| match serde_json::from_str::<Notedb>(&crypto::decrypt_field(&json)) {
+			Ok(note) => Some((note, saved_at)),
+			Err(e) => {
+				eprintln!("草稿损坏，无法恢复 id={}: {:?}", note_id, e);
+				None
+			}
+		})
+		.collect()
+}
+
+//草稿恢复窗口
+impl BibleApp {
+	/// 启动时发现未保存的草稿，询问是否恢复
+	pub fn show_draft_recovery_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		if self.pending_drafts.is_empty() {
+			return;
+		}
+
+		let mut recover: Option<usize> = None;
+		let mut discard: Option<usize> = None;
+		let mut discard_all = false;
+
+		egui::Window::new(egui::RichText::new("📝 恢复未保存的笔记").size(14.0))
+			.collapsible(false)
+			.resizable(false)
+			.anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+			.show(ctx, |ui| {
+				ui.label("上次关闭程序时，以下笔记还有未保存的修改：");
+				ui.add_space(4.0);
+				for (i, (note, saved_at)) in self.pending_drafts.iter().enumerate() {
+					ui.horizontal(|ui| {
+						ui.label(note.title.as_deref().filter(|t| !t.is_empty()).unwrap_or("<无标题>"));
+						ui.label(
+							egui::RichText::new(display_timestamp(saved_at))
+							.size(10.0)
+							.color(colors.comment_text_color),
+						);
+						ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+							if ui.button("丢弃").clicked() {
+								discard = Some(i);
+							}
+							if ui.button("恢复").clicked() {
+								recover = Some(i);
+							}
+						});
+					});
+				}
+				ui.separator();
+				if ui.button("全部丢弃").clicked() {
+					discard_all = true;
+				}
+			});
+
+		if let Some(i) = recover {
+			let (note, _) = self.pending_drafts.remove(i);
+			self.open_draft_editor(note);
+		} else if let Some(i) = discard {
+			let (note, _) = self.pending_drafts.remove(i);
+			if let Err(e) = remove_draft("notes", &note.id) {
+				eprintln!("删除草稿失败 id={}: {:?}", note.id, e);
+			}
+		} else if discard_all {
+			for (note, _) in self.pending_drafts.drain(..) {
+				if let Err(e) = remove_draft("notes", &note.id) {
+					eprintln!("删除草稿失败 id={}: {:?}", note.id, e);
+				}
+			}
+		}
+	}
+}
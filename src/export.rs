@@ -0,0 +1,602 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::notes::Notedb;
+use crate::note_list::{date_edit, in_date_range, parse_filter_date};
+use crate::notebooks::{self, Notebook};
+use crate::reference::book_full_name;
+use crate::tags::TAG_JOINER;
+use crate::utils::display_timestamp;
+
+/// JSON 导出文件的格式标识与版本
+pub const EXPORT_FORMAT: &str = "bible_reader.notes";
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// JSON 导出文件
+#[derive(Serialize, Deserialize)]
+pub struct NotesExport {
+	pub format: String,
+	pub schema_version: u32,
+	pub exported_at: String,
+	pub notes: Vec<ExportedNote>,
+}
+
+/// 导出文件中的一条笔记
+#[derive(Serialize, Deserialize, Default)]
+pub struct ExportedNote {
+	pub id: String,
+	pub title: Option<String>,
+	pub subject: Option<String>,
+	#[serde(default)]
+	pub tags: Vec<String>,
+	/// 笔记本路径，例如「讲道 / 2024」
+	pub notebook: Option<String>,
+	pub reference: Option<String>,
+	pub version: Option<String>,
+	pub book_num: Option<i32>,
+	pub book_name: Option<String>,
+	pub chapter: Option<String>,
+	pub created_at: Option<String>,
+	pub updated_at: Option<String>,
+	pub body: Option<String>,
+}
+
+impl ExportedNote {
+	fn from_note(note: &Notedb, notebooks: &[Notebook]) -> Self {
+		Self {
+			id: note.id.clone(),
+			title: note.title.clone(),
+			subject: note.subject.clone(),
+			tags: note.tags.clone(),
+			notebook: note.notebook_id.as_deref().map(|id| notebooks::notebook_path(notebooks, id)),
+			reference: note.reference.clone(),
+			version: note.version.clone(),
+			book_num: note.book_num,
+			book_name: note.book_name.clone(),
+			chapter: note.chapter.clone(),
+			created_at: note.created_at.clone(),
+			updated_at: note.updated_at.clone(),
+			body: note.body.clone(),
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+	/// 每条笔记一个 Markdown 文件
+	MarkdownFiles,
+	/// 按书卷 / 章分组的单个 Markdown 文档
+	MarkdownCombined,
+	/// 单个 HTML 文档，带打印样式，可在浏览器中打印为 PDF
+	Html,
+	Json,
+}
+
+impl ExportFormat {
+	const ALL: [ExportFormat; 4] = [
+		ExportFormat::MarkdownFiles,
+		ExportFormat::MarkdownCombined,
+		ExportFormat::Html,
+		ExportFormat::Json,
+	];
+
+	fn label(self) -> &'static str {
+		match self {
+			ExportFormat::MarkdownFiles => "Markdown（每条笔记一个文件）",
+			ExportFormat::MarkdownCombined => "Markdown（合并为一个文档）",
+			ExportFormat::Html => "HTML（可打印为 PDF）",
+			ExportFormat::Json => "JSON",
+		}
+	}
+}
+
+/// 导出窗口的状态
+pub struct ExportDialog {
+	format: ExportFormat,
+	/// true：只导出笔记列表当前显示的笔记（搜索 / 笔记本 / 标签筛选后）
+	only_listed: bool,
+	book_num: Option<i32>,
+	date_from: String,
+	date_to: String,
+	dest_dir: String,
+	status: Option<String>,
+}
+
+impl Default for ExportDialog {
+	fn default() -> Self {
+		let dest_dir = dirs::document_dir()
+			.or_else(dirs::home_dir)
+			.unwrap_or_default()
+			.join("bible_reader_export");
+		Self {
+			format: ExportFormat::MarkdownCombined,
+			only_listed: true,
+			book_num: None,
+			date_from: String::new(),
+			date_to: String::new(),
+			dest_dir: dest_dir.to_string_lossy().to_string(),
+			status: None,
+		}
+	}
+}
+
+impl ExportDialog {
+	/// 起止日期中格式不对的输入
+	fn invalid_dates(&self) -> (bool, bool) {
+		(parse_filter_date(&self.date_from).is_err(), parse_filter_date(&self.date_to).is_err())
+	}
+
+	/// 按书卷和日期（修改日期，没有则用创建日期）筛选
+	fn matches(&self, note: &Notedb) -> bool {
+		if self.book_num.is_some() && note.book_num != self.book_num {
+			return false;
+		}
+		in_date_range(note, &self.date_from, &self.date_to)
+	}
+}
+
+fn note_title(note: &Notedb) -> &str {
+	note.title.as_deref().filter(|t| !t.trim().is_empty()).unwrap_or("无标题")
+}
+
+/// 笔记所在位置，例如「约翰福音 3 章」
+fn note_location(note: &Notedb) -> Option<String> {
+	let book = note.book_num?;
+	Some(match &note.chapter {
+		Some(ch) => format!("{} {} 章", book_full_name(book), ch),
+		None => book_full_name(book).to_string(),
+	})
+}
+
+/// 笔记的元信息行（引用、译本、笔记本、标签、时间）
+fn note_meta(note: &Notedb, notebooks: &[Notebook]) -> Vec<(&'static str, String)> {
+	let mut meta = Vec::new();
+	if let Some(r) = note.reference.as_deref().filter(|r| !r.is_empty()) {
+		meta.push(("引用", r.to_string()));
+	}
+	if let Some(loc) = note_location(note) {
+		meta.push(("位置", loc));
+	}
+	if let Some(v) = note.version.as_deref().filter(|v| !v.is_empty()) {
+		meta.push(("译本", crate::utils::version_display_name(v)));
+	}
+	if let Some(id) = &note.notebook_id {
+		meta.push(("笔记本", notebooks::notebook_path(notebooks, id)));
+	}
+	if !note.tags.is_empty() {
+		meta.push(("标签", note.tags.join(TAG_JOINER)));
+	}
+	if let Some(c) = &note.created_at {
+		meta.push(("创建", display_timestamp(c)));
+	}
+	if let Some(u) = &note.updated_at {
+		meta.push(("修改", display_timestamp(u)));
+	}
+	meta
+}
+
+/// 文件名中不能出现的字符替换为下划线
+fn safe_file_name(name: &str) -> String {
+	let cleaned: String = name
+		.chars()
+		.map(|c| if "/\\:*?\"<>|\n\r\t".contains(c) { '_' } else { c })
+		.take(60)
+		.collect();
+	cleaned.trim().to_string()
+}
+
+fn yaml_string(s: &str) -> String {
+	format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// 单条笔记的 Markdown（带 YAML 头信息，便于其他笔记软件导入）
+fn note_to_markdown_file(note: &Notedb, notebooks: &[Notebook]) -> String {
+	let mut out = String::from("---\n");
+	out.push_str(&format!("id: {}\n", note.id));
+	out.push_str(&format!("title: {}\n", yaml_string(note_title(note))));
+	if let Some(s) = note.subject.as_deref().filter(|s| !s.is_empty()) {
+		out.push_str(&format!("subject: {}\n", yaml_string(s)));
+	}
+	if let Some(r) = note.reference.as_deref().filter(|r| !r.is_empty()) {
+		out.push_str(&format!("reference: {}\n", yaml_string(r)));
+	}
+	if let Some(v) = note.version.as_deref().filter(|v| !v.is_empty()) {
+		out.push_str(&format!("version: {}\n", yaml_string(v)));
+	}
+	if let Some(loc) = note_location(note) {
+		out.push_str(&format!("location: {}\n", yaml_string(&loc)));
+	}
+	if let Some(id) = &note.notebook_id {
+		out.push_str(&format!("notebook: {}\n", yaml_string(&notebooks::notebook_path(notebooks, id))));
+	}
+	if !note.tags.is_empty() {
+		let tags: Vec<String> = note.tags.iter().map(|t| yaml_string(t)).collect();
+		out.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+	}
+	if let Some(c) = &note.created_at {
+		out.push_str(&format!("created_at: {}\n", yaml_string(c)));
+	}
+	if let Some(u) = &note.updated_at {
+		out.push_str(&format!("updated_at: {}\n", yaml_string(u)));
+	}
+	out.push_str("---\n\n");
+	out.push_str(&format!("# {}\n\n", note_title(note)));
+	out.push_str(note.body.as_deref().unwrap_or("").trim_end());
+	out.push('\n');
+	out
+}
+
+/// 按书卷、章排序并分组；没有关联经文的笔记放在最后
+fn group_by_chapter(notes: &[Notedb]) -> Vec<(Option<i32>, Option<String>, Vec<&Notedb>)> {
+	let mut sorted: Vec<&Notedb> = notes.iter().collect();
+	sorted.sort_by_key(|n| (
+		n.book_num.unwrap_or(i32::MAX),
+		n.chapter.as_deref().and_then(|c| c.parse::<i32>().ok()).unwrap_or(0),
+		n.created_at.clone().unwrap_or_default(),
+	));
+
+	let mut groups: Vec<(Option<i32>, Option<String>, Vec<&Notedb>)> = Vec::new();
+	for note in sorted {
+		match groups.last_mut() {
+			Some((book, chapter, list)) if *book == note.book_num && *chapter == note.chapter => list.push(note),
+			_ => groups.push((note.book_num, note.chapter.clone(), vec![note])),
+		}
+	}
+	groups
+}
+
+fn notes_to_markdown_document(notes: &[Notedb], notebooks: &[Notebook], exported_at: &str) -> String {
+	let mut out = format!("# 圣经笔记\n\n导出时间：{}，共 {} 条笔记\n", exported_at, notes.len());
+	let mut last_book: Option<Option<i32>> = None;
+
+	for (book, chapter, list) in group_by_chapter(notes) {
+		if last_book != Some(book) {
+			let heading = book.map(book_full_name).unwrap_or("未关联经文");
+			out.push_str(&format!("\n# {}\n", heading));
+			last_book = Some(book);
+		}
+		if book.is_some() && let Some(ch) = &chapter {
+			out.push_str(&format!("\n## 第 {} 章\n", ch));
+		}
+		for note in list {
+			out.push_str(&format!("\n### {}\n\n", note_title(note)));
+			for (key, value) in note_meta(note, notebooks) {
+				out.push_str(&format!("- {}：{}\n", key, value));
+			}
+			out.push('\n');
+			out.push_str(note.body.as_deref().unwrap_or("").trim_end());
+			out.push('\n');
+		}
+	}
+	out
+}
+
+fn html_escape(s: &str) -> String {
+	s.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
+
+const HTML_STYLE: &str = "
+body { font-family: 'Source Han Sans CN', 'Noto Sans CJK SC', sans-serif; max-width: 760px; margin: 2em auto; line-height: 1.7; color: #222; }
+h1.book { border-bottom: 2px solid #888; margin-top: 2em; }
+h2.chapter { color: #555; }
+article { margin: 1.5em 0; padding-bottom: 1em; border-bottom: 1px solid #ddd; }
+.meta { color: #777; font-size: 0.85em; }
+.meta span { margin-right: 1.2em; }
+blockquote { border-left: 3px solid #bbb; margin-left: 0; padding-left: 1em; color: #555; }
+pre, code { background: #f4f4f4; }
+@page { size: A4; margin: 2cm; }
+@media print {
+	body { margin: 0; max-width: none; }
+	h1.book { page-break-before: always; }
+	h1.book:first-of-type { page-break-before: auto; }
+	article { page-break-inside: avoid; }
+}
+";
+
+fn notes_to_html(notes: &[Notedb], notebooks: &[Notebook], exported_at: &str) -> String {
+	let mut out = String::from("<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>圣经笔记</title>\n<style>");
+	out.push_str(HTML_STYLE);
+	out.push_str("</style>\n</head>\n<body>\n");
+	out.push_str(&format!(
+		"<h1>圣经笔记</h1>\n<p class=\"meta\">导出时间：{}，共 {} 条笔记</p>\n",
+		html_escape(exported_at),
+		notes.len()
+	));
+
+	let mut last_book: Option<Option<i32>> = None;
+	for (book, chapter, list) in group_by_chapter(notes) {
+		if last_book != Some(book) {
+			let heading = book.map(book_full_name).unwrap_or("未关联经文");
+			out.push_str(&format!("<h1 class=\"book\">{}</h1>\n", html_escape(heading)));
+			last_book = Some(book);
+		}
+		if book.is_some() && let Some(ch) = &chapter {
+			out.push_str(&format!("<h2 class=\"chapter\">第 {} 章</h2>\n", html_escape(ch)));
+		}
+		for note in list {
+			out.push_str("<article>\n");
+			out.push_str(&format!("<h3>{}</h3>\n<p class=\"meta\">", html_escape(note_title(note))));
+			for (key, value) in note_meta(note, notebooks) {
+				out.push_str(&format!("<span>{}：{}</span>", key, html_escape(&value)));
+			}
+			out.push_str("</p>\n");
+			let parser = pulldown_cmark::Parser::new_ext(
+				note.body.as_deref().unwrap_or(""),
+				pulldown_cmark::Options::ENABLE_STRIKETHROUGH | pulldown_cmark::Options::ENABLE_TASKLISTS,
+			)
+			// 正文可能来自导入或同步，其中的 HTML 原样输出会带进 <script> 等，一律按文字显示
+			.map(|event| match event {
+				pulldown_cmark::Event::Html(html) | pulldown_cmark::Event::InlineHtml(html) => pulldown_cmark::Event::Text(html),
+				other => other,
+			});
+			pulldown_cmark::html::push_html(&mut out, parser);
+			out.push_str("</article>\n");
+		}
+	}
+	out.push_str("</body>\n</html>\n");
+	out
+}
+
+pub fn notes_to_json(notes: &[Notedb], notebooks: &[Notebook], exported_at: &str) -> serde_json::Result<String> {
+	let export = NotesExport {
+		format: EXPORT_FORMAT.to_string(),
+		schema_version: EXPORT_SCHEMA_VERSION,
+		exported_at: exported_at.to_string(),
+		notes: notes.iter().map(|n| ExportedNote::from_note(n, notebooks)).collect(),
+	};
+	serde_json::to_string_pretty(&export)
+}
+
+/// 写出导出文件，返回生成的文件或目录
+pub fn export_notes(
+	format: ExportFormat,
+	notes: &[Notedb],
+	notebooks: &[Notebook],
+	dest_dir: &Path,
+) -> std::io::Result<PathBuf> {
+	fs::create_dir_all(dest_dir)?;
+	let now = chrono::Local::now();
+	let exported_at = now.format("%Y-%m-%d %H:%M:%S").to_string();
+	let stamp = now.format("%Y%m%d-%H%M%S").to_string();
+
+	let path = match format {
+		ExportFormat::MarkdownFiles => {
+			let dir = dest_dir.join(format!("notes-{}", stamp));
+			fs::create_dir_all(&dir)?;
+			for note in notes {
+				let short_id: String = note.id.chars().take(8).collect();
+				let name = format!("{}-{}.md", safe_file_name(note_title(note)), short_id);
+				fs::write(dir.join(name), note_to_markdown_file(note, notebooks))?;
+			}
+			dir
+		}
+		ExportFormat::MarkdownCombined => {
+			let file = dest_dir.join(format!("notes-{}.md", stamp));
+			fs::write(&file, notes_to_markdown_document(notes, notebooks, &exported_at))?;
+			file
+		}
+		ExportFormat::Html => {
+			let file = dest_dir.join(format!("notes-{}.html", stamp));
+			fs::write(&file, notes_to_html(notes, notebooks, &exported_at))?;
+			file
+		}
+		ExportFormat::Json => {
+			let file = dest_dir.join(format!("notes-{}.json", stamp));
+			let json = notes_to_json(notes, notebooks, &exported_at).map_err(std::io::Error::other)?;
+			fs::write(&file, json)?;
+			file
+		}
+	};
+	Ok(path)
+}
+
+//导出窗口
+impl BibleApp {
+	pub fn show_export_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(mut dialog) = self.export_dialog.take() else { return };
+
+		let mut open = true;
+		let mut run = false;
+
+		egui::Window::new(egui::RichText::new("📤 导出笔记").size(14.0))
+			.open(&mut open)
+			.resizable(false)
+			.default_width(420.0)
+			.show(ctx, |ui| {
+				egui::Grid::new("export_grid").num_columns(2).spacing([12.0, 8.0]).show(ui, |ui| {
+					ui.label("格式：");
+					egui::ComboBox::from_id_salt("export_format")
+						.selected_text(dialog.format.label())
+						.width(260.0)
+						.show_ui(ui, |ui| {
+							for f in ExportFormat::ALL {
+								ui.selectable_value(&mut dialog.format, f, f.label());
+							}
+						});
+					ui.end_row();
+
+					ui.label("范围：");
+					ui.horizontal(|ui| {
+						ui.radio_value(&mut dialog.only_listed, true, "笔记列表中显示的笔记");
+						ui.radio_value(&mut dialog.only_listed, false, "全部笔记");
+					});
+					ui.end_row();
+
+					ui.label("书卷：");
+					let book_text = dialog.book_num.map(book_full_name).unwrap_or("全部书卷");
+					egui::ComboBox::from_id_salt("export_book")
+						.selected_text(book_text)
+						.width(260.0)
+						.show_ui(ui, |ui| {
+							ui.selectable_value(&mut dialog.book_num, None, "全部书卷");
+							for (num, name) in &self.books {
+								ui.selectable_value(&mut dialog.book_num, Some(*num), name);
+							}
+						});
+					ui.end_row();
+
+					ui.label("日期：");
+					ui.horizontal(|ui| {
+						let (bad_from, bad_to) = dialog.invalid_dates();
+						ui.add(date_edit(&mut dialog.date_from, "起 2024-01-01", bad_from));
+						ui.label("至");
+						ui.add(date_edit(&mut dialog.date_to, "止 2024-12-31", bad_to));
+						if bad_from || bad_to {
+							ui.label(egui::RichText::new("格式应为 2024-01-01").size(12.0).color(egui::Color32::RED));
+						}
+					});
+					ui.end_row();
+
+					ui.label("保存到：");
+					ui.add(egui::TextEdit::singleline(&mut dialog.dest_dir).desired_width(260.0));
+					ui.end_row();
+				});
+
+				ui.separator();
+				ui.horizontal(|ui| {
+					if ui.button("导出").clicked() {
+						run = true;
+					}
+					if let Some(status) = &dialog.status {
+						ui.label(egui::RichText::new(status).size(12.0).color(colors.comment_text_color));
+					}
+				});
+			});
+
+		if run && crate::crypto::is_locked() {
+			dialog.status = Some("笔记已加密，请先解锁再导出".to_string());
+		} else if run && dialog.invalid_dates() != (false, false) {
+			dialog.status = Some("日期格式不对，应为 2024-01-01".to_string());
+		} else if run {
+			let source = if dialog.only_listed {
+				// 与笔记列表显示的一致（含笔记本、标签、译本、书卷和日期筛选）
+				self.listed_notes().into_iter().cloned().collect()
+			} else {
+				self.load_notes("notes", "all")
+			};
+			let notes: Vec<Notedb> = source.into_iter().filter(|n| dialog.matches(n)).collect();
+
+			dialog.status = Some(if notes.is_empty() {
+				"没有符合条件的笔记".to_string()
+			} else {
+				match export_notes(dialog.format, &notes, &self.notebooks, Path::new(dialog.dest_dir.trim())) {
+					Ok(path) => {
+						println!("已导出 {} 条笔记到 {:?}", notes.len(), path);
+						format!("已导出 {} 条笔记：{}", notes.len(), path.display())
+					}
+					Err(e) => {
+						eprintln!("导出笔记失败: {:?}", e);
+						format!("导出失败：{}", e)
+					}
+				}
+			});
+		}
+
+		if open {
+			self.export_dialog = Some(dialog);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn note(id: &str, book_num: Option<i32>, chapter: Option<&str>, created_at: &str) -> Notedb {
+		Notedb {
+			id: id.to_string(),
+			title: Some(id.to_string()),
+			book_num,
+			chapter: chapter.map(str::to_string),
+			created_at: Some(created_at.to_string()),
+			..Default::default()
+		}
+	}
+
+	fn sermons() -> Vec<Notebook> {
+		vec![
+			Notebook { id: "s".to_string(), name: "讲道".to_string(), parent_id: None },
+			Notebook { id: "y".to_string(), name: "2024".to_string(), parent_id: Some("s".to_string()) },
+		]
+	}
+
+	#[test]
+	fn cleans_file_names_and_yaml_strings() {
+		assert_eq!(safe_file_name(" 约3:16 / \"爱\" "), "约3_16 _ _爱_");
+		assert_eq!(safe_file_name(&"长".repeat(80)).chars().count(), 60);
+		assert_eq!(yaml_string(r#"他说"阿们"\"#), r#""他说\"阿们\"\\""#);
+	}
+
+	#[test]
+	fn writes_front_matter_for_single_files() {
+		let mut n = note("n1", Some(43), Some("3"), "2024-05-01");
+		n.notebook_id = Some("y".to_string());
+		n.tags = vec!["恩典".to_string(), "信心".to_string()];
+		n.body = Some("正文\n\n".to_string());
+		let md = note_to_markdown_file(&n, &sermons());
+		assert!(md.starts_with("---\nid: n1\ntitle: \"n1\"\n"));
+		assert!(md.contains("location: \"约翰福音 3 章\"\n"));
+		assert!(md.contains("notebook: \"讲道 / 2024\"\n"));
+		assert!(md.contains("tags: [\"恩典\", \"信心\"]\n"));
+		assert!(md.ends_with("---\n\n# n1\n\n正文\n"));
+	}
+
+	#[test]
+	fn groups_by_book_and_numeric_chapter_with_unlinked_last() {
+		let notes = vec![
+			note("free", None, None, "2024-01-01"),
+			note("jn10", Some(43), Some("10"), "2024-01-01"),
+			note("jn3b", Some(43), Some("3"), "2024-02-01"),
+			note("mt5", Some(40), Some("5"), "2024-01-01"),
+			note("jn3a", Some(43), Some("3"), "2024-01-01"),
+		];
+		let groups: Vec<Vec<&str>> = group_by_chapter(&notes).into_iter()
+			.map(|(_, _, list)| list.iter().map(|n| n.id.as_str()).collect())
+			.collect();
+		assert_eq!(groups, vec![vec!["mt5"], vec!["jn3a", "jn3b"], vec!["jn10"], vec!["free"]]);
+
+		let doc = notes_to_markdown_document(&notes, &[], "2024-06-01");
+		let headings: Vec<&str> = doc.lines().filter(|l| l.starts_with("# ") || l.starts_with("## ")).collect();
+		assert_eq!(headings, vec!["# 圣经笔记", "# 马太福音", "## 第 5 章", "# 约翰福音", "## 第 3 章", "## 第 10 章", "# 未关联经文"]);
+	}
+
+	#[test]
+	fn html_export_escapes_raw_html() {
+		let mut n = note("<b>标题</b>", None, None, "2024-01-01");
+		n.body = Some("**粗体**\n\n<script>alert(1)</script>".to_string());
+		let html = notes_to_html(&[n], &[], "2024-06-01");
+		assert!(html.contains("<h3>&lt;b&gt;标题&lt;/b&gt;</h3>"));
+		assert!(html.contains("<strong>粗体</strong>"));
+		assert!(!html.contains("<script>"));
+	}
+
+	#[test]
+	fn json_export_round_trips() {
+		let mut n = note("n1", Some(43), Some("3"), "2024-05-01");
+		n.notebook_id = Some("y".to_string());
+		n.body = Some("正文".to_string());
+		let json = notes_to_json(&[n], &sermons(), "2024-06-01").unwrap();
+		let export: NotesExport = serde_json::from_str(&json).unwrap();
+		assert_eq!((export.format.as_str(), export.schema_version), (EXPORT_FORMAT, EXPORT_SCHEMA_VERSION));
+		assert_eq!(export.notes.len(), 1);
+		assert_eq!(export.notes[0].notebook.as_deref(), Some("讲道 / 2024"));
+		assert_eq!(export.notes[0].body.as_deref(), Some("正文"));
+	}
+
+	#[test]
+	fn dialog_filters_by_book_and_date() {
+		let dialog = ExportDialog {
+			book_num: Some(43),
+			date_from: "2024-05-01".to_string(),
+			..Default::default()
+		};
+		assert!(dialog.matches(&note("a", Some(43), Some("3"), "2024-05-02")));
+		assert!(!dialog.matches(&note("b", Some(40), Some("5"), "2024-05-02")));
+		assert!(!dialog.matches(&note("c", Some(43), Some("3"), "2024-04-30")));
+	}
+}
@@ -0,0 +1,259 @@
+use std::fs;
+use std::path::PathBuf;
+use serde::Deserialize;
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::reference::{ScriptureRef, book_full_name, parse_reference};
+use crate::quote::load_passage;
+
+/// 四福音书的书卷编号：马太、马可、路加、约翰
+pub const GOSPEL_BOOKS: [i32; 4] = [40, 41, 42, 43];
+
+/// 内置的福音书对照数据，首次运行时复制到用户数据目录，可自行修改
+const BUILT_IN_HARMONY: &str = include_str!("../assets/data/gospel_harmony.json");
+
+#[derive(Deserialize)]
+struct PericopeEntry {
+	title: String,
+	matthew: Option<String>,
+	mark: Option<String>,
+	luke: Option<String>,
+	john: Option<String>,
+}
+
+/// 一个经段在四卷福音书中的对应经文
+#[derive(Clone)]
+pub struct Pericope {
+	pub title: String,
+	/// 依次为马太、马可、路加、约翰；没有记载的为 None
+	pub passages: [Option<ScriptureRef>; 4],
+}
+
+impl Pericope {
+	/// 是否涉及某卷某章
+	pub fn touches(&self, book_num: i32, chapter: i32) -> bool {
+		self.passages.iter().flatten().any(|r| r.book_num == book_num && r.chapter == chapter)
+	}
+}
+
+/// 对照数据文件：~/.local/share/bible_reader/gospel_harmony.json（不存在时写入内置数据）
+pub fn harmony_path() -> Option<PathBuf> {
+	let path = dirs::data_dir()?.join("bible_reader/gospel_harmony.json");
+	if !path.exists()
+		&& let Err(e) = path.parent()
+			.map_or(Ok(()), fs::create_dir_all)
+			.and_then(|_| fs::write(&path, BUILT_IN_HARMONY))
+	{
+		eprintln!("写入福音书对照数据失败 {:?}: {:?}", path, e);
+		return None;
+	}
+	Some(path)
+}
+
+fn parse_entry(entry: PericopeEntry) -> Pericope {
+	let parse = |text: Option<String>, book: i32| {
+		let text = text?;
+		match parse_reference(&text) {
+			Some(r) if r.book_num == book => Some(r),
+			_ => {
+				eprintln!("福音书对照「{}」中无法识别的经文：{}", entry.title, text);
+				None
+			}
+		}
+	};
+	Pericope {
+		passages: [
+			parse(entry.matthew.clone(), GOSPEL_BOOKS[0]),
+			parse(entry.mark.clone(), GOSPEL_BOOKS[1]),
+			parse(entry.luke.clone(), GOSPEL_BOOKS[2]),
+			parse(entry.john.clone(), GOSPEL_BOOKS[3]),
+		],
+		title: entry.title,
+	}
+}
+
+/// 读取对照数据；文件有误时退回内置数据
+pub fn load_harmony() -> Vec<Pericope> {
+	let text = harmony_path()
+		.and_then(|p| fs::read_to_string(p).ok())
+		.unwrap_or_else(|| BUILT_IN_HARMONY.to_string());
+	let entries: Vec<PericopeEntry> = match serde_json::from_str(&text) {
+		Ok(entries) => entries,
+		Err(e) => {
+			eprintln!("福音书对照数据格式不正确，使用内置数据: {:?}", e);
+			serde_json::from_str(BUILT_IN_HARMONY).unwrap_or_default()
+		}
+	};
+	entries.into_iter().map(parse_entry).collect()
+}
+
+/// 福音书对照窗口的状态
+pub struct HarmonyWindow {
+	pub selected: usize,
+	filter: String,
+	/// 已读取经文的（经段, 译本）
+	texts_key: Option<(usize, String)>,
+	/// 四栏的经文：（节, 经文）
+	texts: Vec<Option<Vec<(i32, String)>>>,
+	/// 对照数据文件的位置，打开窗口时取一次
+	data_path: Option<PathBuf>,
+}
+
+impl HarmonyWindow {
+	pub fn new(selected: usize) -> Self {
+		Self { selected, filter: String::new(), texts_key: None, texts: Vec::new(), data_path: harmony_path() }
+	}
+}
+
+//福音书对照
+impl BibleApp {
+	/// 打开对照窗口并选中某个经段
+	pub fn open_harmony(&mut self, index: usize) {
+		match &mut self.harmony_window {
+			Some(window) => window.selected = index,
+			None => self.harmony_window = Some(HarmonyWindow::new(index)),
+		}
+	}
+
+	/// 当前章涉及的对照经段（只在福音书中）
+	pub fn chapter_pericopes(&self) -> Vec<(usize, &Pericope)> {
+		let (Some(book), Some(chap)) = (self.current_book, self.current_chapter.as_deref()) else { return Vec::new() };
+		if !GOSPEL_BOOKS.contains(&book) {
+			return Vec::new();
+		}
+		let chapter: i32 = chap.parse().unwrap_or(0);
+		self.gospel_harmony.iter().enumerate().filter(|(_, p)| p.touches(book, chapter)).collect()
+	}
+
+	pub fn show_harmony_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(mut window) = self.harmony_window.take() else { return };
+
+		let mut open = true;
+		let mut go_to: Option<ScriptureRef> = None;
+		let source = self.bible_source();
+		let count = self.gospel_harmony.len();
+		window.selected = window.selected.min(count.saturating_sub(1));
+
+		egui::Window::new(egui::RichText::new("📖 福音书对照").size(14.0))
+			.open(&mut open)
+			.resizable(true)
+			.default_size([900.0, 560.0])
+			.show(ctx, |ui| {
+				if count == 0 {
+					ui.label(egui::RichText::new("没有对照数据").color(colors.comment_text_color));
+					return;
+				}
+				ui.horizontal(|ui| {
+					if ui.add_enabled(window.selected > 0, egui::Button::new("◀")).clicked() {
+						window.selected -= 1;
+					}
+					egui::ComboBox::from_id_salt("harmony_pericope")
+						.selected_text(format!("{}. {}", window.selected + 1, self.gospel_harmony[window.selected].title))
+						.width(240.0)
+						.height(420.0)
+						.show_ui(ui, |ui| {
+							ui.add(egui::TextEdit::singleline(&mut window.filter).hint_text("筛选经段"));
+							let filter = window.filter.trim();
+							for (i, p) in self.gospel_harmony.iter().enumerate() {
+								if !filter.is_empty() && !p.title.contains(filter) {
+									continue;
+								}
+								ui.selectable_value(&mut window.selected, i, format!("{}. {}", i + 1, p.title));
+							}
+						});
+					if ui.add_enabled(window.selected + 1 < count, egui::Button::new("▶")).clicked() {
+						window.selected += 1;
+					}
+					if let Some(path) = &window.data_path {
+						ui.label(egui::RichText::new(format!("数据：{}", path.display())).size(11.0).color(colors.comment_text_color));
+					}
+				});
+				ui.separator();
+
+				let pericope = &self.gospel_harmony[window.selected];
+				let key = (window.selected, source.current_version.clone());
+				if window.texts_key.as_ref() != Some(&key) {
+					window.texts = pericope.passages.iter()
+						.map(|r| r.as_ref().and_then(|r| load_passage(&source, &source.current_version, r)))
+						.collect();
+					window.texts_key = Some(key);
+				}
+				let texts = &window.texts;
+				ui.columns(4, |columns| {
+					for (i, column) in columns.iter_mut().enumerate() {
+						column.label(egui::RichText::new(book_full_name(GOSPEL_BOOKS[i])).strong());
+						let Some(r) = &pericope.passages[i] else {
+							column.label(egui::RichText::new("（未记载）").color(colors.comment_text_color));
+							continue;
+						};
+						if column.link(r.display()).on_hover_text("在主窗格中打开").clicked() {
+							go_to = Some(r.clone());
+						}
+						egui::ScrollArea::vertical().id_salt(("harmony_column", i)).auto_shrink([false; 2]).show(column, |ui| {
+							match texts.get(i).and_then(|t| t.as_ref()) {
+								Some(verses) => {
+									for (verse, text) in verses {
+										ui.label(egui::RichText::new(format!("{} {}", verse, text)).color(colors.text_color));
+									}
+								}
+								None => {
+									ui.label(egui::RichText::new("当前译本中没有这段经文").color(colors.comment_text_color));
+								}
+							}
+						});
+					}
+				});
+			});
+
+		if let Some(r) = go_to {
+			self.go_to_reference(&r);
+		}
+
+		if open {
+			self.harmony_window = Some(window);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entry(title: &str, matthew: Option<&str>, john: Option<&str>) -> PericopeEntry {
+		PericopeEntry {
+			title: title.to_string(),
+			matthew: matthew.map(str::to_string),
+			mark: None,
+			luke: None,
+			john: john.map(str::to_string),
+		}
+	}
+
+	#[test]
+	fn built_in_data_parses_completely() {
+		let entries: Vec<PericopeEntry> = serde_json::from_str(BUILT_IN_HARMONY).unwrap();
+		assert!(!entries.is_empty());
+		for entry in entries {
+			let given = [&entry.matthew, &entry.mark, &entry.luke, &entry.john].map(|t| t.is_some());
+			let title = entry.title.clone();
+			let pericope = parse_entry(entry);
+			assert_eq!(pericope.passages.each_ref().map(|p| p.is_some()), given, "{}", title);
+		}
+	}
+
+	#[test]
+	fn drops_references_to_the_wrong_gospel() {
+		let pericope = parse_entry(entry("登山宝训", Some("路6:20-49"), Some("约3:16")));
+		assert!(pericope.passages[0].is_none());
+		assert_eq!(pericope.passages[3].as_ref().map(|r| (r.book_num, r.chapter)), Some((43, 3)));
+	}
+
+	#[test]
+	fn matches_pericopes_to_chapters() {
+		let pericope = parse_entry(entry("五饼二鱼", Some("太14:13-21"), Some("约6:1-14")));
+		assert!(pericope.touches(40, 14));
+		assert!(pericope.touches(43, 6));
+		assert!(!pericope.touches(43, 14));
+		assert!(!pericope.touches(41, 6));
+	}
+}
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use rusqlite::Connection;
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::reference::{ScriptureRef, book_full_name};
+use crate::quote::load_passage;
+use crate::userdata::open_user_db;
+use crate::utils::{now_timestamp, display_timestamp, verse_byte_ranges};
+
+/// 可选的高亮颜色：（保存在数据库中的名字, 默认说明, 背景色）
+pub const HIGHLIGHT_COLORS: [(&str, &str, [u8; 4]); 5] = [
+	("yellow", "黄色", [255, 214, 0, 90]),
+	("green", "绿色", [80, 200, 80, 80]),
+	("blue", "蓝色", [70, 150, 255, 80]),
+	("pink", "粉色", [255, 110, 170, 80]),
+	("purple", "紫色", [160, 100, 230, 80]),
+];
+
+/// 一条经文高亮
+#[derive(Clone)]
+pub struct Highlight {
+	pub book_num: i32,
+	pub chapter: i32,
+	pub verse: i32,
+	pub color: String,
+	pub created_at: String,
+}
+
+impl Highlight {
+	pub fn reference(&self) -> ScriptureRef {
+		ScriptureRef {
+			book_num: self.book_num,
+			chapter: self.chapter,
+			verse_start: Some(self.verse),
+			verse_end: None,
+		}
+	}
+}
+
+pub fn highlight_color(color: &str) -> egui::Color32 {
+	let rgba = HIGHLIGHT_COLORS
+		.iter()
+		.find(|(name, _, _)| *name == color)
+		.map(|(_, _, rgba)| *rgba)
+		.unwrap_or(HIGHLIGHT_COLORS[0].2);
+	egui::Color32::from_rgba_unmultiplied(rgba[0], rgba[1], rgba[2], rgba[3])
+}
+
+/// 高亮按书卷、章、节保存，与译本无关
+pub fn ensure_highlight_schema(conn: &Connection) -> rusqlite::Result<()> {
+	conn.execute_batch(
+		"CREATE TABLE IF NOT EXISTS highlights (
+			book_num INTEGER NOT NULL,
+			chapter INTEGER NOT NULL,
+			verse INTEGER NOT NULL,
+			color TEXT NOT NULL,
+			created_at TEXT,
+			PRIMARY KEY (book_num, chapter, verse)
+		);
+		CREATE TABLE IF NOT EXISTS highlight_labels (
+			color TEXT PRIMARY KEY,
+			label TEXT NOT NULL
+		);",
+	)
+}
+
+/// 设置或清除（color 为 None）一节的高亮
+pub fn set_highlight(book_num: i32, chapter: i32, verse: i32, color: Option<&str>) -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	match color {
+		Some(color) => conn.execute(
+			"INSERT OR REPLACE INTO highlights (book_num, chapter, verse, color, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+			rusqlite::params![book_num, chapter, verse, color, now_timestamp()],
+		)?,
+		None => conn.execute(
+			"DELETE FROM highlights WHERE book_num = ?1 AND chapter = ?2 AND verse = ?3",
+			rusqlite::params![book_num, chapter, verse],
+		)?,
+	};
+	Ok(())
+}
+
+/// 一章中各节的高亮颜色
+pub fn load_chapter_highlights(book_num: i32, chapter: i32) -> HashMap<i32, String> {
+	let Ok(Some(conn)) = open_user_db() else { return HashMap::new() };
+	let Ok(mut stmt) = conn.prepare("SELECT verse, color FROM highlights WHERE book_num = ?1 AND chapter = ?2") else {
+		return HashMap::new();
+	};
+	match stmt.query_map([book_num, chapter], |row| Ok((row.get(0)?, row.get(1)?))) {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => HashMap::new(),
+	}
+}
+
+/// 全部高亮，按经文顺序
+pub fn load_all_highlights() -> Vec<Highlight> {
+	let Ok(Some(conn)) = open_user_db() else { return Vec::new() };
+	let Ok(mut stmt) = conn.prepare(
+		"SELECT book_num, chapter, verse, color, COALESCE(created_at, '') FROM highlights
+		 ORDER BY book_num, chapter, verse",
+	) else {
+		return Vec::new();
+	};
+	let rows = stmt.query_map([], |row| {
+		Ok(Highlight {
+			book_num: row.get(0)?,
+			chapter: row.get(1)?,
+			verse: row.get(2)?,
+			color: row.get(3)?,
+			created_at: row.get(4)?,
+		})
+	});
+	match rows {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
+/// 用户给各颜色起的名字（例如 黄色 =「应许」）
+pub fn load_color_labels() -> HashMap<String, String> {
+	let Ok(Some(conn)) = open_user_db() else { return HashMap::new() };
+	let Ok(mut stmt) = conn.prepare("SELECT color, label FROM highlight_labels") else { return HashMap::new() };
+	match stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))) {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => HashMap::new(),
+	}
+}
+
+pub fn set_color_label(color: &str, label: &str) -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	if label.trim().is_empty() {
+		conn.execute("DELETE FROM highlight_labels WHERE color = ?1", [color])?;
+	} else {
+		conn.execute(
+			"INSERT OR REPLACE INTO highlight_labels (color, label) VALUES (?1, ?2)",
+			[color, label.trim()],
+		)?;
+	}
+	Ok(())
+}
+
+/// 颜色的显示名：有自定义名字时显示「黄色 · 应许」
+pub fn color_display_name(color: &str, labels: &HashMap<String, String>) -> String {
+	let default = HIGHLIGHT_COLORS
+		.iter()
+		.find(|(name, _, _)| *name == color)
+		.map(|(_, label, _)| *label)
+		.unwrap_or(color);
+	match labels.get(color) {
+		Some(label) => format!("{} · {}", default, label),
+		None => default.to_string(),
+	}
+}
+
+/// 给排版好的正文加上背景色；已有背景（搜索命中）的部分保持不变
+pub fn apply_background(job: &mut egui::text::LayoutJob, ranges: &[(Range<usize>, egui::Color32)]) {
+	if ranges.is_empty() {
+		return;
+	}
+	let mut sections = Vec::with_capacity(job.sections.len());
+	for section in job.sections.drain(..) {
+		let mut cuts = vec![section.byte_range.start, section.byte_range.end];
+		for (range, _) in ranges {
+			for pos in [range.start, range.end] {
+				if pos > section.byte_range.start && pos < section.byte_range.end {
+					cuts.push(pos);
+				}
+			}
+		}
+		cuts.sort_unstable();
+		cuts.dedup();
+		for (i, pair) in cuts.windows(2).enumerate() {
+			let mut piece = section.clone();
+			piece.byte_range = pair[0]..pair[1];
+			if i > 0 {
+				piece.leading_space = 0.0;
+			}
+			if piece.format.background == egui::Color32::TRANSPARENT
+				&& let Some((_, color)) = ranges.iter().find(|(r, _)| r.start <= pair[0] && pair[1] <= r.end)
+			{
+				piece.format.background = *color;
+			}
+			sections.push(piece);
+		}
+	}
+	job.sections = sections;
+}
+
+/// 高亮浏览窗口的状态
+#[derive(Default)]
+pub struct HighlightBrowser {
+	highlights: Vec<Highlight>,
+	labels: HashMap<String, String>,
+	/// 正在编辑的颜色名字
+	label_inputs: HashMap<String, String>,
+	color_filter: Option<String>,
+	book_filter: Option<i32>,
+	/// 预览用的经文：（译本, 书卷, 章, 节）→ 经文
+	verse_texts: HashMap<(String, i32, i32, i32), String>,
+}
+
+impl HighlightBrowser {
+	pub fn new() -> Self {
+		let labels = load_color_labels();
+		Self {
+			highlights: load_all_highlights(),
+			label_inputs: labels.clone(),
+			labels,
+			..Default::default()
+		}
+	}
+}
+
+/// 浏览窗口中的操作
+enum HighlightAction {
+	GoTo(ScriptureRef),
+	Remove(Highlight),
+	SaveLabel(String),
+}
+
+//经文高亮
+impl BibleApp {
+	/// 设置右键所在节的高亮，并刷新正文
+	pub fn set_verse_highlight(&mut self, r: &ScriptureRef, color: Option<&str>) {
+		let Some(verse) = r.verse_start else { return };
+		if let Err(e) = set_highlight(r.book_num, r.chapter, verse, color) {
+			eprintln!("保存高亮失败: {:?}", e);
+		}
+		self.reload_chapter_highlights();
+		if let Some(browser) = &mut self.highlight_browser {
+			browser.highlights = load_all_highlights();
+		}
+	}
+
+	/// 重新读取当前章的高亮，下一帧重新排版正文
+	pub fn reload_chapter_highlights(&mut self) {
+		self.chapter_highlights = match (self.current_book, self.current_chapter.as_deref()) {
+			(Some(book), Some(chapter)) => load_chapter_highlights(book, chapter.parse().unwrap_or(1)),
+			_ => HashMap::new(),
+		};
+		self.content_layout = None;
+	}
+
+	/// 当前章各节高亮的字节范围与颜色
+	pub fn highlight_ranges(&self) -> Vec<(Range<usize>, egui::Color32)> {
+		if self.chapter_highlights.is_empty() {
+			return Vec::new();
+		}
+		verse_byte_ranges(&self.content)
+			.into_iter()
+			.filter_map(|(verse, range)| self.chapter_highlights.get(&verse).map(|c| (range, highlight_color(c))))
+			.collect()
+	}
+
+	pub fn show_highlight_browser(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(mut browser) = self.highlight_browser.take() else { return };
+
+		let mut open = true;
+		let mut action: Option<HighlightAction> = None;
+		let source = self.bible_source();
+
+		egui::Window::new(egui::RichText::new("🖍 经文高亮").size(14.0))
+			.open(&mut open)
+			.resizable(true)
+			.default_size([460.0, 480.0])
+			.show(ctx, |ui| {
+				// ===== 颜色与名字 =====
+				egui::CollapsingHeader::new("颜色名字").default_open(false).show(ui, |ui| {
+					for (name, default_label, _) in HIGHLIGHT_COLORS {
+						ui.horizontal(|ui| {
+							let (rect, _) = ui.allocate_exact_size(egui::vec2(16.0, 16.0), egui::Sense::hover());
+							ui.painter().rect_filled(rect, egui::CornerRadius::same(3), highlight_color(name));
+							ui.label(default_label);
+							let input = browser.label_inputs.entry(name.to_string()).or_default();
+							let response = ui.add(egui::TextEdit::singleline(input).hint_text("例如：应许").desired_width(140.0));
+							if response.lost_focus() && browser.labels.get(name) != Some(input) {
+								action = Some(HighlightAction::SaveLabel(name.to_string()));
+							}
+						});
+					}
+				});
+
+				// ===== 筛选 =====
+				ui.horizontal(|ui| {
+					egui::ComboBox::from_id_salt("highlight_color_filter")
+						.selected_text(browser.color_filter.as_deref().map(|c| color_display_name(c, &browser.labels)).unwrap_or("全部颜色".to_string()))
+						.show_ui(ui, |ui| {
+							ui.selectable_value(&mut browser.color_filter, None, "全部颜色");
+							for (name, _, _) in HIGHLIGHT_COLORS {
+								let label = color_display_name(name, &browser.labels);
+								ui.selectable_value(&mut browser.color_filter, Some(name.to_string()), label);
+							}
+						});
+					let mut books: Vec<i32> = browser.highlights.iter().map(|h| h.book_num).collect();
+					books.dedup();
+					egui::ComboBox::from_id_salt("highlight_book_filter")
+						.selected_text(browser.book_filter.map(book_full_name).unwrap_or("全部书卷"))
+						.show_ui(ui, |ui| {
+							ui.selectable_value(&mut browser.book_filter, None, "全部书卷");
+							for b in books {
+								ui.selectable_value(&mut browser.book_filter, Some(b), book_full_name(b));
+							}
+						});
+				});
+				ui.separator();
+
+				let visible: Vec<Highlight> = browser.highlights.iter()
+					.filter(|h| browser.color_filter.as_deref().is_none_or(|c| h.color == c))
+					.filter(|h| browser.book_filter.is_none_or(|b| h.book_num == b))
+					.cloned()
+					.collect();
+				if visible.is_empty() {
+					ui.label(egui::RichText::new("还没有高亮（在正文上右键可高亮一节）").color(colors.comment_text_color));
+				}
+
+				egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+					for h in visible {
+						let key = (source.current_version.clone(), h.book_num, h.chapter, h.verse);
+						let text = browser.verse_texts.entry(key).or_insert_with(|| {
+							load_passage(&source, &source.current_version, &h.reference())
+								.and_then(|p| p.into_iter().next())
+								.map(|(_, t)| t)
+								.unwrap_or_default()
+						});
+						ui.horizontal(|ui| {
+							let (rect, _) = ui.allocate_exact_size(egui::vec2(10.0, 16.0), egui::Sense::hover());
+							ui.painter().rect_filled(rect, egui::CornerRadius::same(2), highlight_color(&h.color));
+							let link = ui.link(h.reference().display());
+							if link.on_hover_text(format!("高亮于 {}", display_timestamp(&h.created_at))).clicked() {
+								action = Some(HighlightAction::GoTo(h.reference()));
+							}
+							ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+								if ui.small_button("✖").on_hover_text("清除高亮").clicked() {
+									action = Some(HighlightAction::Remove(h.clone()));
+								}
+								ui.add(egui::Label::new(egui::RichText::new(text.as_str()).size(13.0)).truncate());
+							});
+						});
+					}
+				});
+			});
+
+		match action {
+			Some(HighlightAction::GoTo(r)) => self.go_to_reference(&r),
+			Some(HighlightAction::Remove(h)) => {
+				if let Err(e) = set_highlight(h.book_num, h.chapter, h.verse, None) {
+					eprintln!("清除高亮失败: {:?}", e);
+				}
+				browser.highlights = load_all_highlights();
+				self.reload_chapter_highlights();
+			}
+			Some(HighlightAction::SaveLabel(color)) => {
+				let label = browser.label_inputs.get(&color).cloned().unwrap_or_default();
+				if let Err(e) = set_color_label(&color, &label) {
+					eprintln!("保存颜色名字失败: {:?}", e);
+				}
+				self.color_labels = load_color_labels();
+				browser.labels = self.color_labels.clone();
+			}
+			None => {}
+		}
+
+		if open {
+			self.highlight_browser = Some(browser);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::userdata::test_user_db;
+
+	#[test]
+	fn sets_and_clears_verse_highlights() {
+		let _db = test_user_db();
+		set_highlight(43, 3, 16, Some("yellow")).unwrap();
+		set_highlight(43, 3, 17, Some("green")).unwrap();
+		set_highlight(1, 1, 1, Some("blue")).unwrap();
+		set_highlight(43, 3, 16, Some("pink")).unwrap();
+		set_highlight(43, 3, 17, None).unwrap();
+
+		assert_eq!(load_chapter_highlights(43, 3), HashMap::from([(16, "pink".to_string())]));
+		let all: Vec<(i32, i32, i32)> = load_all_highlights().iter().map(|h| (h.book_num, h.chapter, h.verse)).collect();
+		assert_eq!(all, vec![(1, 1, 1), (43, 3, 16)]);
+	}
+
+	#[test]
+	fn names_colors_with_user_labels() {
+		let _db = test_user_db();
+		set_color_label("yellow", " 应许 ").unwrap();
+		set_color_label("green", "命令").unwrap();
+		set_color_label("green", "").unwrap();
+		let labels = load_color_labels();
+		assert_eq!(color_display_name("yellow", &labels), "黄色 · 应许");
+		assert_eq!(color_display_name("green", &labels), "绿色");
+		assert_eq!(color_display_name("unknown", &labels), "unknown");
+		assert_eq!(highlight_color("unknown"), highlight_color("yellow"));
+	}
+
+	#[test]
+	fn background_keeps_search_hits() {
+		let hit = egui::Color32::RED;
+		let mark = egui::Color32::GREEN;
+		let mut job = egui::text::LayoutJob::default();
+		job.append("abc", 0.0, egui::TextFormat::default());
+		job.append("def", 0.0, egui::TextFormat { background: hit, ..Default::default() });
+
+		apply_background(&mut job, &[(1..5, mark)]);
+		let sections: Vec<(Range<usize>, egui::Color32)> = job.sections.iter()
+			.map(|s| (s.byte_range.clone(), s.format.background))
+			.collect();
+		assert_eq!(sections, vec![
+			(0..1, egui::Color32::TRANSPARENT),
+			(1..3, mark),
+			(3..5, hit),
+			(5..6, hit),
+		]);
+	}
+}
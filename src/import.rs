@@ -0,0 +1,626 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use rusqlite::{Connection, OpenFlags};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::notes::{Notedb, NOTE_COLUMNS, note_from_row, column_exists, table_exists, write_note};
+use crate::notebooks::{self, read_notebooks};
+use crate::export::{NotesExport, EXPORT_FORMAT};
+use crate::reference::{book_full_name, parse_reference};
+use crate::tags::{self, split_keywords};
+use crate::utils::{now_timestamp, compare_timestamps, display_timestamp};
+
+/// 导入来源
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImportSource {
+	/// 本程序导出的 JSON
+	Json,
+	/// 带 YAML 头信息的 Markdown 文件夹
+	MarkdownFolder,
+	/// 另一个 note.db
+	NoteDb,
+}
+
+impl ImportSource {
+	fn label(self) -> &'static str {
+		match self {
+			ImportSource::Json => "JSON 文件",
+			ImportSource::MarkdownFolder => "Markdown 文件夹",
+			ImportSource::NoteDb => "其他 note.db",
+		}
+	}
+
+	fn hint(self) -> &'static str {
+		match self {
+			ImportSource::Json => "导出的 notes-xxxx.json 文件路径",
+			ImportSource::MarkdownFolder => "存放 .md 文件的文件夹路径",
+			ImportSource::NoteDb => "note.db 文件路径",
+		}
+	}
+}
+
+/// 待导入的笔记；笔记本用路径表示，导入时在本地找到或新建
+pub struct IncomingNote {
+	pub note: Notedb,
+	pub notebook_path: Option<String>,
+}
+
+/// 与本地笔记比较的结果
+pub enum ImportStatus {
+	/// 本地没有，直接导入
+	New,
+	/// 内容与本地某条笔记相同（id 不同），跳过
+	Duplicate(String),
+	/// id 相同且内容相同，跳过
+	Unchanged,
+	/// id 相同但内容和修改时间不同，由用户选择保留哪一边
+	Conflict { local: Box<Notedb>, use_imported: bool },
+}
+
+pub struct ImportItem {
+	pub incoming: IncomingNote,
+	pub status: ImportStatus,
+}
+
+/// 笔记内容的哈希（标题、引用、正文），用来发现 id 不同的重复笔记
+pub fn content_hash(note: &Notedb) -> String {
+	let mut hasher = Sha256::new();
+	for part in [&note.title, &note.reference, &note.body] {
+		hasher.update(part.as_deref().unwrap_or("").trim().as_bytes());
+		hasher.update([0u8]);
+	}
+	format!("{:x}", hasher.finalize())
+}
+
+/// 读取本程序导出的 JSON
+fn read_json(path: &Path) -> Result<Vec<IncomingNote>, String> {
+	let text = fs::read_to_string(path).map_err(|e| format!("无法读取文件：{}", e))?;
+	let export: NotesExport = serde_json::from_str(&text).map_err(|e| format!("JSON 格式不正确：{}", e))?;
+	if export.format != EXPORT_FORMAT {
+		return Err(format!("不是笔记导出文件（format = {}）", export.format));
+	}
+
+	Ok(export.notes.into_iter().map(|n| IncomingNote {
+		note: Notedb {
+			id: if n.id.is_empty() { Uuid::new_v4().to_string() } else { n.id },
+			book_num: n.book_num,
+			book_name: n.book_name,
+			chapter: n.chapter,
+			verse_start: -1,
+			char_offset: Some(0),
+			title: n.title,
+			keywords: None,
+			reference: n.reference,
+			body: n.body,
+			subject: n.subject,
+			version: n.version,
+			created_at: n.created_at,
+			updated_at: n.updated_at,
+			notebook_id: None,
+			deleted_at: None,
+			tags: n.tags,
+		},
+		notebook_path: n.notebook,
+	}).collect())
+}
+
+/// 去掉导出时加上的引号与转义
+fn yaml_value(raw: &str) -> String {
+	let raw = raw.trim();
+	match raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+		Some(inner) => inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+		None => raw.to_string(),
+	}
+}
+
+/// 解析 `tags: ["a", "b"]` 或 `tags: a, b`
+fn yaml_list(raw: &str) -> Vec<String> {
+	let raw = raw.trim();
+	let inner = raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')).unwrap_or(raw);
+	let mut items = Vec::new();
+	let mut current = String::new();
+	let mut in_quotes = false;
+	let mut escaped = false;
+	for c in inner.chars() {
+		match c {
+			_ if escaped => {
+				current.push(c);
+				escaped = false;
+			}
+			'\\' if in_quotes => escaped = true,
+			'"' => in_quotes = !in_quotes,
+			',' if !in_quotes => items.push(std::mem::take(&mut current)),
+			_ => current.push(c),
+		}
+	}
+	items.push(current);
+	items.into_iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+/// 解析一个 Markdown 笔记；没有头信息时用第一个标题或文件名作标题
+fn parse_markdown_note(text: &str, file_stem: &str) -> IncomingNote {
+	let mut note = Notedb {
+		verse_start: -1,
+		char_offset: Some(0),
+		..Default::default()
+	};
+	let mut notebook_path = None;
+	let mut body = text;
+
+	if let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n"))
+		&& let Some(end) = rest.find("\n---")
+	{
+		for line in rest[..end].lines() {
+			let Some((key, value)) = line.split_once(':') else { continue };
+			let value = value.trim();
+			match key.trim() {
+				"id" => note.id = yaml_value(value),
+				"title" => note.title = Some(yaml_value(value)),
+				"subject" => note.subject = Some(yaml_value(value)),
+				"reference" => note.reference = Some(yaml_value(value)),
+				"version" => note.version = Some(yaml_value(value)),
+				"notebook" => notebook_path = Some(yaml_value(value)),
+				"tags" => note.tags = yaml_list(value),
+				"created_at" => note.created_at = Some(yaml_value(value)),
+				"updated_at" => note.updated_at = Some(yaml_value(value)),
+				"location" => {
+					// 「约翰福音 3 章」
+					let location = yaml_value(value);
+					if let Some(r) = parse_reference(location.trim_end_matches('章').trim()) {
+						note.book_num = Some(r.book_num);
+						note.book_name = Some(book_full_name(r.book_num).to_string());
+						note.chapter = Some(r.chapter.to_string());
+					}
+				}
+				_ => {}
+			}
+		}
+		// 只去掉结尾的 `---` 这一行，正文本身可能以 `-`（列表）开头
+		let closing = &rest[end + 1..];
+		let closing = closing.strip_prefix("---").unwrap_or(closing);
+		body = closing.strip_prefix("\r\n").or_else(|| closing.strip_prefix('\n')).unwrap_or(closing);
+		// 头信息与正文之间的空行
+		body = body.trim_start_matches(['\r', '\n']);
+	}
+
+	// 正文第一行是与标题相同的一级标题时去掉（导出时加上的）
+	if let Some(first) = body.lines().next()
+		&& let Some(heading) = first.strip_prefix("# ")
+	{
+		if note.title.is_none() {
+			note.title = Some(heading.trim().to_string());
+		}
+		if note.title.as_deref() == Some(heading.trim()) {
+			body = body[first.len()..].trim_start_matches(['\r', '\n']);
+		}
+	}
+	if note.title.is_none() {
+		note.title = Some(file_stem.to_string());
+	}
+	if note.id.is_empty() {
+		note.id = Uuid::new_v4().to_string();
+	}
+	note.body = Some(body.trim_end().to_string());
+
+	IncomingNote { note, notebook_path }
+}
+
+fn read_markdown_folder(dir: &Path) -> Result<Vec<IncomingNote>, String> {
+	let entries = fs::read_dir(dir).map_err(|e| format!("无法打开文件夹：{}", e))?;
+	let mut paths: Vec<_> = entries
+		.flatten()
+		.map(|e| e.path())
+		.filter(|p| p.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("md")))
+		.collect();
+	paths.sort();
+
+	let mut notes = Vec::new();
+	for path in paths {
+		match fs::read_to_string(&path) {
+			Ok(text) => {
+				let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("无标题");
+				notes.push(parse_markdown_note(&text, stem));
+			}
+			Err(e) => eprintln!("跳过无法读取的文件 {:?}: {:?}", path, e),
+		}
+	}
+	Ok(notes)
+}
+
+/// 读取另一个 note.db（只读打开，不修改对方的数据库）
+fn read_note_db(path: &Path) -> Result<Vec<IncomingNote>, String> {
+	let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+		.map_err(|e| format!("无法打开数据库：{}", e))?;
+	let category = "notes";
+	if !table_exists(&conn, category) {
+		return Err("数据库中没有笔记表".to_string());
+	}
+	if crate::crypto::load_crypto_params(&conn, category).ok().flatten().is_some() {
+		return Err("该数据库已加密，无法导入".to_string());
+	}
+
+	// 旧版本数据库缺少的列按 NULL 读取
+	let columns: Vec<String> = NOTE_COLUMNS
+		.split(',')
+		.map(str::trim)
+		.map(|c| if column_exists(&conn, category, c) { c.to_string() } else { format!("NULL AS {}", c) })
+		.collect();
+	let deleted_filter = if column_exists(&conn, category, "deleted_at") { "WHERE deleted_at IS NULL" } else { "" };
+	let sql = format!("SELECT {} FROM {} {}", columns.join(", "), category, deleted_filter);
+
+	let mut notes: Vec<Notedb> = {
+		let mut stmt = conn.prepare(&sql).map_err(|e| format!("读取笔记失败：{}", e))?;
+		let rows = stmt.query_map([], note_from_row).map_err(|e| format!("读取笔记失败：{}", e))?;
+		rows.flatten().collect()
+	};
+	tags::attach_tags(&conn, category, &mut notes);
+	let foreign_notebooks = read_notebooks(&conn, category);
+
+	Ok(notes.into_iter().map(|mut note| {
+		if note.tags.is_empty() {
+			note.tags = split_keywords(note.keywords.as_deref().unwrap_or(""));
+		}
+		let notebook_path = note.notebook_id.take()
+			.map(|id| notebooks::notebook_path(&foreign_notebooks, &id))
+			.filter(|p| !p.is_empty());
+		IncomingNote { note, notebook_path }
+	}).collect())
+}
+
+pub fn read_import_source(source: ImportSource, path: &Path) -> Result<Vec<IncomingNote>, String> {
+	match source {
+		ImportSource::Json => read_json(path),
+		ImportSource::MarkdownFolder => read_markdown_folder(path),
+		ImportSource::NoteDb => read_note_db(path),
+	}
+}
+
+/// 与本地笔记逐条比较：先按 id 合并，再按内容哈希查重
+pub fn classify_import(incoming: Vec<IncomingNote>, local: &[Notedb]) -> Vec<ImportItem> {
+	let by_id: HashMap<&str, &Notedb> = local.iter().map(|n| (n.id.as_str(), n)).collect();
+	let mut hashes: HashMap<String, String> = local
+		.iter()
+		.map(|n| (content_hash(n), n.title.clone().unwrap_or_default()))
+		.collect();
+	let mut seen_ids: HashSet<String> = HashSet::new();
+
+	incoming.into_iter().filter_map(|inc| {
+		// 同一批中重复的 id 只取第一条
+		if !seen_ids.insert(inc.note.id.clone()) {
+			return None;
+		}
+		let hash = content_hash(&inc.note);
+		let status = match by_id.get(inc.note.id.as_str()) {
+			Some(local) if content_hash(local) == hash => ImportStatus::Unchanged,
+			Some(local) => {
+				let imported_newer = compare_timestamps(inc.note.updated_at.as_deref(), local.updated_at.as_deref()).is_gt();
+				ImportStatus::Conflict { local: Box::new((*local).clone()), use_imported: imported_newer }
+			}
+			None => match hashes.get(&hash) {
+				Some(title) => ImportStatus::Duplicate(title.clone()),
+				None => {
+					hashes.insert(hash, inc.note.title.clone().unwrap_or_default());
+					ImportStatus::New
+				}
+			},
+		};
+		Some(ImportItem { incoming: inc, status })
+	}).collect()
+}
+
+/// 写入选中的笔记，返回（新增, 覆盖, 失败）条数；笔记库未解锁时不导入
+pub fn apply_import(items: Vec<ImportItem>) -> Result<(usize, usize, usize), String> {
+	if crate::crypto::is_locked() {
+		return Err("笔记已加密，请先解锁再导入".to_string());
+	}
+	let mut added = 0;
+	let mut replaced = 0;
+	let mut failed = 0;
+	let mut notebook_ids: HashMap<String, Option<String>> = HashMap::new();
+	let now = now_timestamp();
+
+	for item in items {
+		let is_new = match item.status {
+			ImportStatus::New => true,
+			ImportStatus::Conflict { use_imported: true, .. } => false,
+			_ => continue,
+		};
+		let mut note = item.incoming.note;
+		if let Some(path) = item.incoming.notebook_path {
+			let id = notebook_ids.entry(path.clone()).or_insert_with(|| {
+				notebooks::ensure_notebook_path("notes", &path).unwrap_or_else(|e| {
+					eprintln!("创建笔记本「{}」失败: {:?}", path, e);
+					None
+				})
+			});
+			note.notebook_id = id.clone();
+		}
+		let updated_at = note.updated_at.clone().filter(|u| !u.is_empty()).unwrap_or_else(|| now.clone());
+		match write_note("notes", &note, &updated_at) {
+			Ok(()) if is_new => added += 1,
+			Ok(()) => replaced += 1,
+			Err(e) => {
+				eprintln!("导入笔记失败 id={}: {}", note.id, e);
+				failed += 1;
+			}
+		}
+	}
+	Ok((added, replaced, failed))
+}
+
+/// 导入窗口的状态
+pub struct ImportDialog {
+	source: ImportSource,
+	path: String,
+	items: Vec<ImportItem>,
+	status: Option<String>,
+}
+
+impl Default for ImportDialog {
+	fn default() -> Self {
+		Self {
+			source: ImportSource::Json,
+			path: String::new(),
+			items: Vec::new(),
+			status: None,
+		}
+	}
+}
+
+fn note_label(note: &Notedb) -> String {
+	let title = note.title.as_deref().filter(|t| !t.is_empty()).unwrap_or("<无标题>");
+	match note.reference.as_deref().filter(|r| !r.is_empty()) {
+		Some(r) => format!("{}（{}）", title, r),
+		None => title.to_string(),
+	}
+}
+
+//导入窗口
+impl BibleApp {
+	pub fn show_import_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(mut dialog) = self.import_dialog.take() else { return };
+
+		let mut open = true;
+		let mut read = false;
+		let mut run = false;
+
+		egui::Window::new(egui::RichText::new("📥 导入笔记").size(14.0))
+			.open(&mut open)
+			.resizable(true)
+			.default_size([560.0, 420.0])
+			.show(ctx, |ui| {
+				ui.horizontal(|ui| {
+					for source in [ImportSource::Json, ImportSource::MarkdownFolder, ImportSource::NoteDb] {
+						if ui.radio_value(&mut dialog.source, source, source.label()).changed() {
+							dialog.items.clear();
+						}
+					}
+				});
+				ui.horizontal(|ui| {
+					ui.add(
+						egui::TextEdit::singleline(&mut dialog.path)
+						.hint_text(dialog.source.hint())
+						.desired_width(ui.available_width() - 60.0),
+					);
+					if ui.button("读取").clicked() {
+						read = true;
+					}
+				});
+				if let Some(status) = &dialog.status {
+					ui.label(egui::RichText::new(status).size(12.0).color(colors.comment_text_color));
+				}
+				if dialog.items.is_empty() {
+					return;
+				}
+
+				let count = |f: fn(&ImportStatus) -> bool| dialog.items.iter().filter(|i| f(&i.status)).count();
+				ui.separator();
+				ui.label(format!(
+					"新笔记 {} 条，冲突 {} 条，重复 {} 条，未变化 {} 条",
+					count(|s| matches!(s, ImportStatus::New)),
+					count(|s| matches!(s, ImportStatus::Conflict { .. })),
+					count(|s| matches!(s, ImportStatus::Duplicate(_))),
+					count(|s| matches!(s, ImportStatus::Unchanged)),
+				));
+
+				egui::ScrollArea::vertical().auto_shrink([false; 2]).max_height(ui.available_height() - 40.0).show(ui, |ui| {
+					for (i, item) in dialog.items.iter_mut().enumerate() {
+						let note = &item.incoming.note;
+						ui.push_id(i, |ui| match &mut item.status {
+							ImportStatus::New => {
+								ui.label(format!("➕ {}", note_label(note)));
+							}
+							ImportStatus::Duplicate(local_title) => {
+								ui.label(
+									egui::RichText::new(format!("⏭ {}（与本地「{}」内容相同）", note_label(note), local_title))
+									.color(colors.comment_text_color),
+								);
+							}
+							ImportStatus::Unchanged => {
+								ui.label(
+									egui::RichText::new(format!("⏭ {}（未变化）", note_label(note)))
+									.color(colors.comment_text_color),
+								);
+							}
+							ImportStatus::Conflict { local, use_imported } => {
+								ui.label(egui::RichText::new(format!("⚠ {}", note_label(note))).strong());
+								ui.horizontal(|ui| {
+									ui.add_space(16.0);
+									ui.radio_value(
+										use_imported,
+										false,
+										format!("保留本地（修改于 {}）", local.updated_at.as_deref().map(display_timestamp).unwrap_or("未知".to_string())),
+									);
+									ui.radio_value(
+										use_imported,
+										true,
+										format!("使用导入（修改于 {}）", note.updated_at.as_deref().map(display_timestamp).unwrap_or("未知".to_string())),
+									);
+								});
+							}
+						});
+					}
+				});
+
+				ui.separator();
+				if ui.button("导入").clicked() {
+					run = true;
+				}
+			});
+
+		// 未解锁时本地正文是占位文字，查重的结果不可靠
+		if (read || run) && crate::crypto::is_locked() {
+			dialog.status = Some("笔记已加密，请先解锁再导入".to_string());
+		} else if read {
+			let path = dialog.path.trim().to_string();
+			match read_import_source(dialog.source, Path::new(&path)) {
+				Ok(incoming) => {
+					let mut local = self.load_notes("notes", "all");
+					local.extend(self.load_notes("notes", "trash"));
+					dialog.status = Some(format!("读取到 {} 条笔记", incoming.len()));
+					dialog.items = classify_import(incoming, &local);
+				}
+				Err(e) => {
+					eprintln!("读取导入文件失败 {}: {}", path, e);
+					dialog.status = Some(e);
+					dialog.items.clear();
+				}
+			}
+		} else if run {
+			match apply_import(std::mem::take(&mut dialog.items)) {
+				Ok((added, replaced, failed)) => {
+					println!("导入笔记：新增 {} 条，覆盖 {} 条，失败 {} 条", added, replaced, failed);
+					dialog.status = Some(if failed > 0 {
+						format!("已导入：新增 {} 条，覆盖 {} 条；{} 条写入失败", added, replaced, failed)
+					} else {
+						format!("已导入：新增 {} 条，覆盖 {} 条", added, replaced)
+					});
+				}
+				Err(e) => {
+					eprintln!("导入笔记失败: {}", e);
+					dialog.status = Some(e);
+				}
+			}
+			self.last_appended_notes_chapter = None;
+			if self.show_notes_list_window {
+				self.reload_notes_list();
+			}
+		}
+
+		if open {
+			self.import_dialog = Some(dialog);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn local_note(id: &str, title: &str, body: &str, updated_at: &str) -> Notedb {
+		Notedb {
+			id: id.to_string(),
+			title: Some(title.to_string()),
+			body: Some(body.to_string()),
+			updated_at: Some(updated_at.to_string()),
+			..Default::default()
+		}
+	}
+
+	fn incoming(note: Notedb) -> IncomingNote {
+		IncomingNote { note, notebook_path: None }
+	}
+
+	#[test]
+	fn parses_front_matter() {
+		let text = "---\nid: abc\ntitle: \"神爱世人\"\nreference: 约 3:16\nnotebook: 福音书/约翰\ntags: [\"爱\", \"救恩\"]\nlocation: 约翰福音 3 章\nupdated_at: 2024-05-01T08:00:00+08:00\n---\n\n# 神爱世人\n\n正文第一段\n";
+		let inc = parse_markdown_note(text, "file");
+		let note = &inc.note;
+		assert_eq!(note.id, "abc");
+		assert_eq!(note.title.as_deref(), Some("神爱世人"));
+		assert_eq!(note.reference.as_deref(), Some("约 3:16"));
+		assert_eq!(note.tags, vec!["爱".to_string(), "救恩".to_string()]);
+		assert_eq!(note.book_num, Some(43));
+		assert_eq!(note.chapter.as_deref(), Some("3"));
+		assert_eq!(note.updated_at.as_deref(), Some("2024-05-01T08:00:00+08:00"));
+		assert_eq!(inc.notebook_path.as_deref(), Some("福音书/约翰"));
+		// 与标题相同的一级标题已去掉
+		assert_eq!(note.body.as_deref(), Some("正文第一段"));
+	}
+
+	#[test]
+	fn keeps_list_body_after_front_matter() {
+		let text = "---\ntitle: 清单\n---\n- item one\n- item two\n";
+		let note = parse_markdown_note(text, "file").note;
+		assert_eq!(note.title.as_deref(), Some("清单"));
+		assert_eq!(note.body.as_deref(), Some("- item one\n- item two"));
+	}
+
+	#[test]
+	fn handles_crlf_front_matter() {
+		let text = "---\r\ntitle: 清单\r\n---\r\n- item one\r\n- item two\r\n";
+		let note = parse_markdown_note(text, "file").note;
+		assert_eq!(note.title.as_deref(), Some("清单"));
+		assert_eq!(note.body.as_deref(), Some("- item one\r\n- item two"));
+	}
+
+	#[test]
+	fn without_front_matter_uses_heading_or_file_name() {
+		let note = parse_markdown_note("# 标题\n\n正文", "file").note;
+		assert_eq!(note.title.as_deref(), Some("标题"));
+		assert_eq!(note.body.as_deref(), Some("正文"));
+		assert!(!note.id.is_empty());
+
+		let note = parse_markdown_note("- item one\n- item two", "文件名").note;
+		assert_eq!(note.title.as_deref(), Some("文件名"));
+		assert_eq!(note.body.as_deref(), Some("- item one\n- item two"));
+	}
+
+	#[test]
+	fn parses_yaml_lists() {
+		assert_eq!(yaml_list("[\"a, b\", \"c\"]"), vec!["a, b".to_string(), "c".to_string()]);
+		assert_eq!(yaml_list("a, b"), vec!["a".to_string(), "b".to_string()]);
+		assert!(yaml_list("[]").is_empty());
+	}
+
+	#[test]
+	fn classifies_new_duplicate_and_unchanged_notes() {
+		let local = vec![local_note("1", "甲", "正文甲", "2024-05-01")];
+		let items = classify_import(
+			vec![
+				incoming(local_note("1", "甲", "正文甲", "2024-05-01")),
+				incoming(local_note("2", "甲", "正文甲", "2024-05-01")),
+				incoming(local_note("3", "乙", "正文乙", "2024-05-01")),
+				// 同一批中与上一条内容相同
+				incoming(local_note("4", "乙", "正文乙", "2024-05-01")),
+				// 同一批中重复的 id
+				incoming(local_note("3", "丙", "正文丙", "2024-05-01")),
+			],
+			&local,
+		);
+		assert_eq!(items.len(), 4);
+		assert!(matches!(items[0].status, ImportStatus::Unchanged));
+		assert!(matches!(&items[1].status, ImportStatus::Duplicate(title) if title == "甲"));
+		assert!(matches!(items[2].status, ImportStatus::New));
+		assert!(matches!(&items[3].status, ImportStatus::Duplicate(title) if title == "乙"));
+	}
+
+	#[test]
+	fn conflicts_prefer_the_newer_side() {
+		let local = vec![local_note("1", "甲", "旧正文", "2024-05-02T00:00:00+00:00")];
+		let items = classify_import(
+			vec![incoming(local_note("1", "甲", "新正文", "2024-05-03T00:00:00+00:00"))],
+			&local,
+		);
+		assert!(matches!(items[0].status, ImportStatus::Conflict { use_imported: true, .. }));
+
+		let items = classify_import(
+			vec![incoming(local_note("1", "甲", "新正文", "2024-05-01T00:00:00+00:00"))],
+			&local,
+		);
+		assert!(matches!(items[0].status, ImportStatus::Conflict { use_imported: false, .. }));
+	}
+}
@@ -3,7 +3,31 @@
 mod theme;
 mod utils;
 mod notes;
+mod note_list;
 mod note_app;
+mod tags;
+mod notebooks;
+mod revisions;
+mod reference;
+mod markdown;
+mod drafts;
+mod quote;
+mod export;
+mod import;
+mod settings;
+mod sync;
+mod backup;
+mod crypto;
+mod userdata;
+mod highlights;
+mod bookmarks;
+mod reading_plan;
+mod reading_log;
+mod tabs;
+mod split_view;
+mod harmony;
+mod outline;
+mod shortcuts;
 use std::fs;
 use rusqlite::Connection;
 use eframe::egui;
@@ -11,9 +35,7 @@ use egui::{FontDefinitions, FontFamily, FontId, TextStyle, TextFormat};
 use egui::text::LayoutJob;
 use std::path::PathBuf;
 use std::collections::HashMap;
-use serde_json;
 use uuid::Uuid;
-use chrono::Utc;
 use crate::theme::{Theme, ThemeColors, apply_theme};
 use crate::utils::{
 	load_books,
@@ -26,9 +48,31 @@ use crate::utils::{
 	book_number_to_abbr,
 	highlight_search_terms,
 	draw_hover_button,
+	verse_at_char,
 };
-use crate::notes::{Notedb};
-use crate::note_app::NoteApp;
+use crate::notes::{Notedb, TrashConfirm};
+use crate::note_app::{NoteApp, NoteEditorAction};
+use crate::notebooks::{Notebook, NotebookFilter, NotebookEdit};
+use crate::revisions::Revision;
+use crate::reference::ScriptureRef;
+use crate::quote::BibleSource;
+use crate::export::ExportDialog;
+use crate::import::ImportDialog;
+use crate::note_list::NoteListView;
+use crate::settings::Settings;
+use crate::backup::BackupDialog;
+use crate::crypto::CryptoDialog;
+use crate::highlights::HighlightBrowser;
+use crate::bookmarks::{Bookmark, BookmarkEdit};
+use crate::reading_plan::ReadingPlanDialog;
+use crate::reading_log::{ChapterVisit, ReadingHistoryDialog};
+use crate::tabs::ReaderTab;
+use crate::split_view::SplitPane;
+use crate::harmony::{HarmonyWindow, Pericope};
+use crate::shortcuts::{ShortcutAction, KeyBinding, ShortcutsDialog, GoToDialog};
+
+/// 后退 / 前进记录最多保留的条数
+const MAX_JUMP_HISTORY: usize = 100;
 
 /// 应用状态
 struct BibleApp {
@@ -51,10 +95,48 @@ struct BibleApp {
 	highlight_query: Option<String>,
 	jump_back_stack: Vec<(String, i32, String)>,   // 译本, 书卷, 章节
 	jump_forward_stack: Vec<(String, i32, String)>,
+	/// 阅读标签页；当前标签页的状态保存在上面的字段中，切换时写回
+	tabs: Vec<ReaderTab>,
+	active_tab: usize,
+	/// 正文当前的滚动位置，以及切换标签页后要恢复的位置
+	content_scroll_offset: f32,
+	pending_scroll: Option<f32>,
+	/// 分屏对照的副窗格
+	split_pane: Option<SplitPane>,
+	/// 福音书对照数据与窗口
+	pub gospel_harmony: Vec<Pericope>,
+	pub harmony_window: Option<HarmonyWindow>,
+	/// 当前书卷的大纲（小标题）与简介，按（译本, 书卷）缓存
+	book_outline: crate::outline::BookOutline,
+	book_intro: Option<String>,
+	book_outline_key: Option<(String, i32)>,
+	show_book_overview: bool,
+	/// 当前的快捷键设置
+	shortcut_bindings: Vec<(ShortcutAction, KeyBinding)>,
+	shortcuts_dialog: Option<ShortcutsDialog>,
+	goto_dialog: Option<GoToDialog>,
+	/// 下一帧让搜索框获得焦点（Ctrl+F）
+	focus_search: bool,
 	show_version_menu: bool,
 	change_version_menu: bool,
 	show_settings_menu: bool,
 	show_highlight: bool,
+	/// 当前章的经文高亮：节号 → 颜色
+	pub chapter_highlights: HashMap<i32, String>,
+	/// 用户给高亮颜色起的名字
+	pub color_labels: HashMap<String, String>,
+	pub highlight_browser: Option<HighlightBrowser>,
+	pub bookmarks: Vec<Bookmark>,
+	pub show_bookmarks_panel: bool,
+	pub bookmark_edit: Option<BookmarkEdit>,
+	pub reading_plan_dialog: Option<ReadingPlanDialog>,
+	/// 当前读经计划中已读完的章：（书卷, 章）
+	pub plan_read_chapters: std::collections::HashSet<(i32, i32)>,
+	/// 正在阅读的章（换章时写入阅读记录）
+	chapter_visit: Option<ChapterVisit>,
+	/// 阅读记录中算作读过的章
+	pub read_chapters: std::collections::HashSet<(i32, i32)>,
+	pub reading_history: Option<ReadingHistoryDialog>,
 	pub show_notes: bool,
 	pub last_appended_notes_chapter: Option<(String, i32, String)>,
 	pub appended_notes_current: Vec<Notedb>,
@@ -63,6 +145,41 @@ struct BibleApp {
 	pub note_window_open: bool,
 	pub current_note: Option<Notedb>,
 	pub notes_search_keyword: String,
+	pub notes_highlight_terms: Vec<String>,
+	pub notes_tag_counts: Vec<(String, usize)>,
+	pub notes_tag_filter: Option<String>,
+	pub tag_rename: Option<(String, String)>,
+	pub notebooks: Vec<Notebook>,
+	pub notes_notebook_filter: NotebookFilter,
+	pub notebook_edit: Option<NotebookEdit>,
+	pub notes_list_view: NoteListView,
+	pub notes_show_trash: bool,
+	pub trash_cache: Vec<Notedb>,
+	pub trash_confirm: Option<TrashConfirm>,
+	pub history_note: Option<Notedb>,
+	pub history_revisions: Vec<Revision>,
+	pub history_selected: Option<usize>,
+	/// 恢复历史版本失败的原因
+	pub history_error: Option<String>,
+	note_editors: Vec<NoteApp>,
+	/// 右键菜单对应的经节范围（起止节）
+	context_verses: Option<(i32, i32)>,
+	/// 正文中拖动选中的字符范围（起点，终点）
+	selection_chars: Option<(usize, usize)>,
+	pub export_dialog: Option<ExportDialog>,
+	pub import_dialog: Option<ImportDialog>,
+	pub settings: Settings,
+	show_sync_window: bool,
+	sync_folder_input: String,
+	sync_status: Option<String>,
+	sync_job: Option<std::sync::mpsc::Receiver<Result<crate::sync::SyncReport, String>>>,
+	pub backup_dialog: Option<BackupDialog>,
+	show_crypto_window: bool,
+	crypto_dialog: Option<CryptoDialog>,
+	/// 启动时笔记库已加密，弹出解锁窗口
+	unlock_prompt: bool,
+	/// 上次未保存的笔记草稿（启动时询问是否恢复）
+	pending_drafts: Vec<(Notedb, String)>,
 	pub active_search_type: String,
 	editable_mode: bool,
 	content_layout: Option<egui::text::LayoutJob>, 
@@ -152,6 +269,10 @@ impl BibleApp {
 			let preferred_version = "和合本.sqlite3".to_string();
 
 
+			let settings = crate::settings::load_settings();
+			crate::crypto::init("notes");
+			let sync_folder_input = settings.sync_folder.clone().unwrap_or_default();
+
 			// 先创建 app（不加载书卷）
 			let mut app = Self {
 				theme: Theme::Light,
@@ -173,6 +294,21 @@ impl BibleApp {
 				highlight_query: None,
 				jump_back_stack: Vec::new(),     
 				jump_forward_stack: Vec::new(),  
+				tabs: Vec::new(),
+				active_tab: 0,
+				content_scroll_offset: 0.0,
+				pending_scroll: None,
+				split_pane: None,
+				gospel_harmony: crate::harmony::load_harmony(),
+				harmony_window: None,
+				book_outline: Default::default(),
+				book_intro: None,
+				book_outline_key: None,
+				show_book_overview: false,
+				shortcut_bindings: crate::shortcuts::load_bindings(&settings.shortcuts),
+				shortcuts_dialog: None,
+				goto_dialog: None,
+				focus_search: false,
 				show_notes: false,
 				last_appended_notes_chapter: None, 
 				appended_notes_current: Vec::new(),
@@ -181,16 +317,68 @@ impl BibleApp {
 				change_version_menu: false,
 				show_settings_menu: false,
 				show_highlight: false,
+				chapter_highlights: HashMap::new(),
+				color_labels: crate::highlights::load_color_labels(),
+				highlight_browser: None,
+				bookmarks: crate::bookmarks::load_bookmarks(),
+				show_bookmarks_panel: false,
+				bookmark_edit: None,
+				reading_plan_dialog: None,
+				plan_read_chapters: settings.active_reading_plan.as_deref()
+					.map(crate::reading_plan::read_chapters)
+					.unwrap_or_default(),
+				chapter_visit: None,
+				read_chapters: crate::reading_log::load_read_chapters(),
+				reading_history: None,
 				show_notes_list_window: false,
 				notes_cache: Vec::new(),
 				note_window_open: false,
 				notes_search_keyword: String::new(),
+				notes_highlight_terms: Vec::new(),
+				notes_tag_counts: Vec::new(),
+				notes_tag_filter: None,
+				tag_rename: None,
+				notebooks: Vec::new(),
+				notes_notebook_filter: NotebookFilter::All,
+				notebook_edit: None,
+				notes_list_view: NoteListView::default(),
+				notes_show_trash: false,
+				trash_cache: Vec::new(),
+				trash_confirm: None,
+				history_note: None,
+				history_revisions: Vec::new(),
+				history_selected: None,
+				history_error: None,
+				note_editors: Vec::new(),
+				context_verses: None,
+				selection_chars: None,
+				export_dialog: None,
+				import_dialog: None,
+				settings,
+				show_sync_window: false,
+				sync_folder_input,
+				sync_status: None,
+				sync_job: None,
+				backup_dialog: None,
+				show_crypto_window: false,
+				crypto_dialog: None,
+				unlock_prompt: crate::crypto::is_locked(),
+				pending_drafts: crate::drafts::load_drafts("notes"),
 				active_search_type: String::new(),
 				editable_mode: false,
 				content_layout: None,
 				last_processed_key: String::new(),
 			};
 
+			// 启动时先备份笔记数据库，再合并其他设备的笔记变更
+			crate::backup::apply_backup_settings(&app.settings);
+			if crate::notes::notes_db_path().is_some_and(|p| p.exists())
+				&& let Err(e) = crate::backup::backup_notes()
+			{
+				eprintln!("启动时备份笔记失败: {}", e);
+			}
+			app.run_sync();
+
 			// 若没有任何圣经数据库，就不加载，直接返回 app
 			if app.versions.is_empty() {
 				eprintln!("Warning: 未找到任何圣经数据库文件 (*.db / *.sqlite3)");
@@ -215,6 +403,8 @@ impl BibleApp {
 
 				app.on_version_changed(version_to_load);
 			}
+			// 恢复上次打开的标签页
+			app.restore_tabs();
 			app
 		}
 	}
@@ -302,6 +492,7 @@ fn perform_search(&mut self) -> rusqlite::Result<()> {
 impl BibleApp {
 	fn ui_left_books_panel(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
 		let mut selected_book: Option<i32> = None;
+		let read = self.all_read_chapters();
 
 		egui::SidePanel::left("books_panel")
 			.resizable(true)
@@ -327,10 +518,20 @@ impl BibleApp {
 							} else {
 								colors.text_color
 							};
-							let txt = egui::RichText::new(name.clone())
+							// 已读进度，例如「创世记  60%」
+							let label = match crate::reading_log::book_progress_label(&read, *num) {
+								Some(progress) => format!("{}  {}", name, progress),
+								None => name.clone(),
+							};
+							let txt = egui::RichText::new(label)
 								.color(txt_color);
 
-							if ui.add(egui::Button::new(txt).fill(bg)).clicked() {
+							let resp = ui.add(egui::Button::new(txt).fill(bg)).on_hover_text(format!(
+								"已读 {}/{} 章",
+								crate::reading_log::book_read_count(&read, *num),
+								crate::reference::chapter_count(*num),
+							));
+							if resp.clicked() {
 								selected_book = Some(*num);
 							}
 						}
@@ -347,19 +548,41 @@ impl BibleApp {
 	impl BibleApp {
 		fn ui_left_chapters_panel(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
 			let mut chosen: Option<String> = None;
+			let mut chosen_pericope: Option<usize> = None;
 			let book_num = self.current_book;
 			let book_abbr = &book_num
             .map(book_number_to_abbr)
             .unwrap_or("未选择");  
+			self.ensure_book_outline();
 
 			egui::SidePanel::left("chapters_panel")
 				.resizable(true)
 				.default_width(120.0)
 				.show(ctx, |ui| {
 					if let Some(_book) = book_num {
-						ui.label(format!("章节（{}）",book_abbr));
+						ui.horizontal(|ui| {
+							ui.label(format!("章节（{}）",book_abbr));
+							if ui.small_button("📋").on_hover_text("书卷概览：简介与大纲").clicked() {
+								self.show_book_overview = !self.show_book_overview;
+							}
+						});
 						ui.separator();
 
+						// 福音书中本章涉及的对照经段，点击打开福音书对照
+						let pericopes = self.chapter_pericopes();
+						if !pericopes.is_empty() {
+							egui::CollapsingHeader::new(egui::RichText::new("对照经段").size(13.0))
+								.default_open(true)
+								.show(ui, |ui| {
+									for (i, p) in pericopes {
+										if ui.link(egui::RichText::new(&p.title).size(12.0)).clicked() {
+											chosen_pericope = Some(i);
+										}
+									}
+								});
+							ui.separator();
+						}
+
 						egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
 							for chap in &self.chapters {
 								let is_selected = Some(chap) == self.current_chapter.as_ref();
@@ -375,11 +598,39 @@ impl BibleApp {
 									colors.text_color
 								};
 
-								let txt = egui::RichText::new(chapter_display_name(chap))
+								// 读过的章（阅读记录或读经计划）标上 ✓
+								let read = self.is_chapter_read(book_num.unwrap_or(0), chapter_number(chap) as i32);
+								let label = if read {
+									format!("{} ✓", chapter_display_name(chap))
+								} else {
+									chapter_display_name(chap)
+								};
+								let txt = egui::RichText::new(label)
 									.color(txt_color);
 
-								if ui.add(egui::Button::new(txt).fill(bg)).clicked() {
-									chosen = Some(chap.clone());
+								// 有小标题的章可展开大纲，点击小标题跳到该章
+								match self.book_outline.get(&(chapter_number(chap) as i32)) {
+									Some(headings) => {
+										let id = ui.make_persistent_id(("chapter_outline", book_num, chap));
+										egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+											.show_header(ui, |ui| {
+												if ui.add(egui::Button::new(txt).fill(bg)).clicked() {
+													chosen = Some(chap.clone());
+												}
+											})
+											.body(|ui| {
+												for (verse, title) in headings {
+													if ui.link(egui::RichText::new(title).size(12.0)).on_hover_text(format!("第 {} 节起", verse)).clicked() {
+														chosen = Some(chap.clone());
+													}
+												}
+											});
+									}
+									None => {
+										if ui.add(egui::Button::new(txt).fill(bg)).clicked() {
+											chosen = Some(chap.clone());
+										}
+									}
 								}
 							}
 						});
@@ -389,6 +640,9 @@ impl BibleApp {
 			if let (Some(book), Some(chap)) = (book_num, chosen) {
 				self.on_chapter_selected(book, chap);
 			}
+			if let Some(i) = chosen_pericope {
+				self.open_harmony(i);
+			}
 		}
 	}
 
@@ -654,6 +908,76 @@ impl BibleApp {
 							colors
 						);
 
+						let sync_btn = draw_hover_button(
+							ui,
+							"笔记同步",
+							egui::Vec2::new(70.0, 24.0),
+							colors
+						);
+
+						let backup_btn = draw_hover_button(
+							ui,
+							"笔记备份",
+							egui::Vec2::new(70.0, 24.0),
+							colors
+						);
+
+						let crypto_btn = draw_hover_button(
+							ui,
+							"笔记加密",
+							egui::Vec2::new(70.0, 24.0),
+							colors
+						);
+
+						let highlights_btn = draw_hover_button(
+							ui,
+							"经文高亮",
+							egui::Vec2::new(70.0, 24.0),
+							colors
+						);
+
+						let plan_btn = draw_hover_button(
+							ui,
+							"读经计划",
+							egui::Vec2::new(70.0, 24.0),
+							colors
+						);
+
+						let history_btn = draw_hover_button(
+							ui,
+							"阅读历史",
+							egui::Vec2::new(70.0, 24.0),
+							colors
+						);
+
+						let split_btn = draw_hover_button(
+							ui,
+							if self.split_pane.is_some() { "关闭分屏" } else { "分屏对照" },
+							egui::Vec2::new(70.0, 24.0),
+							colors
+						);
+
+						let harmony_btn = draw_hover_button(
+							ui,
+							"福音书对照",
+							egui::Vec2::new(70.0, 24.0),
+							colors
+						);
+
+						let bookmarks_btn = draw_hover_button(
+							ui,
+							if self.show_bookmarks_panel { "隐藏书签" } else { "书签栏" },
+							egui::Vec2::new(70.0, 24.0),
+							colors
+						);
+
+						let shortcuts_btn = draw_hover_button(
+							ui,
+							"快捷键",
+							egui::Vec2::new(70.0, 24.0),
+							colors
+						);
+
 						let toggle_editable_btn = draw_hover_button(
 							ui,
 							if self.editable_mode { "只读模式" } else { "编辑模式" },
@@ -688,7 +1012,8 @@ impl BibleApp {
 						}
 
 						if notes_list_btn.clicked(){
-							self.notes_cache = self.load_notes("notes", "all");
+							self.notes_search_keyword.clear();
+							self.reload_notes_list();
 							self.show_notes_list_window = true;
 							self.show_settings_menu = false;
 						}
@@ -698,6 +1023,67 @@ impl BibleApp {
 							self.show_settings_menu = false;
 						}
 
+						if sync_btn.clicked(){
+							self.show_sync_window = true;
+							self.show_settings_menu = false;
+						}
+
+						if backup_btn.clicked(){
+							if self.backup_dialog.is_none() {
+								self.backup_dialog = Some(BackupDialog::new());
+							}
+							self.show_settings_menu = false;
+						}
+
+						if crypto_btn.clicked(){
+							self.show_crypto_window = true;
+							self.show_settings_menu = false;
+						}
+
+						if highlights_btn.clicked(){
+							if self.highlight_browser.is_none() {
+								self.highlight_browser = Some(HighlightBrowser::new());
+							}
+							self.show_settings_menu = false;
+						}
+
+						if plan_btn.clicked(){
+							if self.reading_plan_dialog.is_none() {
+								self.reading_plan_dialog = Some(ReadingPlanDialog::new(self.settings.active_reading_plan.as_deref()));
+							}
+							self.show_settings_menu = false;
+						}
+
+						if history_btn.clicked(){
+							if self.reading_history.is_none() {
+								self.reading_history = Some(ReadingHistoryDialog::new());
+							}
+							self.show_settings_menu = false;
+						}
+
+						if split_btn.clicked(){
+							self.toggle_split_pane();
+							self.show_settings_menu = false;
+						}
+
+						if harmony_btn.clicked(){
+							let index = self.chapter_pericopes().first().map(|(i, _)| *i).unwrap_or(0);
+							self.open_harmony(index);
+							self.show_settings_menu = false;
+						}
+
+						if bookmarks_btn.clicked(){
+							self.show_bookmarks_panel = !self.show_bookmarks_panel;
+							self.show_settings_menu = false;
+						}
+
+						if shortcuts_btn.clicked(){
+							if self.shortcuts_dialog.is_none() {
+								self.shortcuts_dialog = Some(ShortcutsDialog::new(&self.settings.shortcuts));
+							}
+							self.show_settings_menu = false;
+						}
+
 						if toggle_editable_btn.clicked(){
 							self.editable_mode = !self.editable_mode
 						}
@@ -721,6 +1107,9 @@ impl BibleApp {
 	fn ui_top_toolbar(&mut self, ui: &mut egui::Ui, colors: &ThemeColors) {
 		ui.horizontal(|ui| {
 
+			// 后退 / 前进
+			self.ui_jump_buttons(ui, colors);
+
 			//译本切换按钮
 			self.change_version_button(ui, &colors);
 
@@ -743,6 +1132,16 @@ impl BibleApp {
 				.fill(colors.menu_button_bg)
 			);
 
+			// 本章书签
+			let bookmarked = self.current_chapter_bookmarked();
+			let bookmark_btn = ui.add(egui::Button::new(
+				egui::RichText::new(if bookmarked { "★" } else { "☆" }).color(colors.text_color)
+			).fill(colors.menu_button_bg))
+				.on_hover_text(format!("{}{}", if bookmarked { "删除本章书签" } else { "为本章添加书签" }, self.shortcut_hint(ShortcutAction::ToggleBookmark)));
+			if bookmark_btn.clicked() {
+				self.toggle_current_bookmark();
+			}
+
 			// 搜索框
 			ui.add_space(10.0);
 			self.ui_search_box(ui, colors);
@@ -775,6 +1174,7 @@ impl BibleApp {
 			.show(ui, |ui| {
 				let search = ui.add(
 					egui::TextEdit::singleline(&mut self.search_query)
+					.id(egui::Id::new("bible_search_box"))
 					.hint_text(
 						egui::RichText::new("搜索经文")
 						.color(colors.comment_text_color)
@@ -785,6 +1185,12 @@ impl BibleApp {
 					.min_size(egui::vec2(80.0, 14.0))
 				);
 
+				// Ctrl+F
+				if self.focus_search {
+					search.request_focus();
+					self.focus_search = false;
+				}
+
 				if search.clicked() || search.gained_focus() || search.has_focus(){
 					self.active_search_type = "bible".to_string();
 				}
@@ -847,6 +1253,7 @@ impl BibleApp {
 		}
 
 		let mut chosen: Option<(i32, String)> = None;
+		let mut open_in_tab: Option<(i32, String)> = None;
 		let mut close = false;
 
 		let result_count = self.search_results.len();
@@ -905,13 +1312,16 @@ impl BibleApp {
 
 						// 追加正文高亮
 						if let Some(query) = self.highlight_query.as_deref() {
-							highlight_search_terms(&snippet, query, colors, &mut job, &body_font_id);
+							highlight_search_terms(&snippet, &[query], colors, &mut job, &body_font_id);
 						}
 
-						// 用 Button 显示
-						if ui.add(egui::Button::new(job)).clicked() {
+						// 用 Button 显示；中键在新标签页中打开
+						let resp = ui.add(egui::Button::new(job)).on_hover_text("中键点击在新标签页中打开");
+						if resp.clicked() {
 							chosen = Some((*book, chap_num.to_string()));
 							close = true;
+						} else if resp.middle_clicked() {
+							open_in_tab = Some((*book, chap_num.to_string()));
 						}
 					}
 				});
@@ -931,6 +1341,10 @@ impl BibleApp {
 			}
 		}
 
+		if let Some((book, chap)) = open_in_tab {
+			self.open_in_new_tab(book, chap);
+		}
+
 		if close {
 			self.show_search_window = false;
 		}
@@ -952,13 +1366,22 @@ impl BibleApp {
         );
 
 				if self.content_layout.is_none() || self.last_processed_key != current_key {
+					if self.last_processed_key != current_key {
+						self.reload_chapter_highlights();
+						self.selection_chars = None;
+					}
 					let theme_colors = apply_theme(ctx, &self.theme);
 					self.content_layout = Some(self.prepare_content_layout(ui, &theme_colors));
 					self.last_processed_key = current_key;
 				}
 
 				//let body_font_id = ui.style().text_styles[&egui::TextStyle::Body].clone();
-        egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+        let mut scroll_area = egui::ScrollArea::vertical().auto_shrink([false; 2]);
+        // 切换标签页后恢复该页的滚动位置
+        if let Some(offset) = self.pending_scroll.take() {
+            scroll_area = scroll_area.vertical_scroll_offset(offset);
+        }
+        let scroll_output = scroll_area.show(ui, |ui| {
             if self.editable_mode {
 							let text_edit = egui::TextEdit::multiline(&mut self.content)
 								.desired_width(ui.available_width() - 12.0)
@@ -975,6 +1398,33 @@ impl BibleApp {
                             .sense(egui::Sense::click())
                             .selectable(true),
                     );
+                    let rect = text_response.rect;
+                    // 跟踪拖动选中的文字，右键时按选区取经节范围
+                    if text_response.drag_started_by(egui::PointerButton::Primary)
+                        && let Some(origin) = ui.input(|i| i.pointer.press_origin())
+                    {
+                        let start = self.char_at_pos(ui, layout, rect, origin);
+                        self.selection_chars = Some((start, start));
+                    }
+                    if text_response.dragged_by(egui::PointerButton::Primary)
+                        && let Some(pos) = text_response.interact_pointer_pos()
+                    {
+                        let end = self.char_at_pos(ui, layout, rect, pos);
+                        if let Some((_, e)) = &mut self.selection_chars {
+                            *e = end;
+                        }
+                    }
+                    if text_response.clicked() {
+                        self.selection_chars = None;
+                    }
+                    if text_response.secondary_clicked()
+                        && let Some(pos) = text_response.interact_pointer_pos()
+                    {
+                        self.context_verses = self.selected_verses().or_else(|| {
+                            let index = self.char_at_pos(ui, layout, rect, pos);
+                            verse_at_char(&self.content, index).map(|v| (v, v))
+                        });
+                    }
                     self.show_right_click_menu(&mut text_response);
                 }
             }
@@ -983,30 +1433,50 @@ impl BibleApp {
 							self.show_appended_notes(ui);
 						}
         });
+        self.content_scroll_offset = scroll_output.state.offset.y;
+    }
+
+    /// 正文中鼠标位置所在的字符序号
+    fn char_at_pos(&self, ui: &egui::Ui, layout: &egui::text::LayoutJob, rect: egui::Rect, pos: egui::Pos2) -> usize {
+        let mut job = layout.clone();
+        job.wrap.max_width = rect.width();
+        let galley = ui.painter().layout_job(job);
+        galley.cursor_from_pos(pos - rect.min).index
+    }
+
+    /// 当前选区覆盖的起止节；没有选中文字时为 None
+    fn selected_verses(&self) -> Option<(i32, i32)> {
+        let (a, b) = self.selection_chars?;
+        if a == b {
+            return None;
+        }
+        let (start, end) = (a.min(b), a.max(b));
+        let first = verse_at_char(&self.content, start)?;
+        let last = verse_at_char(&self.content, end - 1).unwrap_or(first);
+        Some((first.min(last), first.max(last)))
     }
 
     fn prepare_content_layout(&self, ui: &egui::Ui, colors: &ThemeColors) -> egui::text::LayoutJob {
         let mut job = egui::text::LayoutJob::default();
         let body_font_id = ui.style().text_styles[&egui::TextStyle::Body].clone();
 
-        if self.show_highlight {
-            if let Some(query) = self.highlight_query.as_deref() {
-                if !query.is_empty() {
-                    highlight_search_terms(&self.content, query, colors, &mut job, &body_font_id);
-                    return job; 
-                }
-            }
+        let query = self.highlight_query.as_deref().filter(|q| self.show_highlight && !q.is_empty());
+        if let Some(query) = query {
+            highlight_search_terms(&self.content, &[query], colors, &mut job, &body_font_id);
+        } else {
+            job.append(
+                &self.content,
+                0.0,
+                egui::TextFormat {
+                    font_id: body_font_id,
+                    color: colors.text_color,
+                    ..Default::default()
+                },
+            );
         }
 
-        job.append(
-            &self.content,
-            0.0,
-            egui::TextFormat {
-                font_id: body_font_id,
-                color: colors.text_color,
-                ..Default::default()
-            },
-        );
+        // 用户的经文高亮画在背景上
+        crate::highlights::apply_background(&mut job, &self.highlight_ranges());
         job
     }
 }
@@ -1014,9 +1484,65 @@ impl BibleApp {
 ///右键菜单
 impl BibleApp {
 	fn show_right_click_menu(&mut self, response: &mut egui::Response) {
+		// 选中的经节，没有选区时为右键所在的经节
+		let context_ref = match (self.current_book, &self.current_chapter, self.context_verses) {
+			(Some(book), Some(chap), Some((start, end))) => Some(ScriptureRef {
+				book_num: book,
+				chapter: chap.parse().unwrap_or(1),
+				verse_start: Some(start),
+				verse_end: (end > start).then_some(end),
+			}),
+			_ => None,
+		};
+
 		response.context_menu(|ui| {
 			if ui.button("➕ 添加笔记").clicked() { 
-				self.open_noteapp_window(None);
+				match &context_ref {
+					Some(r) => {
+						let mut note = self.new_note_here();
+						note.reference = Some(r.display());
+						self.open_noteapp_window(Some(note));
+					}
+					None => self.open_noteapp_window(None),
+				}
+				ui.close_kind(egui::UiKind::Menu)
+			}
+
+			if let Some(r) = &context_ref
+				&& let Some(editor) = self.note_editors.last_mut()
+				&& ui.button(format!("📎 设为笔记引用（{}）", r.display()))
+					.on_hover_text("填入正在编辑的笔记的“引用经文”")
+					.clicked()
+			{
+				editor.set_reference(r);
+				ui.close_kind(egui::UiKind::Menu)
+			}
+
+			if let Some(r) = &context_ref {
+				let current = r.verse_start.and_then(|v| self.chapter_highlights.get(&v)).cloned();
+				let mut chosen: Option<Option<&str>> = None;
+				ui.menu_button("🖍 高亮", |ui| {
+					for (name, _, _) in crate::highlights::HIGHLIGHT_COLORS {
+						let text = egui::RichText::new(crate::highlights::color_display_name(name, &self.color_labels))
+							.background_color(crate::highlights::highlight_color(name));
+						if ui.selectable_label(current.as_deref() == Some(name), text).clicked() {
+							chosen = Some(Some(name));
+						}
+					}
+					if current.is_some() && ui.button("清除高亮").clicked() {
+						chosen = Some(None);
+					}
+				});
+				if let Some(color) = chosen {
+					self.set_verse_highlight(r, color);
+					ui.close_kind(egui::UiKind::Menu)
+				}
+			}
+
+			if let Some(r) = &context_ref
+				&& ui.button(format!("🔖 添加书签（{}）", r.display())).clicked()
+			{
+				self.add_verse_bookmark(r);
 				ui.close_kind(egui::UiKind::Menu)
 			}
 
@@ -1036,55 +1562,106 @@ impl BibleApp {
 
 ///打开笔记编辑窗口
 impl BibleApp {
-	fn open_noteapp_window(&self, note_opt: Option<&Notedb>) {
+	fn open_noteapp_window(&mut self, note_opt: Option<Notedb>) {
+		// 未解锁时正文只是占位文字，不能编辑
+		if crate::crypto::is_locked() {
+			eprintln!("笔记已加密，请先解锁");
+			self.unlock_prompt = true;
+			return;
+		}
 		let note = if let Some(note) = note_opt {
-			note.clone() // 编辑已有笔记
-		} else {
-			// 新建笔记
-			Notedb {
-				id: Uuid::new_v4().to_string(),
-				created_at: Some(Utc::now().format("%Y-%m-%d").to_string()),
-				book_num: self.current_book,
-				book_name: self.current_book_name.clone(),
-				chapter: self.current_chapter.clone(),
-				verse_start: -1,
-				char_offset: Some(0),
-				version: Some(self.current_version.clone()),
-				..Default::default()
-			}
-		};
-
-		let note_json = match serde_json::to_string(&note) {
-			Ok(json) => json,
-			Err(e) => {
-				eprintln!("序列化笔记失败: {e}");
+			// 已在编辑中则切到该窗口
+			if let Some(editor) = self.note_editors.iter_mut().find(|e| e.note.id == note.id) {
+				editor.request_focus();
 				return;
 			}
+			note // 编辑已有笔记
+		} else {
+			self.new_note_here()
 		};
 
-		let exe = match std::env::current_exe() {
-			Ok(exe) => exe,
-			Err(e) => {
-				eprintln!("无法获取当前可执行文件路径: {e}");
-				return;
+		let bible = self.bible_source();
+		self.note_editors.push(NoteApp::new(note, bible));
+	}
+
+	/// 在当前章新建的空白笔记
+	fn new_note_here(&self) -> Notedb {
+		Notedb {
+			id: Uuid::new_v4().to_string(),
+			created_at: Some(crate::utils::now_timestamp()),
+			book_num: self.current_book,
+			book_name: self.current_book_name.clone(),
+			chapter: self.current_chapter.clone(),
+			verse_start: -1,
+			char_offset: Some(0),
+			version: Some(self.current_version.clone()),
+			..Default::default()
+		}
+	}
+
+	/// 笔记编辑窗口引用经文时可用的译本
+	fn bible_source(&self) -> BibleSource {
+		BibleSource {
+			bible_root: self.bible_root.clone(),
+			versions: self.versions.clone(),
+			current_version: self.current_version.clone(),
+		}
+	}
+
+	/// 用草稿内容打开编辑窗口
+	pub fn open_draft_editor(&mut self, note: Notedb) {
+		self.note_editors.retain(|e| e.note.id != note.id);
+		let bible = self.bible_source();
+		self.note_editors.push(NoteApp::from_draft(note, bible));
+	}
+
+	/// 显示所有打开的笔记编辑窗口，并把保存 / 删除同步到阅读器
+	fn show_note_editors(&mut self, ctx: &egui::Context) {
+		let mut closed: Vec<String> = Vec::new();
+		let mut changed = false;
+		let mut go_to = None;
+
+		for editor in &mut self.note_editors {
+			match editor.show(ctx) {
+				NoteEditorAction::None => {}
+				NoteEditorAction::Saved(note) => {
+					if self.current_note.as_ref().is_some_and(|n| n.id == note.id) {
+						self.current_note = Some((*note).clone());
+					}
+					closed.push(note.id);
+					changed = true;
+				}
+				NoteEditorAction::Deleted(id) => {
+					if self.current_note.as_ref().is_some_and(|n| n.id == id) {
+						self.current_note = None;
+						self.note_window_open = false;
+					}
+					closed.push(id);
+					changed = true;
+				}
+				NoteEditorAction::GoTo(r) => go_to = Some(r),
+				NoteEditorAction::Closed => closed.push(editor.note.id.clone()),
 			}
-		};
+		}
+
+		self.note_editors.retain(|e| !closed.contains(&e.note.id));
 
-		if let Err(e) = std::process::Command::new(exe)
-			.arg("--note-window")
-				.arg("--note-json")
-				.arg(note_json)
-				.spawn()
-		{
-			eprintln!("无法启动笔记窗口: {e}");
+		if changed {
+			self.last_appended_notes_chapter = None;
+			if self.show_notes_list_window {
+				self.reload_notes_list();
+			}
+		}
+		if let Some(r) = go_to {
+			self.go_to_reference(&r);
 		}
 	}
 }
 
 ///版本切换
 impl BibleApp {
-	fn on_version_changed(&mut self, ver: String) {
-		self.record_jump();
+	/// 切换到另一译本：清空与旧译本相关的搜索、缓存和编辑状态，重新读取书卷并打开数据库
+	pub fn switch_version(&mut self, ver: String) {
 		self.search_results.clear();
 		self.show_search_window = false;
 		self.last_search_query.clear();
@@ -1093,18 +1670,10 @@ impl BibleApp {
 		self.show_highlight = false; 
 		self.editable_mode = false;
 
-		let old_book = self.current_book;
-		let old_chapter = self.current_chapter.clone();
-
-		self.current_version = ver.clone();
+		self.current_version = ver;
 		let db_path = self.bible_root.join(&self.current_version);
 		self.books = load_books(&db_path);
 
-		// 保持原书卷
-		self.current_book = old_book
-			.filter(|b| self.books.iter().any(|(n, _)| n == b))
-			.or_else(|| self.books.first().map(|(n, _)| *n));
-
 		// --- 打开数据库并持久化连接 ---
 		match Connection::open(&db_path) {
 			Ok(conn) => {
@@ -1115,6 +1684,21 @@ impl BibleApp {
 				self.conn = None;
 			}
 		}
+	}
+
+	fn on_version_changed(&mut self, ver: String) {
+		self.record_jump();
+
+		let old_book = self.current_book;
+		let old_chapter = self.current_chapter.clone();
+
+		self.switch_version(ver);
+		let db_path = self.bible_root.join(&self.current_version);
+
+		// 保持原书卷
+		self.current_book = old_book
+			.filter(|b| self.books.iter().any(|(n, _)| n == b))
+			.or_else(|| self.books.first().map(|(n, _)| *n));
 
 		if let Some(book) = self.current_book {
 			let mut chapters = load_chapters(&db_path, book);
@@ -1156,6 +1740,25 @@ impl BibleApp {
 		}
 	}
 
+	/// 跳转到经文引用所在章（笔记中的引用链接）
+	pub fn go_to_reference(&mut self, r: &crate::reference::ScriptureRef) {
+		let chapters = if self.current_book != Some(r.book_num) {
+			let mut chapters = load_chapters(&self.bible_root.join(&self.current_version), r.book_num);
+			chapters.sort_by_key(|c| chapter_number(c));
+			chapters
+		} else {
+			self.chapters.clone()
+		};
+		let ch = r.chapter.to_string();
+		// 找到章节后才替换章节列表，找不到时保持当前书卷不变
+		if chapters.contains(&ch) {
+			self.chapters = chapters;
+			self.on_chapter_selected(r.book_num, ch);
+		} else {
+			eprintln!("当前译本中找不到 {}", r.display());
+		}
+	}
+
 	fn on_chapter_selected(&mut self, book_num: i32, ch: String) {
 		self.record_jump();
 		self.current_book = Some(book_num.clone());
@@ -1171,52 +1774,63 @@ impl BibleApp {
 
 ///转跳
 impl BibleApp {
+	/// 当前位置：（译本, 书卷, 章节）
+	fn current_state(&self) -> Option<(String, i32, String)> {
+		match (self.current_book, &self.current_chapter) {
+			(Some(book), Some(chap)) => Some((self.current_version.clone(), book, chap.clone())),
+			_ => None,
+		}
+	}
+	/// 压入跳转栈：去掉栈中相同的位置，超出上限时丢弃最早的
+	fn push_jump(stack: &mut Vec<(String, i32, String)>, state: (String, i32, String)) {
+		stack.retain(|s| *s != state);
+		stack.push(state);
+		if stack.len() > MAX_JUMP_HISTORY {
+			stack.remove(0);
+		}
+	}
 	fn record_jump(&mut self) {
-		if let (Some(book), Some(chap)) = (self.current_book, &self.current_chapter) {
-			let current_state = (
-				self.current_version.clone(),
-				book,
-				chap.clone(),
-			);
-
-			// 避免连续重复状态
-			if self.jump_back_stack.last() != Some(&current_state) {
-				self.jump_back_stack.push(current_state.clone());
-			}
+		if let Some(current_state) = self.current_state() {
+			Self::push_jump(&mut self.jump_back_stack, current_state);
 
 			// 新操作清空 forward 栈
 			self.jump_forward_stack.clear();
 		}
 	}
 	fn jump_back(&mut self) {
-		if let Some(prev) = self.jump_back_stack.pop() {
+		self.jump_back_steps(1);
+	}
+	fn jump_forward(&mut self) {
+		self.jump_forward_steps(1);
+	}
+	/// 后退若干步（后退下拉列表中选中较早的位置）
+	fn jump_back_steps(&mut self, steps: usize) {
+		let mut target = None;
+		for _ in 0..steps {
+			let Some(prev) = self.jump_back_stack.pop() else { break };
 			// 1. 当前状态推入 forward_stack
-			if let (Some(book), Some(chap)) = (self.current_book, &self.current_chapter) {
-				let current_state = (
-					self.current_version.clone(),
-					book,
-					chap.clone(),
-				);
-				self.jump_forward_stack.push(current_state);
+			if let Some(current_state) = target.take().or_else(|| self.current_state()) {
+				Self::push_jump(&mut self.jump_forward_stack, current_state);
 			}
-
-			// 2. 跳转到 prev 所指内容
+			target = Some(prev);
+		}
+		// 2. 跳转到 prev 所指内容
+		if let Some(prev) = target {
 			self.apply_state(prev);
 		}
 	}
-	fn jump_forward(&mut self) {
-		if let Some(next) = self.jump_forward_stack.pop() {
+	fn jump_forward_steps(&mut self, steps: usize) {
+		let mut target = None;
+		for _ in 0..steps {
+			let Some(next) = self.jump_forward_stack.pop() else { break };
 			// 1. 当前状态推入 back_stack
-			if let (Some(book), Some(chap)) = (self.current_book, &self.current_chapter) {
-				let current_state = (
-					self.current_version.clone(),
-					book,
-					chap.clone(),
-				);
-				self.jump_back_stack.push(current_state);
+			if let Some(current_state) = target.take().or_else(|| self.current_state()) {
+				Self::push_jump(&mut self.jump_back_stack, current_state);
 			}
-
-			// 2. 跳转到 next
+			target = Some(next);
+		}
+		// 2. 跳转到 next
+		if let Some(next) = target {
 			self.apply_state(next);
 		}
 	}
@@ -1237,18 +1851,58 @@ impl BibleApp {
 			ch_num,
 		);
 	}
-	fn check_jump_shortcuts(&mut self, ctx: &egui::Context) {
-		// 遍历当前帧所有键事件
-		for event in &ctx.input(|i| i.events.clone()) {
-			if let egui::Event::Key { key, pressed, modifiers, .. } = event {
-				if *pressed && modifiers.ctrl {
-					match key {
-						egui::Key::O => self.jump_back(),
-						egui::Key::I => self.jump_forward(),
-						_ => {}
-					}
+}
+
+/// 跳转记录的显示名，例如「和合本 · 约翰福音 第 3 章」
+fn jump_state_label(state: &(String, i32, String)) -> String {
+	let (ver, book, chap) = state;
+	format!("{} · {} {}", version_display_name(ver), crate::reference::book_full_name(*book), chapter_display_name(chap))
+}
+
+///后退 / 前进按钮
+impl BibleApp {
+	fn ui_jump_buttons(&mut self, ui: &mut egui::Ui, colors: &ThemeColors) {
+		let mut back_steps = 0;
+		let mut forward_steps = 0;
+
+		let back_btn = ui.add_enabled(
+			!self.jump_back_stack.is_empty(),
+			egui::Button::new(egui::RichText::new("◀").color(colors.text_color)).fill(colors.menu_button_bg),
+		).on_hover_text(format!("后退{}，鼠标后退键也可；右键查看记录", self.shortcut_hint(ShortcutAction::Back)));
+		if back_btn.clicked() {
+			back_steps = 1;
+		}
+		back_btn.context_menu(|ui| {
+			// 最近的在上
+			for (i, state) in self.jump_back_stack.iter().rev().enumerate() {
+				if ui.button(jump_state_label(state)).clicked() {
+					back_steps = i + 1;
+					ui.close_kind(egui::UiKind::Menu);
+				}
+			}
+		});
+
+		let forward_btn = ui.add_enabled(
+			!self.jump_forward_stack.is_empty(),
+			egui::Button::new(egui::RichText::new("▶").color(colors.text_color)).fill(colors.menu_button_bg),
+		).on_hover_text(format!("前进{}，鼠标前进键也可；右键查看记录", self.shortcut_hint(ShortcutAction::Forward)));
+		if forward_btn.clicked() {
+			forward_steps = 1;
+		}
+		forward_btn.context_menu(|ui| {
+			for (i, state) in self.jump_forward_stack.iter().rev().enumerate() {
+				if ui.button(jump_state_label(state)).clicked() {
+					forward_steps = i + 1;
+					ui.close_kind(egui::UiKind::Menu);
 				}
 			}
+		});
+
+		if back_steps > 0 {
+			self.jump_back_steps(back_steps);
+		}
+		if forward_steps > 0 {
+			self.jump_forward_steps(forward_steps);
 		}
 	}
 }
@@ -1266,9 +1920,16 @@ impl eframe::App for BibleApp {
 		// 左侧 UI
 		self.ui_left_books_panel(ctx, &colors);
 		self.ui_left_chapters_panel(ctx, &colors);
+		self.ui_left_bookmarks_panel(ctx, &colors);
+		// 右侧分屏
+		self.ui_split_pane(ctx, &colors);
+		// 后台同步完成后刷新笔记
+		self.poll_sync(ctx);
 
 		// 中央 UI
 		egui::CentralPanel::default().show(ctx, |ui| {
+			// 标签栏
+			self.ui_tab_bar(ui, &colors);
 			// 顶部工具栏
 			self.ui_top_toolbar(ui, &colors);
 			ui.separator();
@@ -1289,71 +1950,60 @@ impl eframe::App for BibleApp {
 
 		self.show_notes_list_window(ctx, &colors);
 
-		// 检测快捷键
-		self.check_jump_shortcuts(ctx);
-	}
-}
+		self.show_note_history_window(ctx, &colors);
 
-fn main() -> eframe::Result<()> {
-	let args: Vec<String> = std::env::args().collect();
-	if args.len() > 1 && args[1] == "--note-window" {
-		let mut note_json: Option<String> = None;
-		let mut i = 1;
-		while i < args.len() {
-			match args[i].as_str() {
-				"--note-json" => {
-					if let Some(v) = args.get(i + 1) {
-						note_json = Some(v.clone());
-					}
-					i += 1;
-				}
-				_ => {}
-			}
-			i += 1;
-		}
+		self.show_note_editors(ctx);
 
-		// 反序列化 JSON 为 Notedb
-		let note_data: Notedb = if let Some(nj) = note_json {
-			serde_json::from_str(&nj).unwrap()
-		} else {
-			Notedb {
-				id: Uuid::new_v4().to_string(),
-				created_at: Some(Utc::now().format("%Y-%m-%d").to_string()),
-				..Default::default()
-			}
-		};
+		self.show_draft_recovery_window(ctx, &colors);
 
-		let options = eframe::NativeOptions {
-			renderer: eframe::Renderer::Wgpu,
-			viewport: egui::ViewportBuilder::default()
-				.with_inner_size([600.0, 600.0])
-				.with_title("撰写笔记"),
-				..Default::default()
-		};
-		eframe::run_native(
-			"撰写笔记",
-			options,
-			Box::new(move |cc| {
-				configure_chinese_font(&cc.egui_ctx);
-				Ok(Box::new(NoteApp { 
-					note: note_data,
-				}))
-			}),
-		)
-
-	} else {
-		let options = eframe::NativeOptions {
-			renderer: eframe::Renderer::Wgpu,
-			viewport: egui::ViewportBuilder::default()
-				.with_inner_size([1200.0, 800.0])
-				.with_title("圣经阅读器"),
-				..Default::default()
-		};
+		self.show_export_window(ctx, &colors);
+		self.show_import_window(ctx, &colors);
+		self.show_sync_window(ctx, &colors);
+		self.show_backup_window(ctx, &colors);
+		self.show_crypto_window(ctx, &colors);
+		self.show_highlight_browser(ctx, &colors);
+		self.show_reading_plan_window(ctx, &colors);
+		self.show_reading_history_window(ctx, &colors);
+		self.show_harmony_window(ctx, &colors);
+		self.show_book_overview_window(ctx, &colors);
+		self.show_goto_window(ctx, &colors);
+		self.show_shortcuts_window(ctx, &colors);
+		self.show_unlock_window(ctx, &colors);
 
-		eframe::run_native(
-			"圣经阅读器",
-			options,
-			Box::new(|cc| Ok(Box::new(BibleApp::new(cc)))),
-		)
+		// 检测快捷键
+		self.check_shortcuts(ctx);
+
+		// 记录阅读的章
+		self.track_reading();
+	}
+
+	fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+		// 退出时把编辑中的笔记写入草稿，下次启动可恢复
+		for editor in &mut self.note_editors {
+			editor.flush_draft();
+		}
+		// 写入正在阅读的这一章
+		self.finish_chapter_visit();
+		// 保存标签页，下次启动时恢复
+		self.save_tabs();
+		// 把本次的笔记变更写入同步文件夹，最多等待 SYNC_EXIT_TIMEOUT
+		self.run_sync();
+		self.wait_for_sync(crate::sync::SYNC_EXIT_TIMEOUT);
 	}
 }
+
+fn main() -> eframe::Result<()> {
+	let options = eframe::NativeOptions {
+		renderer: eframe::Renderer::Wgpu,
+		viewport: egui::ViewportBuilder::default()
+			.with_inner_size([1200.0, 800.0])
+			.with_title("圣经阅读器"),
+			..Default::default()
+	};
+
+	eframe::run_native(
+		"圣经阅读器",
+		options,
+		Box::new(|cc| Ok(Box::new(BibleApp::new(cc)))),
+	)
+}
@@ -0,0 +1,402 @@
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use crate::reference::{ScriptureRef, find_references, parse_reference};
+
+/// 每层列表 / 引用的缩进宽度
+const INDENT: f32 = 16.0;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+struct InlineStyle {
+	strong: bool,
+	italic: bool,
+	strike: bool,
+	code: bool,
+	size: Option<f32>,
+}
+
+#[derive(Clone, PartialEq)]
+enum LinkTarget {
+	Url(String),
+	Scripture(ScriptureRef),
+}
+
+struct Span {
+	text: String,
+	style: InlineStyle,
+	link: Option<LinkTarget>,
+}
+
+/// 把 pulldown-cmark 事件流逐块画成 egui 控件
+struct Renderer {
+	/// 当前块中的行（软换行也按换行处理，旧的纯文本笔记排版不变）
+	lines: Vec<Vec<Span>>,
+	style: InlineStyle,
+	link: Option<LinkTarget>,
+	quote_depth: usize,
+	/// 列表栈：有序列表保存下一个序号
+	lists: Vec<Option<u64>>,
+	bullet: Option<String>,
+	code_block: Option<String>,
+	clicked: Option<ScriptureRef>,
+}
+
+fn heading_size(level: HeadingLevel) -> f32 {
+	match level {
+		HeadingLevel::H1 => 22.0,
+		HeadingLevel::H2 => 19.0,
+		HeadingLevel::H3 => 17.0,
+		_ => 15.0,
+	}
+}
+
+fn link_target(url: &str) -> Option<LinkTarget> {
+	if url.starts_with("http://") || url.starts_with("https://") {
+		Some(LinkTarget::Url(url.to_string()))
+	} else {
+		parse_reference(url).map(LinkTarget::Scripture)
+	}
+}
+
+impl Renderer {
+	fn new() -> Self {
+		Self {
+			lines: Vec::new(),
+			style: InlineStyle::default(),
+			link: None,
+			quote_depth: 0,
+			lists: Vec::new(),
+			bullet: None,
+			code_block: None,
+			clicked: None,
+		}
+	}
+
+	fn push_text(&mut self, text: &str, style: InlineStyle) {
+		if self.lines.is_empty() {
+			self.lines.push(Vec::new());
+		}
+		let line = self.lines.last_mut().unwrap();
+		// 相邻同样式的文字合并，经文引用才不会被拆开
+		if let Some(last) = line.last_mut()
+			&& last.style == style
+			&& last.link == self.link
+		{
+			last.text.push_str(text);
+			return;
+		}
+		line.push(Span { text: text.to_string(), style, link: self.link.clone() });
+	}
+
+	fn line_break(&mut self) {
+		if self.lines.is_empty() {
+			self.lines.push(Vec::new());
+		}
+		self.lines.push(Vec::new());
+	}
+
+	fn indent(&self) -> f32 {
+		(self.lists.len() + self.quote_depth) as f32 * INDENT
+	}
+
+	/// 引用块左侧竖线
+	fn paint_quote_bar(&self, ui: &egui::Ui, rect: egui::Rect) {
+		if self.quote_depth == 0 {
+			return;
+		}
+		let stroke = egui::Stroke::new(3.0, ui.visuals().weak_text_color());
+		for depth in 0..self.quote_depth {
+			let x = rect.left() + depth as f32 * INDENT + 4.0;
+			ui.painter().vline(x, rect.y_range(), stroke);
+		}
+	}
+
+	fn rich_text(text: &str, style: InlineStyle, quoted: bool, ui: &egui::Ui) -> egui::RichText {
+		let mut rt = egui::RichText::new(text);
+		if let Some(size) = style.size {
+			rt = rt.size(size).strong();
+		}
+		if style.strong {
+			rt = rt.strong();
+		}
+		if style.italic || quoted {
+			rt = rt.italics();
+		}
+		if style.strike {
+			rt = rt.strikethrough();
+		}
+		if style.code {
+			rt = rt.code();
+		}
+		if quoted {
+			rt = rt.color(ui.visuals().weak_text_color());
+		}
+		rt
+	}
+
+	fn draw_span(&mut self, ui: &mut egui::Ui, span: &Span, quoted: bool) {
+		match &span.link {
+			Some(LinkTarget::Url(url)) => {
+				ui.hyperlink_to(Self::rich_text(&span.text, span.style, quoted, ui), url);
+			}
+			Some(LinkTarget::Scripture(r)) => {
+				let rt = Self::rich_text(&span.text, span.style, quoted, ui);
+				if ui.link(rt).on_hover_text(r.display()).clicked() {
+					self.clicked = Some(r.clone());
+				}
+			}
+			None if span.style.code => {
+				ui.label(Self::rich_text(&span.text, span.style, quoted, ui));
+			}
+			None => {
+				// 正文中的经文引用变成可点击链接
+				let mut last = 0;
+				for (range, r) in find_references(&span.text) {
+					if range.start > last {
+						ui.label(Self::rich_text(&span.text[last..range.start], span.style, quoted, ui));
+					}
+					let rt = Self::rich_text(&span.text[range.clone()], span.style, quoted, ui);
+					if ui.link(rt).on_hover_text(r.display()).clicked() {
+						self.clicked = Some(r);
+					}
+					last = range.end;
+				}
+				if last < span.text.len() {
+					ui.label(Self::rich_text(&span.text[last..], span.style, quoted, ui));
+				}
+			}
+		}
+	}
+
+	/// 画出当前积累的段落 / 标题 / 列表项
+	fn flush(&mut self, ui: &mut egui::Ui) {
+		let bullet = self.bullet.take();
+		if self.lines.iter().all(|l| l.is_empty()) && bullet.is_none() {
+			self.lines.clear();
+			return;
+		}
+		let lines = std::mem::take(&mut self.lines);
+		let quoted = self.quote_depth > 0;
+		let indent = self.indent();
+
+		let resp = ui.horizontal(|ui| {
+			ui.add_space(indent);
+			if let Some(bullet) = &bullet {
+				ui.label(bullet);
+			}
+			ui.vertical(|ui| {
+				for line in &lines {
+					ui.horizontal_wrapped(|ui| {
+						ui.spacing_mut().item_spacing.x = 0.0;
+						if line.is_empty() {
+							ui.label("");
+						}
+						for span in line {
+							self.draw_span(ui, span, quoted);
+						}
+					});
+				}
+			});
+		});
+		self.paint_quote_bar(ui, resp.response.rect);
+	}
+
+	fn draw_code_block(&mut self, ui: &mut egui::Ui, code: &str) {
+		let indent = self.indent();
+		let resp = ui.horizontal(|ui| {
+			ui.add_space(indent);
+			egui::Frame::new()
+				.fill(ui.visuals().code_bg_color)
+				.corner_radius(4.0)
+				.inner_margin(6.0)
+				.show(ui, |ui| {
+					ui.add(egui::Label::new(
+						egui::RichText::new(code.trim_end_matches('\n')).monospace(),
+					).wrap());
+				});
+		});
+		self.paint_quote_bar(ui, resp.response.rect);
+	}
+
+	fn event(&mut self, ui: &mut egui::Ui, event: Event) {
+		if let Some(code) = &mut self.code_block {
+			match event {
+				Event::Text(text) => code.push_str(&text),
+				Event::End(TagEnd::CodeBlock) => {
+					let code = self.code_block.take().unwrap_or_default();
+					self.draw_code_block(ui, &code);
+				}
+				_ => {}
+			}
+			return;
+		}
+
+		match event {
+			Event::Start(tag) => match tag {
+				Tag::Paragraph => self.flush(ui),
+				Tag::Heading { level, .. } => {
+					self.flush(ui);
+					ui.add_space(4.0);
+					self.style.size = Some(heading_size(level));
+				}
+				Tag::BlockQuote(_) => {
+					self.flush(ui);
+					self.quote_depth += 1;
+				}
+				Tag::CodeBlock(_) => {
+					self.flush(ui);
+					self.code_block = Some(String::new());
+				}
+				Tag::List(start) => {
+					self.flush(ui);
+					self.lists.push(start);
+				}
+				Tag::Item => {
+					self.flush(ui);
+					let bullet = match self.lists.last_mut() {
+						Some(Some(n)) => {
+							let b = format!("{}.", n);
+							*n += 1;
+							b
+						}
+						_ => "•".to_string(),
+					};
+					self.bullet = Some(bullet);
+				}
+				Tag::Emphasis => self.style.italic = true,
+				Tag::Strong => self.style.strong = true,
+				Tag::Strikethrough => self.style.strike = true,
+				Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+					self.link = link_target(&dest_url);
+				}
+				_ => {}
+			},
+			Event::End(tag) => match tag {
+				TagEnd::Paragraph | TagEnd::Item => self.flush(ui),
+				TagEnd::Heading(_) => {
+					self.flush(ui);
+					self.style.size = None;
+				}
+				TagEnd::BlockQuote(_) => {
+					self.flush(ui);
+					self.quote_depth = self.quote_depth.saturating_sub(1);
+				}
+				TagEnd::List(_) => {
+					self.flush(ui);
+					self.lists.pop();
+				}
+				TagEnd::Emphasis => self.style.italic = false,
+				TagEnd::Strong => self.style.strong = false,
+				TagEnd::Strikethrough => self.style.strike = false,
+				TagEnd::Link | TagEnd::Image => self.link = None,
+				_ => {}
+			},
+			Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => {
+				self.push_text(&text, self.style);
+			}
+			Event::Code(text) => {
+				let style = InlineStyle { code: true, ..self.style };
+				self.push_text(&text, style);
+			}
+			Event::SoftBreak | Event::HardBreak => self.line_break(),
+			Event::Rule => {
+				self.flush(ui);
+				ui.separator();
+			}
+			Event::TaskListMarker(done) => {
+				self.bullet = Some(if done { "☑".to_string() } else { "☐".to_string() });
+			}
+			_ => {}
+		}
+	}
+}
+
+/// 以 Markdown 渲染笔记正文
+///
+/// 支持标题、列表、粗体/斜体、引用块与代码；正文里的经文引用可点击，
+/// 返回本帧被点击的引用。
+pub fn render_markdown(ui: &mut egui::Ui, text: &str) -> Option<ScriptureRef> {
+	let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+	let mut renderer = Renderer::new();
+
+	ui.vertical(|ui| {
+		for event in Parser::new_ext(text, options) {
+			renderer.event(ui, event);
+		}
+		renderer.flush(ui);
+	});
+
+	renderer.clicked
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// 在无窗口的 egui 中运行
+	fn with_ui(f: impl FnOnce(&mut egui::Ui)) {
+		let ctx = egui::Context::default();
+		let mut f = Some(f);
+		let _ = ctx.run(egui::RawInput::default(), |ctx| {
+			egui::CentralPanel::default().show(ctx, |ui| {
+				if let Some(f) = f.take() {
+					f(ui);
+				}
+			});
+		});
+	}
+
+	/// 第一段画出之前积累的各行：（文字, 是否粗体）
+	fn first_paragraph(text: &str) -> Vec<Vec<(String, bool)>> {
+		let mut renderer = Renderer::new();
+		with_ui(|ui| {
+			for event in Parser::new_ext(text, Options::empty()) {
+				if matches!(event, Event::End(TagEnd::Paragraph)) {
+					break;
+				}
+				renderer.event(ui, event);
+			}
+		});
+		renderer.lines.iter()
+			.map(|line| line.iter().map(|s| (s.text.clone(), s.style.strong)).collect())
+			.collect()
+	}
+
+	#[test]
+	fn classifies_link_targets() {
+		assert!(matches!(link_target("https://example.com"), Some(LinkTarget::Url(_))));
+		match link_target("约3:16") {
+			Some(LinkTarget::Scripture(r)) => assert_eq!((r.book_num, r.chapter), (43, 3)),
+			_ => panic!("应识别为经文"),
+		}
+		assert!(link_target("笔记").is_none());
+	}
+
+	#[test]
+	fn keeps_soft_breaks_and_merges_same_style_text() {
+		assert_eq!(first_paragraph("神爱世人，**甚至**将\n他的独生子"), vec![
+			vec![("神爱世人，".to_string(), false), ("甚至".to_string(), true), ("将".to_string(), false)],
+			vec![("他的独生子".to_string(), false)],
+		]);
+	}
+
+	#[test]
+	fn numbers_ordered_lists_from_their_start() {
+		let mut bullets = Vec::new();
+		let mut renderer = Renderer::new();
+		with_ui(|ui| {
+			for event in Parser::new_ext("3. 甲\n4. 乙\n", Options::empty()) {
+				let item = matches!(event, Event::Start(Tag::Item));
+				renderer.event(ui, event);
+				if item {
+					bullets.push(renderer.bullet.clone().unwrap_or_default());
+				}
+			}
+		});
+		assert_eq!(bullets, vec!["3.", "4."]);
+	}
+
+	#[test]
+	fn renders_every_block_kind() {
+		let text = "# 标题\n\n> 引用 **粗体**\n\n- [x] 已完成\n- [ ] 未完成\n\n```\ncode\n```\n\n---\n\n参见 [约翰福音](约3:16) 与 太5:3";
+		with_ui(|ui| assert!(render_markdown(ui, text).is_none()));
+	}
+}
@@ -1,9 +1,323 @@
-use eframe::egui;
 use egui::{RichText,ScrollArea};
 use crate::notes::{Notedb,save_note,delete_note};
+use crate::tags::{split_keywords, load_tag_counts, TAG_JOINER};
+use crate::notebooks::{Notebook, load_notebooks, notebooks_in_tree_order, notebook_path};
+use crate::markdown::render_markdown;
+use crate::reference::{ScriptureRef, parse_reference};
+use crate::quote::{BibleSource, load_passage, format_quotation};
+use crate::utils::version_display_name;
+use crate::drafts::{save_draft, remove_draft, AUTOSAVE_INTERVAL};
+
+/// 自动补全最多显示的候选标签数
+const MAX_TAG_SUGGESTIONS: usize = 8;
 
 pub struct NoteApp {
 		pub note: Notedb,
+		tag_input: String,
+		known_tags: Vec<String>,
+		notebooks: Vec<Notebook>,
+		/// 正文旁显示 Markdown 实时预览
+		preview: bool,
+		/// 下一帧把窗口切到前台
+		focus_pending: bool,
+		/// 最近一次保存到数据库的内容，None 表示从草稿恢复、尚未保存
+		saved_fields: Option<NoteFields>,
+		/// 最近一次写入草稿的内容
+		draft_fields: Option<NoteFields>,
+		last_autosave: f64,
+		/// 关闭时有未保存的修改，等待用户确认
+		confirm_close: bool,
+		bible: BibleSource,
+		quote_open: bool,
+		quote_ref: String,
+		quote_version: String,
+		quote_error: Option<String>,
+		/// 正文输入框，插入经文时取光标位置
+		body_id: Option<egui::Id>,
+		/// 上次保存或删除失败的原因
+		error: Option<String>,
+}
+
+/// 编辑窗口中可修改的字段，用于判断是否有未保存的修改
+#[derive(PartialEq)]
+struct NoteFields {
+	subject: String,
+	title: String,
+	notebook_id: Option<String>,
+	tags: Vec<String>,
+	reference: String,
+	body: String,
+}
+
+impl NoteFields {
+	fn of(note: &Notedb) -> Self {
+		Self {
+			subject: note.subject.clone().unwrap_or_default(),
+			title: note.title.clone().unwrap_or_default(),
+			notebook_id: note.notebook_id.clone(),
+			tags: note.tags.clone(),
+			reference: note.reference.clone().unwrap_or_default(),
+			body: note.body.clone().unwrap_or_default(),
+		}
+	}
+}
+
+impl NoteApp {
+	pub fn new(mut note: Notedb, bible: BibleSource) -> Self {
+		// 旧笔记只有关键词字符串
+		if note.tags.is_empty() {
+			note.tags = split_keywords(note.keywords.as_deref().unwrap_or(""));
+		}
+		let known_tags = load_tag_counts("notes")
+			.into_iter()
+			.map(|(name, _)| name)
+			.collect();
+		let saved_fields = Some(NoteFields::of(&note));
+		let quote_ref = note.reference.clone().unwrap_or_default();
+		let quote_version = bible.current_version.clone();
+		Self {
+			note,
+			tag_input: String::new(),
+			known_tags,
+			notebooks: load_notebooks("notes"),
+			preview: false,
+			focus_pending: false,
+			saved_fields,
+			draft_fields: None,
+			last_autosave: 0.0,
+			confirm_close: false,
+			bible,
+			quote_open: false,
+			quote_ref,
+			quote_version,
+			quote_error: None,
+			body_id: None,
+			error: None,
+		}
+	}
+
+	/// 从草稿恢复的笔记，在保存之前一直视为有修改
+	pub fn from_draft(note: Notedb, bible: BibleSource) -> Self {
+		let mut app = Self::new(note, bible);
+		app.draft_fields = Some(NoteFields::of(&app.note));
+		app.saved_fields = None;
+		app
+	}
+
+	/// 是否有尚未保存到数据库的修改
+	pub fn is_dirty(&self) -> bool {
+		!self.tag_input.trim().is_empty()
+			|| self.saved_fields.as_ref() != Some(&NoteFields::of(&self.note))
+	}
+
+	/// 有修改且与上次草稿不同时写入草稿
+	pub fn flush_draft(&mut self) {
+		if !self.is_dirty() {
+			return;
+		}
+		let fields = NoteFields::of(&self.note);
+		if self.draft_fields.as_ref() == Some(&fields) {
+			return;
+		}
+		let now = crate::utils::now_timestamp();
+		match save_draft("notes", &self.note, &now) {
+			Ok(()) => self.draft_fields = Some(fields),
+			Err(e) => eprintln!("自动保存草稿失败 id={}: {:?}", self.note.id, e),
+		}
+	}
+
+	fn discard_draft(&mut self) {
+		if let Err(e) = remove_draft("notes", &self.note.id) {
+			eprintln!("删除草稿失败 id={}: {:?}", self.note.id, e);
+		}
+		self.draft_fields = None;
+	}
+
+	/// 把阅读器中选中的经文设为笔记的引用经文
+	pub fn set_reference(&mut self, r: &ScriptureRef) {
+		self.note.reference = Some(r.display());
+		self.quote_ref = r.display();
+		self.quote_open = true;
+		self.focus_pending = true;
+	}
+
+	fn quote_panel(&mut self, ui: &mut egui::Ui) {
+		ui.horizontal(|ui| {
+			ui.label(RichText::new("经文：").size(14.0));
+			let input = ui.add(
+				egui::TextEdit::singleline(&mut self.quote_ref)
+				.hint_text("如 约 3:16-18")
+				.desired_width(160.0)
+			);
+			egui::ComboBox::from_id_salt("quote_version")
+				.selected_text(version_display_name(&self.quote_version))
+				.show_ui(ui, |ui| {
+					for ver in &self.bible.versions {
+						ui.selectable_value(&mut self.quote_version, ver.clone(), version_display_name(ver));
+					}
+				});
+			let enter = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+			if ui.button("插入正文").clicked() || enter {
+				self.insert_quotation(ui.ctx());
+			}
+		});
+		if let Some(err) = &self.quote_error {
+			ui.label(RichText::new(err).size(12.0).color(egui::Color32::from_rgb(180, 30, 30)));
+		}
+	}
+
+	/// 在正文光标处（没有光标时在末尾）插入经文引用块
+	fn insert_quotation(&mut self, ctx: &egui::Context) {
+		let Some(r) = parse_reference(&self.quote_ref) else {
+			self.quote_error = Some(format!("无法识别的经文引用：{}", self.quote_ref));
+			return;
+		};
+		let Some(passage) = load_passage(&self.bible, &self.quote_version, &r) else {
+			self.quote_error = Some(format!("{} 中找不到 {}", version_display_name(&self.quote_version), r.display()));
+			return;
+		};
+		let quote = format_quotation(&r, &self.quote_version, &passage);
+
+		let cursor = self.body_id
+			.and_then(|id| egui::TextEdit::load_state(ctx, id))
+			.and_then(|state| state.cursor.char_range())
+			.map(|range| range.primary.index);
+		let body = self.note.body.get_or_insert_with(String::new);
+		let pos = cursor
+			.and_then(|c| body.char_indices().nth(c).map(|(b, _)| b))
+			.unwrap_or(body.len());
+
+		// 引用块前后各空一行，避免与相邻段落粘连
+		let mut insert = String::new();
+		if pos > 0 && !body[..pos].ends_with("\n\n") {
+			insert.push_str(if body[..pos].ends_with('\n') { "\n" } else { "\n\n" });
+		}
+		insert.push_str(&quote);
+		if pos < body.len() {
+			insert.push('\n');
+		}
+		body.insert_str(pos, &insert);
+
+		if self.note.reference.as_deref().is_none_or(|s| s.trim().is_empty()) {
+			self.note.reference = Some(r.display());
+		}
+		self.quote_error = None;
+	}
+
+	/// 移到回收站；失败时保留草稿和编辑窗口，并在窗口中显示原因
+	fn delete(&mut self) -> NoteEditorAction {
+		if let Err(e) = delete_note("notes", &self.note.id) {
+			eprintln!("删除笔记失败 id={}: {:?}", self.note.id, e);
+			self.error = Some(format!("删除失败：{}", e));
+			self.flush_draft();
+			return NoteEditorAction::None;
+		}
+		self.error = None;
+		self.discard_draft();
+		NoteEditorAction::Deleted(self.note.id.clone())
+	}
+
+	/// 保存到数据库；失败时不删草稿，并在窗口中显示原因
+	fn save(&mut self) -> NoteEditorAction {
+		self.commit_tag_input();
+		if let Err(e) = save_note("notes", &self.note) {
+			self.error = Some(format!("保存失败：{}", e));
+			self.confirm_close = false;
+			self.flush_draft();
+			return NoteEditorAction::None;
+		}
+		self.error = None;
+		self.saved_fields = Some(NoteFields::of(&self.note));
+		self.discard_draft();
+		NoteEditorAction::Saved(Box::new(self.note.clone()))
+	}
+
+	fn notebook_selector(&mut self, ui: &mut egui::Ui) {
+		let selected_text = match &self.note.notebook_id {
+			Some(id) => notebook_path(&self.notebooks, id),
+			None => "未归档".to_string(),
+		};
+		egui::ComboBox::from_id_salt("note_notebook")
+			.selected_text(selected_text)
+			.width(ui.available_width())
+			.show_ui(ui, |ui| {
+				ui.selectable_value(&mut self.note.notebook_id, None, "未归档");
+				for (depth, nb) in notebooks_in_tree_order(&self.notebooks) {
+					let text = format!("{}📁 {}", "　".repeat(depth), nb.name);
+					ui.selectable_value(&mut self.note.notebook_id, Some(nb.id.clone()), text);
+				}
+			});
+	}
+
+	/// 把输入框中的内容加入标签（可一次输入多个，用逗号等分隔）
+	fn commit_tag_input(&mut self) {
+		for tag in split_keywords(&self.tag_input) {
+			if !self.note.tags.contains(&tag) {
+				self.note.tags.push(tag);
+			}
+		}
+		self.tag_input.clear();
+		self.note.keywords = Some(self.note.tags.join(TAG_JOINER));
+	}
+
+	fn tag_editor(&mut self, ui: &mut egui::Ui) {
+		let mut removed: Option<usize> = None;
+		let mut commit = false;
+
+		ui.horizontal_wrapped(|ui| {
+			for (i, tag) in self.note.tags.iter().enumerate() {
+				if ui.small_button(format!("{} ✖", tag))
+					.on_hover_text("移除标签")
+					.clicked() {
+					removed = Some(i);
+				}
+			}
+
+			let input = ui.add(
+				egui::TextEdit::singleline(&mut self.tag_input)
+				.hint_text("输入标签，回车添加")
+				.desired_width(140.0)
+			);
+			if input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+				commit = true;
+				input.request_focus();
+			}
+			// 输入分隔符时立即成为标签
+			if split_keywords(&self.tag_input).len() > 1 || self.tag_input.ends_with([',', '，', '；', ';', '、']) {
+				commit = true;
+			}
+		});
+
+		// 自动补全候选
+		let typed = self.tag_input.trim().to_lowercase();
+		if !typed.is_empty() {
+			let suggestions: Vec<String> = self.known_tags.iter()
+				.filter(|t| t.to_lowercase().contains(&typed) && !self.note.tags.contains(t))
+				.take(MAX_TAG_SUGGESTIONS)
+				.cloned()
+				.collect();
+
+			if !suggestions.is_empty() {
+				ui.horizontal_wrapped(|ui| {
+					ui.label(RichText::new("已有标签：").size(12.0));
+					for tag in suggestions {
+						if ui.button(RichText::new(&tag).size(12.0)).clicked() {
+							self.tag_input = tag;
+							commit = true;
+						}
+					}
+				});
+			}
+		}
+
+		if let Some(i) = removed {
+			self.note.tags.remove(i);
+			self.note.keywords = Some(self.note.tags.join(TAG_JOINER));
+		}
+		if commit {
+			self.commit_tag_input();
+		}
+	}
 }
 
 fn note_visuals() -> egui::Visuals {
@@ -44,21 +358,167 @@ fn note_visuals() -> egui::Visuals {
 
 	v
 }
-impl eframe::App for NoteApp {
-	fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+/// 编辑窗口本帧的操作结果，由 BibleApp 同步到阅读器
+pub enum NoteEditorAction {
+	None,
+	Saved(Box<Notedb>),
+	Deleted(String),
+	GoTo(ScriptureRef),
+	Closed,
+}
+
+//笔记编辑窗口（与阅读器同一进程，以独立的 viewport 显示）
+impl NoteApp {
+	fn viewport_id(&self) -> egui::ViewportId {
+		egui::ViewportId::from_hash_of(("note_editor", &self.note.id))
+	}
+
+	/// 让已打开的编辑窗口回到前台
+	pub fn request_focus(&mut self) {
+		self.focus_pending = true;
+	}
+
+	pub fn show(&mut self, ctx: &egui::Context) -> NoteEditorAction {
+		let title = match self.note.title.as_deref() {
+			Some(t) if !t.is_empty() => format!("撰写笔记 - {}", t),
+			_ => "撰写笔记".to_string(),
+		};
+		let builder = egui::ViewportBuilder::default()
+			.with_inner_size([600.0, 600.0])
+			.with_title(title.clone());
+
+		if std::mem::take(&mut self.focus_pending) {
+			ctx.send_viewport_cmd_to(self.viewport_id(), egui::ViewportCommand::Focus);
+		}
 
-		ctx.set_visuals(note_visuals());
+		// 定时自动保存草稿
+		if self.is_dirty() {
+			let now = ctx.input(|i| i.time);
+			if now - self.last_autosave >= AUTOSAVE_INTERVAL {
+				self.last_autosave = now;
+				self.flush_draft();
+			}
+			ctx.request_repaint_after(std::time::Duration::from_secs_f64(AUTOSAVE_INTERVAL));
+		}
 
-		let note = &mut self.note;
+		ctx.show_viewport_immediate(self.viewport_id(), builder, |ctx, class| {
+			if class == egui::ViewportClass::Embedded {
+				// 平台不支持多窗口时退化为普通窗口
+				let mut open = true;
+				let mut action = NoteEditorAction::None;
+				egui::Window::new(title.as_str())
+					.id(egui::Id::new(("note_editor_window", &self.note.id)))
+					.open(&mut open)
+					.default_size([600.0, 600.0])
+					.show(ctx, |ui| action = self.editor_ui(ui));
+				if !open {
+					return self.request_close();
+				}
+				return action;
+			}
 
-		egui::CentralPanel::default().show(ctx, |ui| {
+			if ctx.input(|i| i.viewport().close_requested()) {
+				let action = self.request_close();
+				if matches!(action, NoteEditorAction::None) {
+					ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+				} else {
+					return action;
+				}
+			}
+			let mut action = NoteEditorAction::None;
+			egui::CentralPanel::default()
+				.frame(egui::Frame::central_panel(&ctx.style()).fill(note_visuals().panel_fill))
+				.show(ctx, |ui| action = self.editor_ui(ui));
+			action
+		})
+	}
+
+	/// 关闭窗口：有未保存的修改时先询问
+	fn request_close(&mut self) -> NoteEditorAction {
+		if self.is_dirty() {
+			self.confirm_close = true;
+			NoteEditorAction::None
+		} else {
+			self.discard_draft();
+			NoteEditorAction::Closed
+		}
+	}
+
+	fn editor_ui(&mut self, ui: &mut egui::Ui) -> NoteEditorAction {
+		// 同时打开多条笔记时，各窗口的控件 id 互不冲突
+		ui.push_id(self.note.id.clone(), |ui| self.editor_contents(ui)).inner
+	}
+
+	fn editor_contents(&mut self, ui: &mut egui::Ui) -> NoteEditorAction {
+		let mut action = NoteEditorAction::None;
+		ui.style_mut().visuals = note_visuals();
+
+		egui::TopBottomPanel::bottom("note_bottom_panel")
+			.frame(egui::Frame::new().fill(note_visuals().panel_fill))
+			.show_inside(ui, |ui| {
+			if let Some(err) = &self.error {
+				ui.add_space(5.0);
+				ui.label(RichText::new(format!("⚠ {}", err))
+					.color(egui::Color32::from_rgb(180, 30, 30)));
+			}
+			if self.confirm_close {
+				ui.add_space(5.0);
+				ui.horizontal(|ui| {
+					ui.label(RichText::new("⚠ 笔记有未保存的修改，是否保存？").strong());
+					ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+						if ui.button("取消").clicked() {
+							self.confirm_close = false;
+						}
+						if ui.button("不保存").clicked() {
+							self.discard_draft();
+							action = NoteEditorAction::Closed;
+						}
+						if ui.button("保存").clicked() {
+							action = self.save();
+						}
+					});
+				});
+				ui.separator();
+			}
+			ui.add_space(5.0);
+			ui.horizontal(|ui| {
+				let btn_w = 80.0;
+				let btn_h = 28.0;
+				ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+					if ui.add_sized([btn_w, btn_h], egui::Button::new("🗑删除"))
+						.on_hover_text("移到回收站，可在笔记列表的回收站中恢复")
+						.on_hover_cursor(egui::CursorIcon::Default)
+						.clicked() {
+						action = self.delete();
+					}
+				});
+				ui.add_space(15.0);
+				ui.toggle_value(&mut self.preview, "👁 预览")
+					.on_hover_text("在正文旁显示 Markdown 预览");
+				ui.toggle_value(&mut self.quote_open, "📖 插入经文")
+					.on_hover_text("把任一译本的经文以引用块插入正文");
+				ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+					if ui.add_sized([btn_w, btn_h], egui::Button::new("保存"))
+						.on_hover_cursor(egui::CursorIcon::Default)
+						.clicked() {
+						action = self.save();
+					}
+				});
+			});
+			ui.add_space(2.0);
+		});
+
+		egui::CentralPanel::default()
+			.frame(egui::Frame::new())
+			.show_inside(ui, |ui| {
+			let mut preview_click: Option<ScriptureRef> = None;
 			let label_width = 90.0;
 			ui.collapsing("笔记标题", |ui| {
 				ui.horizontal(|ui| {
 					ui.add_sized([label_width, 0.0],
 						egui::Label::new(RichText::new("主题：").size(14.0)));
 					let subject_text_edit = egui::TextEdit::singleline(
-						note.subject.get_or_insert(String::new()))
+						self.note.subject.get_or_insert(String::new()))
 						.desired_width(ui.available_width());
 					ui.add(subject_text_edit);
 				});
@@ -66,23 +526,27 @@ impl eframe::App for NoteApp {
 					ui.add_sized([label_width, 0.0], 
 						egui::Label::new(RichText::new("标题：").size(14.0)));
 					let title_text_edit = egui::TextEdit::singleline(
-						note.title.get_or_insert(String::new()))
+						self.note.title.get_or_insert(String::new()))
 						.desired_width(ui.available_width());
 					ui.add(title_text_edit);
 				});
+				ui.horizontal(|ui| {
+					ui.add_sized([label_width, 0.0],
+						egui::Label::new(RichText::new("笔记本：").size(14.0)));
+					self.notebook_selector(ui);
+				});
 				ui.horizontal(|ui| {
 					ui.add_sized([label_width, 0.0], 
-						egui::Label::new(RichText::new("关键词：").size(14.0)));
-					let keyword_text_edit = egui::TextEdit::singleline(
-						note.keywords.get_or_insert(String::new()))
-						.desired_width(ui.available_width());
-					ui.add(keyword_text_edit);
+						egui::Label::new(RichText::new("标签：").size(14.0)));
+					ui.vertical(|ui| {
+						self.tag_editor(ui);
+					});
 				});
 				ui.horizontal(|ui| {
 					ui.add_sized([label_width, 0.0],
 						egui::Label::new(RichText::new("引用经文：").size(14.0)));
 					let ref_text_edit = egui::TextEdit::singleline(
-						note.reference.get_or_insert(String::new()))
+						self.note.reference.get_or_insert(String::new()))
 						.desired_width(ui.available_width());
 					ui.add(ref_text_edit);
 				});
@@ -90,43 +554,43 @@ impl eframe::App for NoteApp {
 
 			ui.separator();
 
-			ScrollArea::vertical().show(ui, |ui| {
-				ui.add_sized(
-					[ui.available_width(), ui.available_height()],
-					egui::TextEdit::multiline(note.body.get_or_insert(String::new()))
-					.hint_text("笔记正文"),
-				);
-			}); 
-			ui.separator();
-		});
+			if self.quote_open {
+				self.quote_panel(ui);
+				ui.separator();
+			}
 
-		egui::TopBottomPanel::bottom("note_bottom_panel").show(ctx, |ui| {
-			ui.add_space(5.0);
-			ui.horizontal(|ui| {
-				let btn_w = 80.0;
-				let btn_h = 28.0;
-				ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
-					if ui.add_sized([btn_w, btn_h], egui::Button::new("🗑删除"))
-						.on_hover_cursor(egui::CursorIcon::Default)
-						.clicked() {
-						if let Err(e) = delete_note("notes", &note.id) {
-							eprintln!("删除笔记失败 id={}: {:?}", note.id, e);
-						} else {
-							println!("删除笔记 id={}", note.id);
+			if self.preview {
+				ui.columns(2, |cols| {
+					ScrollArea::vertical().id_salt("note_body_editor").show(&mut cols[0], |ui| {
+						let resp = ui.add_sized(
+							[ui.available_width(), ui.available_height()],
+							egui::TextEdit::multiline(self.note.body.get_or_insert(String::new()))
+							.hint_text("笔记正文（支持 Markdown）"),
+						);
+						self.body_id = Some(resp.id);
+					});
+					ScrollArea::vertical().id_salt("note_body_preview").show(&mut cols[1], |ui| {
+						if let Some(r) = render_markdown(ui, self.note.body.as_deref().unwrap_or("")) {
+							preview_click = Some(r);
 						}
-						ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-					}
+					});
 				});
-				ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-					if ui.add_sized([btn_w, btn_h], egui::Button::new("保存"))
-						.on_hover_cursor(egui::CursorIcon::Default)
-						.clicked() {
-						save_note("notes", note);
-						ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-					}
+			} else {
+				ScrollArea::vertical().show(ui, |ui| {
+					let resp = ui.add_sized(
+						[ui.available_width(), ui.available_height()],
+						egui::TextEdit::multiline(self.note.body.get_or_insert(String::new()))
+						.hint_text("笔记正文（支持 Markdown）"),
+					);
+					self.body_id = Some(resp.id);
 				});
-			});
-			ui.add_space(2.0);
+			}
+			ui.separator();
+			if let Some(r) = preview_click {
+				action = NoteEditorAction::GoTo(r);
+			}
 		});
+
+		action
 	}
 }
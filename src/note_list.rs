@@ -0,0 +1,335 @@
+use std::cmp::{Ordering, Reverse};
+use crate::theme::ThemeColors;
+use crate::notes::{Notedb, note_time};
+use crate::reference::{book_full_name, parse_reference};
+use crate::utils::{parse_timestamp, timestamp_sort_key, version_display_name};
+
+/// 笔记列表的排序方式
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum NoteSort {
+	/// 按读取顺序：修改时间，搜索时按相关度
+	#[default]
+	Default,
+	/// 按书卷顺序与经文引用
+	Reference,
+	Created,
+	Updated,
+	Title,
+}
+
+impl NoteSort {
+	const ALL: [NoteSort; 5] = [NoteSort::Default, NoteSort::Reference, NoteSort::Created, NoteSort::Updated, NoteSort::Title];
+
+	fn label(self) -> &'static str {
+		match self {
+			NoteSort::Default => "默认",
+			NoteSort::Reference => "经文顺序",
+			NoteSort::Created => "创建时间",
+			NoteSort::Updated => "修改时间",
+			NoteSort::Title => "标题",
+		}
+	}
+}
+
+/// 笔记列表的排序、筛选与分组设置
+#[derive(Default)]
+pub struct NoteListView {
+	pub sort: NoteSort,
+	/// 按书卷、章分组显示
+	pub grouped: bool,
+	pub version: Option<String>,
+	pub book_num: Option<i32>,
+	pub date_from: String,
+	pub date_to: String,
+}
+
+/// 书卷、章、起始节，用于按经文顺序排序；没有关联经文的排在最后
+fn reference_key(note: &Notedb) -> (i32, i32, i32) {
+	let parsed = note.reference.as_deref().and_then(parse_reference);
+	let book = note.book_num.or(parsed.as_ref().map(|r| r.book_num)).unwrap_or(i32::MAX);
+	let chapter = note.chapter.as_deref()
+		.and_then(|c| c.parse().ok())
+		.or(parsed.as_ref().map(|r| r.chapter))
+		.unwrap_or(0);
+	let verse = parsed.and_then(|r| r.verse_start).unwrap_or(0);
+	(book, chapter, verse)
+}
+
+/// 解析筛选用的日期：空为不限，格式不对为 Err
+pub fn parse_filter_date(s: &str) -> Result<Option<chrono::NaiveDate>, ()> {
+	let s = s.trim();
+	if s.is_empty() {
+		return Ok(None);
+	}
+	chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").map(Some).map_err(|_| ())
+}
+
+/// 笔记的本地日期；兼容旧版本只记录日期的值
+fn note_date(s: &str) -> Option<chrono::NaiveDate> {
+	parse_timestamp(s)
+		.map(|t| t.with_timezone(&chrono::Local).date_naive())
+		.or_else(|| chrono::NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok())
+}
+
+/// 笔记日期（修改日期，没有则用创建日期）是否在起止日期之间；格式不对的起止日期不参与筛选
+pub fn in_date_range(note: &Notedb, date_from: &str, date_to: &str) -> bool {
+	let from = parse_filter_date(date_from).ok().flatten();
+	let to = parse_filter_date(date_to).ok().flatten();
+	if from.is_none() && to.is_none() {
+		return true;
+	}
+	let Some(day) = note_time(note).and_then(note_date) else {
+		return false;
+	};
+	from.is_none_or(|f| day >= f) && to.is_none_or(|t| day <= t)
+}
+
+/// 日期输入框，格式不对时文字标红
+pub fn date_edit<'t>(text: &'t mut String, hint: &str, bad: bool) -> egui::TextEdit<'t> {
+	let edit = egui::TextEdit::singleline(text).hint_text(hint).desired_width(100.0);
+	if bad { edit.text_color(egui::Color32::RED) } else { edit }
+}
+
+impl NoteListView {
+	/// 起止日期中格式不对的输入（不参与筛选）
+	pub fn invalid_dates(&self) -> (bool, bool) {
+		(parse_filter_date(&self.date_from).is_err(), parse_filter_date(&self.date_to).is_err())
+	}
+
+	/// 按译本、书卷和日期（修改日期，没有则用创建日期）筛选
+	pub fn matches(&self, note: &Notedb) -> bool {
+		if self.version.is_some() && note.version != self.version {
+			return false;
+		}
+		if self.book_num.is_some() && note.book_num != self.book_num {
+			return false;
+		}
+		// 格式不对的日期不参与筛选，由工具栏提示
+		in_date_range(note, &self.date_from, &self.date_to)
+	}
+
+	pub fn sort(&self, notes: &mut [&Notedb]) {
+		let by_title = |a: &Notedb, b: &Notedb| a.title.as_deref().unwrap_or("").cmp(b.title.as_deref().unwrap_or(""));
+		match self.sort {
+			NoteSort::Default => {}
+			NoteSort::Reference => notes.sort_by(|a, b| reference_key(a).cmp(&reference_key(b)).then_with(|| by_title(a, b))),
+			NoteSort::Created => notes.sort_by_cached_key(|n| Reverse(timestamp_sort_key(n.created_at.as_deref()))),
+			NoteSort::Updated => notes.sort_by_cached_key(|n| Reverse(timestamp_sort_key(note_time(n)))),
+			NoteSort::Title => notes.sort_by(|a, b| by_title(a, b)),
+		}
+	}
+
+	/// 分组显示时，组按经文顺序排列，组内保持当前排序
+	pub fn group<'a>(&self, notes: &[&'a Notedb]) -> Vec<(Option<i32>, Option<i32>, Vec<&'a Notedb>)> {
+		let mut groups: Vec<(Option<i32>, Option<i32>, Vec<&'a Notedb>)> = Vec::new();
+		for note in notes {
+			let chapter = note.chapter.as_deref().and_then(|c| c.parse().ok());
+			match groups.iter_mut().find(|(b, c, _)| *b == note.book_num && *c == chapter) {
+				Some((_, _, list)) => list.push(note),
+				None => groups.push((note.book_num, chapter, vec![note])),
+			}
+		}
+		groups.sort_by(|a, b| match (a.0, b.0) {
+			(Some(x), Some(y)) => (x, a.1).cmp(&(y, b.1)),
+			(Some(_), None) => Ordering::Less,
+			(None, Some(_)) => Ordering::Greater,
+			(None, None) => Ordering::Equal,
+		});
+		groups
+	}
+}
+
+/// 分组标题，例如「约翰福音 3 章」
+pub fn group_title(book_num: Option<i32>, chapter: Option<i32>) -> String {
+	match (book_num, chapter) {
+		(Some(b), Some(c)) => format!("{} {} 章", book_full_name(b), c),
+		(Some(b), None) => book_full_name(b).to_string(),
+		_ => "未关联经文".to_string(),
+	}
+}
+
+/// 排序、分组与筛选工具栏
+pub fn draw_list_toolbar(ui: &mut egui::Ui, colors: &ThemeColors, view: &mut NoteListView, notes: &[Notedb]) {
+	let mut versions: Vec<String> = notes.iter().filter_map(|n| n.version.clone()).filter(|v| !v.is_empty()).collect();
+	versions.sort();
+	versions.dedup();
+	let mut books: Vec<i32> = notes.iter().filter_map(|n| n.book_num).collect();
+	books.sort();
+	books.dedup();
+
+	ui.horizontal_wrapped(|ui| {
+		ui.label(egui::RichText::new("排序").size(12.0).color(colors.comment_text_color));
+		egui::ComboBox::from_id_salt("notes_sort")
+			.selected_text(view.sort.label())
+			.width(80.0)
+			.show_ui(ui, |ui| {
+				for s in NoteSort::ALL {
+					ui.selectable_value(&mut view.sort, s, s.label());
+				}
+			});
+		ui.checkbox(&mut view.grouped, "按章分组");
+
+		ui.label(egui::RichText::new("筛选").size(12.0).color(colors.comment_text_color));
+		egui::ComboBox::from_id_salt("notes_version_filter")
+			.selected_text(view.version.as_deref().map(version_display_name).unwrap_or("全部译本".to_string()))
+			.width(90.0)
+			.show_ui(ui, |ui| {
+				ui.selectable_value(&mut view.version, None, "全部译本");
+				for v in versions {
+					let label = version_display_name(&v);
+					ui.selectable_value(&mut view.version, Some(v), label);
+				}
+			});
+		egui::ComboBox::from_id_salt("notes_book_filter")
+			.selected_text(view.book_num.map(book_full_name).unwrap_or("全部书卷"))
+			.width(90.0)
+			.show_ui(ui, |ui| {
+				ui.selectable_value(&mut view.book_num, None, "全部书卷");
+				for b in books {
+					ui.selectable_value(&mut view.book_num, Some(b), book_full_name(b));
+				}
+			});
+		let (bad_from, bad_to) = view.invalid_dates();
+		ui.add(date_edit(&mut view.date_from, "起始 2024-01-01", bad_from));
+		ui.label("—");
+		ui.add(date_edit(&mut view.date_to, "截止", bad_to));
+		if bad_from || bad_to {
+			ui.label(egui::RichText::new("日期格式应为 2024-01-01，暂不筛选").size(12.0).color(egui::Color32::RED));
+		}
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn note_updated(updated_at: &str) -> Notedb {
+		Notedb { updated_at: Some(updated_at.to_string()), ..Default::default() }
+	}
+
+	#[test]
+	fn parses_filter_dates() {
+		assert_eq!(parse_filter_date("  "), Ok(None));
+		assert_eq!(parse_filter_date("2024-01-05"), Ok(chrono::NaiveDate::from_ymd_opt(2024, 1, 5)));
+		assert_eq!(parse_filter_date("2024-1-5"), Ok(chrono::NaiveDate::from_ymd_opt(2024, 1, 5)));
+		assert!(parse_filter_date("2024/01/05").is_err());
+		assert!(parse_filter_date("2024-13-01").is_err());
+	}
+
+	#[test]
+	fn filters_by_date_range_inclusive() {
+		let note = note_updated("2024-05-01");
+		assert!(in_date_range(&note, "2024-05-01", "2024-05-01"));
+		assert!(in_date_range(&note, "2024-4-30", ""));
+		assert!(!in_date_range(&note, "2024-05-02", ""));
+		assert!(!in_date_range(&note, "", "2024-04-30"));
+	}
+
+	#[test]
+	fn invalid_dates_do_not_filter() {
+		let note = note_updated("2024-05-01");
+		assert!(in_date_range(&note, "昨天", ""));
+		assert!(!in_date_range(&note, "昨天", "2024-04-30"));
+	}
+
+	#[test]
+	fn uses_the_local_date_of_full_timestamps() {
+		let local = chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+			.and_hms_opt(23, 30, 0).unwrap()
+			.and_local_timezone(chrono::Local).unwrap()
+			.to_rfc3339();
+		assert!(in_date_range(&note_updated(&local), "2024-05-01", "2024-05-01"));
+	}
+
+	fn note(id: &str, book_num: Option<i32>, chapter: Option<&str>, reference: Option<&str>) -> Notedb {
+		Notedb {
+			id: id.to_string(),
+			title: Some(id.to_string()),
+			book_num,
+			chapter: chapter.map(str::to_string),
+			reference: reference.map(str::to_string),
+			..Default::default()
+		}
+	}
+
+	fn ids(notes: &[&Notedb]) -> Vec<String> {
+		notes.iter().map(|n| n.id.clone()).collect()
+	}
+
+	#[test]
+	fn sorts_by_reference_then_title() {
+		let notes = [
+			note("free", None, None, None),
+			note("jn3:16", Some(43), Some("3"), Some("约3:16")),
+			note("ref-only", None, None, Some("创1:1")),
+			note("jn3:1", Some(43), Some("3"), Some("约3:1")),
+			note("jn10", Some(43), Some("10"), None),
+		];
+		let view = NoteListView { sort: NoteSort::Reference, ..Default::default() };
+		let mut list: Vec<&Notedb> = notes.iter().collect();
+		view.sort(&mut list);
+		assert_eq!(ids(&list), vec!["ref-only", "jn3:1", "jn3:16", "jn10", "free"]);
+	}
+
+	#[test]
+	fn sorts_by_time_newest_first() {
+		let mut old = note_updated("2024-01-01T10:00:00+00:00");
+		old.id = "old".to_string();
+		old.created_at = Some("2024-03-01T10:00:00+00:00".to_string());
+		let mut new = note_updated("2024-02-01T10:00:00+00:00");
+		new.id = "new".to_string();
+		new.created_at = Some("2023-12-01T10:00:00+00:00".to_string());
+		let notes = [old, new];
+
+		let mut list: Vec<&Notedb> = notes.iter().collect();
+		NoteListView { sort: NoteSort::Updated, ..Default::default() }.sort(&mut list);
+		assert_eq!(ids(&list), vec!["new", "old"]);
+		NoteListView { sort: NoteSort::Created, ..Default::default() }.sort(&mut list);
+		assert_eq!(ids(&list), vec!["old", "new"]);
+	}
+
+	#[test]
+	fn groups_by_chapter_in_scripture_order() {
+		let notes = [
+			note("free", None, None, None),
+			note("jn3b", Some(43), Some("3"), None),
+			note("gen1", Some(1), Some("1"), None),
+			note("jn3a", Some(43), Some("3"), None),
+		];
+		let list: Vec<&Notedb> = notes.iter().collect();
+		let groups: Vec<(String, Vec<String>)> = NoteListView::default().group(&list).into_iter()
+			.map(|(b, c, list)| (group_title(b, c), ids(&list)))
+			.collect();
+		assert_eq!(groups, vec![
+			("创世记 1 章".to_string(), vec!["gen1".to_string()]),
+			("约翰福音 3 章".to_string(), vec!["jn3b".to_string(), "jn3a".to_string()]),
+			("未关联经文".to_string(), vec!["free".to_string()]),
+		]);
+	}
+
+	#[test]
+	fn lists_notes_matching_every_filter() {
+		let mut notes = vec![
+			note("a", Some(43), Some("3"), None),
+			note("b", Some(43), Some("3"), None),
+			note("c", Some(1), Some("1"), None),
+			note("d", Some(43), Some("3"), None),
+		];
+		notes[0].tags = vec!["恩典".to_string()];
+		notes[1].tags = vec!["恩典".to_string()];
+		notes[1].version = Some("kjv".to_string());
+		notes[2].tags = vec!["恩典".to_string()];
+		notes[0].notebook_id = Some("nb".to_string());
+		notes[1].notebook_id = Some("nb".to_string());
+		notes[2].notebook_id = Some("nb".to_string());
+		let notebooks = [crate::notebooks::Notebook { id: "nb".to_string(), name: "讲道".to_string(), parent_id: None }];
+		let view = NoteListView { book_num: Some(43), sort: NoteSort::Title, ..Default::default() };
+		let filter = crate::notebooks::NotebookFilter::Notebook("nb".to_string());
+
+		let listed = crate::notes::listed_notes(&notes, &filter, Some("恩典"), &notebooks, &view);
+		assert_eq!(ids(&listed), vec!["a", "b"]);
+		let view = NoteListView { version: Some("kjv".to_string()), ..view };
+		let listed = crate::notes::listed_notes(&notes, &filter, Some("恩典"), &notebooks, &view);
+		assert_eq!(ids(&listed), vec!["b"]);
+	}
+}
@@ -0,0 +1,439 @@
+use rusqlite::Connection;
+use uuid::Uuid;
+use crate::utils::now_timestamp;
+use crate::theme::ThemeColors;
+use crate::notes::{Notedb, notes_db_path, table_exists};
+
+/// 笔记本（可嵌套的文件夹）
+#[derive(Debug, Clone)]
+pub struct Notebook {
+	pub id: String,
+	pub name: String,
+	pub parent_id: Option<String>,
+}
+
+/// 笔记列表当前浏览的位置
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum NotebookFilter {
+	#[default]
+	All,
+	Unfiled,
+	Notebook(String),
+}
+
+/// 正在新建 / 重命名的笔记本及输入框内容
+#[derive(Debug, Clone)]
+pub enum NotebookEdit {
+	Create { parent_id: Option<String>, name: String },
+	Rename { id: String, name: String },
+}
+
+/// 笔记本侧栏的操作结果
+pub enum NotebookAction {
+	None,
+	Select(NotebookFilter),
+	Create(Option<String>, String),
+	Rename(String, String),
+	Delete(String),
+}
+
+fn notebooks_table(category: &str) -> String {
+	format!("{}_notebooks", category)
+}
+
+/// 建立笔记本表
+pub fn ensure_notebook_schema(conn: &Connection, category: &str) -> rusqlite::Result<()> {
+	conn.execute_batch(&format!(
+		"CREATE TABLE IF NOT EXISTS {} (
+			id TEXT PRIMARY KEY,
+			name TEXT NOT NULL,
+			parent_id TEXT,
+			created_at TEXT
+		);",
+		notebooks_table(category)
+	))
+}
+
+/// 读取全部笔记本（按名称排序）
+pub fn load_notebooks(category: &str) -> Vec<Notebook> {
+	let Some(db_path) = notes_db_path() else { return Vec::new() };
+	let conn = match Connection::open(&db_path) {
+		Ok(c) => c,
+		Err(_) => return Vec::new(),
+	};
+	read_notebooks(&conn, category)
+}
+
+/// 从指定数据库读取笔记本（导入其他 note.db 时也用它）
+pub fn read_notebooks(conn: &Connection, category: &str) -> Vec<Notebook> {
+	if !table_exists(conn, &notebooks_table(category)) {
+		return Vec::new();
+	}
+
+	let sql = format!("SELECT id, name, parent_id FROM {} ORDER BY name", notebooks_table(category));
+	let mut stmt = match conn.prepare(&sql) {
+		Ok(s) => s,
+		Err(_) => return Vec::new(),
+	};
+	let rows = stmt.query_map([], |row| {
+		Ok(Notebook {
+			id: row.get(0)?,
+			name: row.get(1)?,
+			parent_id: row.get(2)?,
+		})
+	});
+	match rows {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
+fn open_with_schema(category: &str) -> rusqlite::Result<Option<Connection>> {
+	let Some(db_path) = notes_db_path() else { return Ok(None) };
+	let conn = Connection::open(&db_path)?;
+	crate::notes::ensure_notes_schema(&conn, category)?;
+	Ok(Some(conn))
+}
+
+/// 新建笔记本，返回其 id
+pub fn create_notebook(category: &str, parent_id: Option<&str>, name: &str) -> rusqlite::Result<Option<String>> {
+	let name = name.trim();
+	if name.is_empty() {
+		return Ok(None);
+	}
+	let Some(conn) = open_with_schema(category)? else { return Ok(None) };
+
+	let id = Uuid::new_v4().to_string();
+	conn.execute(
+		&format!("INSERT INTO {} (id, name, parent_id, created_at) VALUES (?1, ?2, ?3, ?4)", notebooks_table(category)),
+		rusqlite::params![id, name, parent_id, now_timestamp()],
+	)?;
+	println!("已新建笔记本「{}」", name);
+	Ok(Some(id))
+}
+
+pub fn rename_notebook(category: &str, id: &str, name: &str) -> rusqlite::Result<()> {
+	let name = name.trim();
+	if name.is_empty() {
+		return Ok(());
+	}
+	let Some(conn) = open_with_schema(category)? else { return Ok(()) };
+	conn.execute(
+		&format!("UPDATE {} SET name = ?1 WHERE id = ?2", notebooks_table(category)),
+		[name, id],
+	)?;
+	Ok(())
+}
+
+/// 删除笔记本：其中的笔记和子笔记本移到上一级（顶层则为未归档）
+pub fn delete_notebook(category: &str, id: &str) -> rusqlite::Result<()> {
+	let Some(mut conn) = open_with_schema(category)? else { return Ok(()) };
+	let table = notebooks_table(category);
+	let tx = conn.transaction()?;
+
+	let parent_id: Option<String> = tx.query_row(
+		&format!("SELECT parent_id FROM {} WHERE id = ?1", table),
+		[id],
+		|row| row.get(0),
+	)?;
+	tx.execute(
+		&format!("UPDATE {} SET notebook_id = ?1 WHERE notebook_id = ?2", category),
+		rusqlite::params![parent_id, id],
+	)?;
+	tx.execute(
+		&format!("UPDATE {} SET parent_id = ?1 WHERE parent_id = ?2", table),
+		rusqlite::params![parent_id, id],
+	)?;
+	tx.execute(&format!("DELETE FROM {} WHERE id = ?1", table), [id])?;
+	tx.commit()?;
+
+	println!("已删除笔记本 id={}", id);
+	Ok(())
+}
+
+/// 按「讲道 / 2024」这样的路径找到笔记本，不存在的层级逐级新建
+pub fn ensure_notebook_path(category: &str, path: &str) -> rusqlite::Result<Option<String>> {
+	let mut parent: Option<String> = None;
+	for name in path.split(" / ").map(str::trim).filter(|n| !n.is_empty()) {
+		let existing = load_notebooks(category)
+			.into_iter()
+			.find(|nb| nb.name == name && nb.parent_id == parent)
+			.map(|nb| nb.id);
+		parent = match existing {
+			Some(id) => Some(id),
+			None => create_notebook(category, parent.as_deref(), name)?,
+		};
+	}
+	Ok(parent)
+}
+
+/// 把笔记移到笔记本（None 表示未归档）
+pub fn move_note_to_notebook(category: &str, note_id: &str, notebook_id: Option<&str>) -> rusqlite::Result<()> {
+	let Some(conn) = open_with_schema(category)? else { return Ok(()) };
+	conn.execute(
+		&format!("UPDATE {} SET notebook_id = ?1 WHERE id = ?2", category),
+		rusqlite::params![notebook_id, note_id],
+	)?;
+	Ok(())
+}
+
+/// 笔记本及其全部子笔记本的 id
+pub fn notebook_with_descendants(notebooks: &[Notebook], id: &str) -> Vec<String> {
+	let mut ids = vec![id.to_string()];
+	let mut i = 0;
+	while i < ids.len() {
+		let current = ids[i].clone();
+		for nb in notebooks {
+			// 防止损坏数据造成的循环
+			if nb.parent_id.as_deref() == Some(current.as_str()) && !ids.contains(&nb.id) {
+				ids.push(nb.id.clone());
+			}
+		}
+		i += 1;
+	}
+	ids
+}
+
+/// 笔记是否属于当前浏览的位置（包含子笔记本）
+pub fn note_in_filter(note: &Notedb, filter: &NotebookFilter, notebooks: &[Notebook]) -> bool {
+	match filter {
+		NotebookFilter::All => true,
+		NotebookFilter::Unfiled => note.notebook_id.is_none(),
+		NotebookFilter::Notebook(id) => note.notebook_id.as_ref()
+			.is_some_and(|nb| notebook_with_descendants(notebooks, id).contains(nb)),
+	}
+}
+
+/// 笔记本的显示路径，例如「讲道 / 2024」
+pub fn notebook_path(notebooks: &[Notebook], id: &str) -> String {
+	let mut parts = Vec::new();
+	let mut current = Some(id.to_string());
+	while let Some(cur) = current {
+		let Some(nb) = notebooks.iter().find(|n| n.id == cur) else { break };
+		if parts.len() > notebooks.len() {
+			break;
+		}
+		parts.push(nb.name.clone());
+		current = nb.parent_id.clone();
+	}
+	parts.reverse();
+	parts.join(" / ")
+}
+
+/// 按树形顺序排列的笔记本及其层级，用于菜单和下拉框
+pub fn notebooks_in_tree_order(notebooks: &[Notebook]) -> Vec<(usize, &Notebook)> {
+	fn visit<'a>(notebooks: &'a [Notebook], parent: Option<&str>, depth: usize, out: &mut Vec<(usize, &'a Notebook)>) {
+		for nb in notebooks.iter().filter(|n| n.parent_id.as_deref() == parent) {
+			if out.iter().any(|(_, seen)| seen.id == nb.id) {
+				continue;
+			}
+			out.push((depth, nb));
+			visit(notebooks, Some(&nb.id), depth + 1, out);
+		}
+	}
+	let mut out = Vec::new();
+	visit(notebooks, None, 0, &mut out);
+	out
+}
+
+/// 笔记本树侧栏
+pub fn draw_notebook_sidebar(
+	ui: &mut egui::Ui,
+	colors: &ThemeColors,
+	notebooks: &[Notebook],
+	notes: &[Notedb],
+	selected: &NotebookFilter,
+	edit_state: &mut Option<NotebookEdit>,
+) -> NotebookAction {
+	let mut action = NotebookAction::None;
+
+	ui.horizontal(|ui| {
+		ui.label(egui::RichText::new("笔记本").strong());
+		ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+			if ui.small_button("➕").on_hover_text("新建笔记本").clicked() {
+				*edit_state = Some(NotebookEdit::Create { parent_id: None, name: String::new() });
+			}
+		});
+	});
+	ui.separator();
+
+	let all_text = format!("全部笔记 ({})", notes.len());
+	if ui.selectable_label(*selected == NotebookFilter::All, all_text).clicked() {
+		action = NotebookAction::Select(NotebookFilter::All);
+	}
+	let unfiled = notes.iter().filter(|n| n.notebook_id.is_none()).count();
+	if ui.selectable_label(*selected == NotebookFilter::Unfiled, format!("未归档 ({})", unfiled)).clicked() {
+		action = NotebookAction::Select(NotebookFilter::Unfiled);
+	}
+
+	draw_notebook_level(ui, notebooks, notes, None, selected, edit_state, &mut action);
+
+	if let Some(edit) = edit_state {
+		ui.separator();
+		let (title, name) = match edit {
+			NotebookEdit::Create { parent_id, name } => {
+				let title = match parent_id {
+					Some(pid) => format!("在「{}」下新建", notebook_path(notebooks, pid)),
+					None => "新建笔记本".to_string(),
+				};
+				(title, name)
+			}
+			NotebookEdit::Rename { id, name } => (format!("重命名「{}」", notebook_path(notebooks, id)), name),
+		};
+		ui.label(egui::RichText::new(title).size(12.0).color(colors.comment_text_color));
+		ui.add(egui::TextEdit::singleline(name).desired_width(f32::INFINITY));
+
+		let mut finished = false;
+		ui.horizontal(|ui| {
+			if ui.button("确定").clicked() {
+				action = match edit {
+					NotebookEdit::Create { parent_id, name } => NotebookAction::Create(parent_id.clone(), name.trim().to_string()),
+					NotebookEdit::Rename { id, name } => NotebookAction::Rename(id.clone(), name.trim().to_string()),
+				};
+				finished = true;
+			}
+			if ui.button("取消").clicked() {
+				finished = true;
+			}
+		});
+		if finished {
+			*edit_state = None;
+		}
+	}
+
+	action
+}
+
+fn draw_notebook_level(
+	ui: &mut egui::Ui,
+	notebooks: &[Notebook],
+	notes: &[Notedb],
+	parent: Option<&str>,
+	selected: &NotebookFilter,
+	edit_state: &mut Option<NotebookEdit>,
+	action: &mut NotebookAction,
+) {
+	for nb in notebooks.iter().filter(|n| n.parent_id.as_deref() == parent) {
+		let filter = NotebookFilter::Notebook(nb.id.clone());
+		let count = notes.iter().filter(|n| note_in_filter(n, &filter, notebooks)).count();
+		let has_children = notebooks.iter().any(|n| n.parent_id.as_deref() == Some(nb.id.as_str()));
+		let is_selected = *selected == filter;
+
+		let mut header = |ui: &mut egui::Ui| {
+			let resp = ui.selectable_label(is_selected, format!("📁 {} ({})", nb.name, count));
+			if resp.clicked() {
+				*action = NotebookAction::Select(filter.clone());
+			}
+			resp.context_menu(|ui| {
+				if ui.button("➕ 新建子笔记本").clicked() {
+					*edit_state = Some(NotebookEdit::Create { parent_id: Some(nb.id.clone()), name: String::new() });
+					ui.close_kind(egui::UiKind::Menu);
+				}
+				if ui.button("✏ 重命名").clicked() {
+					*edit_state = Some(NotebookEdit::Rename { id: nb.id.clone(), name: nb.name.clone() });
+					ui.close_kind(egui::UiKind::Menu);
+				}
+				if ui.button("🗑 删除（笔记移到上一级）").clicked() {
+					*action = NotebookAction::Delete(nb.id.clone());
+					ui.close_kind(egui::UiKind::Menu);
+				}
+			});
+		};
+
+		if has_children {
+			let id = ui.make_persistent_id(("notebook_tree", &nb.id));
+			egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+				.show_header(ui, |ui| header(ui))
+				.body(|ui| {
+					draw_notebook_level(ui, notebooks, notes, Some(&nb.id), selected, edit_state, action);
+				});
+		} else {
+			ui.horizontal(|ui| {
+				// 与可折叠节点的展开按钮对齐
+				ui.add_space(ui.spacing().indent);
+				header(ui);
+			});
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::notes;
+
+	fn notebook(id: &str, name: &str, parent_id: Option<&str>) -> Notebook {
+		Notebook { id: id.to_string(), name: name.to_string(), parent_id: parent_id.map(str::to_string) }
+	}
+
+	/// 讲道 / 2024 / 复活节，另有一个顶层的「查经」
+	fn tree() -> Vec<Notebook> {
+		vec![
+			notebook("easter", "复活节", Some("y2024")),
+			notebook("study", "查经", None),
+			notebook("y2024", "2024", Some("sermons")),
+			notebook("sermons", "讲道", None),
+		]
+	}
+
+	#[test]
+	fn builds_paths_and_tree_order() {
+		let notebooks = tree();
+		assert_eq!(notebook_path(&notebooks, "easter"), "讲道 / 2024 / 复活节");
+		let order: Vec<(usize, &str)> = notebooks_in_tree_order(&notebooks).into_iter()
+			.map(|(depth, nb)| (depth, nb.name.as_str()))
+			.collect();
+		assert_eq!(order, vec![(0, "查经"), (0, "讲道"), (1, "2024"), (2, "复活节")]);
+	}
+
+	#[test]
+	fn filter_includes_child_notebooks() {
+		let notebooks = tree();
+		let note = |notebook_id: Option<&str>| Notedb { notebook_id: notebook_id.map(str::to_string), ..Default::default() };
+		let sermons = NotebookFilter::Notebook("sermons".to_string());
+		assert!(note_in_filter(&note(Some("easter")), &sermons, &notebooks));
+		assert!(!note_in_filter(&note(Some("study")), &sermons, &notebooks));
+		assert!(!note_in_filter(&note(None), &sermons, &notebooks));
+		assert!(note_in_filter(&note(None), &NotebookFilter::Unfiled, &notebooks));
+	}
+
+	#[test]
+	fn survives_parent_cycles() {
+		let notebooks = vec![notebook("a", "甲", Some("b")), notebook("b", "乙", Some("a"))];
+		assert_eq!(notebook_with_descendants(&notebooks, "a"), vec!["a", "b"]);
+		// 路径只要能走完即可
+		assert!(notebook_path(&notebooks, "a").ends_with("甲"));
+	}
+
+	#[test]
+	fn ensure_path_reuses_existing_notebooks() {
+		let db = notes::test_db();
+		db.open("notes");
+		let id = ensure_notebook_path("notes", "讲道 / 2024").unwrap().unwrap();
+		assert_eq!(ensure_notebook_path("notes", " 讲道 /  2024 ").unwrap(), Some(id.clone()));
+		let notebooks = load_notebooks("notes");
+		assert_eq!(notebooks.len(), 2);
+		assert_eq!(notebook_path(&notebooks, &id), "讲道 / 2024");
+	}
+
+	#[test]
+	fn deleting_moves_contents_up_one_level() {
+		let db = notes::test_db();
+		let conn = db.open("notes");
+		let parent = create_notebook("notes", None, "讲道").unwrap().unwrap();
+		let child = create_notebook("notes", Some(&parent), "2024").unwrap().unwrap();
+		let grandchild = create_notebook("notes", Some(&child), "复活节").unwrap().unwrap();
+		conn.execute("INSERT INTO notes (id, verse_start, notebook_id) VALUES ('n', -1, ?1)", [&child]).unwrap();
+
+		delete_notebook("notes", &child).unwrap();
+		let notebook_id: Option<String> = conn.query_row("SELECT notebook_id FROM notes WHERE id = 'n'", [], |row| row.get(0)).unwrap();
+		assert_eq!(notebook_id, Some(parent.clone()));
+		let notebooks = load_notebooks("notes");
+		assert_eq!(notebook_path(&notebooks, &grandchild), "讲道 / 复活节");
+
+		// 顶层笔记本删除后，其中的笔记变为未归档
+		delete_notebook("notes", &parent).unwrap();
+		let notebook_id: Option<String> = conn.query_row("SELECT notebook_id FROM notes WHERE id = 'n'", [], |row| row.get(0)).unwrap();
+		assert_eq!(notebook_id, None);
+	}
+}
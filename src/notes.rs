@@ -1,8 +1,18 @@
-use chrono::Utc;
 use serde::{Serialize, Deserialize};
+use std::path::PathBuf;
 use crate::theme::ThemeColors;
 use crate::BibleApp;
-use crate::utils::version_display_name;
+use crate::utils::{version_display_name, highlight_search_terms, now_timestamp, display_timestamp, timestamp_sort_key};
+use crate::tags::{self, TagAction, TAG_JOINER};
+use crate::notebooks::{self, Notebook, NotebookAction, NotebookFilter};
+use crate::revisions;
+use crate::backup;
+use crate::crypto;
+use crate::markdown::render_markdown;
+use crate::note_list::{NoteListView, draw_list_toolbar, group_title};
+use crate::export::ExportDialog;
+use crate::import::ImportDialog;
+use crate::reference::{ScriptureRef, parse_reference};
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Notedb {
@@ -20,6 +30,12 @@ pub struct Notedb {
     pub version: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    #[serde(default)]
+    pub notebook_id: Option<String>,
+    #[serde(default)]
+    pub deleted_at: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -29,6 +45,21 @@ enum SearchMode {
     Content,
     Keyword,
 		Subject,
+		Reference,
+}
+
+impl SearchMode {
+	/// 该搜索模式对应的笔记字段（同时也是全文索引的列名）
+	fn columns(&self) -> &'static [&'static str] {
+		match self {
+			SearchMode::Default => &["title", "subject", "keywords", "reference", "body"],
+			SearchMode::Title => &["title"],
+			SearchMode::Content => &["body"],
+			SearchMode::Keyword => &["keywords"],
+			SearchMode::Subject => &["subject"],
+			SearchMode::Reference => &["reference"],
+		}
+	}
 }
 
 #[derive(Debug)]
@@ -38,10 +69,256 @@ struct SearchTerm {
 }
 
 #[derive(Debug)]
-struct SearchQuery {
+pub struct SearchQuery {
     terms: Vec<SearchTerm>,
 }
 
+impl SearchQuery {
+	/// 需要在结果中高亮的搜索词
+	pub fn highlight_terms(&self) -> Vec<String> {
+		self.terms.iter().map(|t| t.text.clone()).collect()
+	}
+}
+
+/// 查询笔记时使用的字段列表，顺序与 note_from_row 对应
+pub const NOTE_COLUMNS: &str = "id, book_num, book_name, chapter, verse_start, char_offset,
+	title, keywords, reference, body, subject, version, created_at, updated_at, notebook_id,
+	deleted_at";
+
+/// 笔记列表显示的笔记：按笔记本、标签和列表的筛选条件过滤后排序
+pub fn listed_notes<'a>(
+	notes: &'a [Notedb],
+	notebook_filter: &NotebookFilter,
+	tag_filter: Option<&str>,
+	all_notebooks: &[Notebook],
+	view: &NoteListView,
+) -> Vec<&'a Notedb> {
+	let mut visible: Vec<&Notedb> = notes.iter()
+		.filter(|note| notebooks::note_in_filter(note, notebook_filter, all_notebooks)
+			&& tag_filter.is_none_or(|tag| note.tags.iter().any(|t| t == tag))
+			&& view.matches(note))
+		.collect();
+	view.sort(&mut visible);
+	visible
+}
+
+/// 列表中笔记的时间：修改时间，没有则用创建时间
+pub fn note_time(note: &Notedb) -> Option<&str> {
+	note.updated_at.as_deref().filter(|t| !t.is_empty()).or(note.created_at.as_deref())
+}
+
+/// 按 note_time 从新到旧排序；与笔记列表的时间排序用同一规则（见 timestamp_sort_key），
+/// 不用 SQLite 的 datetime()，它把只有日期的旧值当作 UTC 零点
+pub fn sort_notes_by_time(notes: &mut [Notedb]) {
+	notes.sort_by_cached_key(|n| std::cmp::Reverse(timestamp_sort_key(note_time(n))));
+}
+
+/// 全文索引 bm25 排序权重：id, title, subject, keywords, reference, body
+const FTS_WEIGHTS: &str = "0.0, 10.0, 5.0, 5.0, 2.0, 1.0";
+
+/// trigram 分词器至少需要三个字符才能命中索引
+const FTS_MIN_TERM_CHARS: usize = 3;
+
+/// 写入全文索引的正文：密文写成空字符串
+fn fts_body(column: &str) -> String {
+	format!("CASE WHEN {col} LIKE '{}%' THEN '' ELSE {col} END", crypto::ENCRYPTED_PREFIX, col = column)
+}
+
+/// 笔记数据库路径（目录不存在时自动创建）
+pub fn notes_db_path() -> Option<PathBuf> {
+	#[cfg(test)]
+	if let Some(path) = TEST_DB_PATH.with(|p| p.borrow().clone()) {
+		return Some(path);
+	}
+	let notes_dir = dirs::data_dir()?.join("bible_reader/notes");
+	if let Err(e) = std::fs::create_dir_all(&notes_dir) {
+		eprintln!("无法创建 notes 目录 {:?}: {:?}", notes_dir, e);
+		return None;
+	}
+	Some(notes_dir.join("note.db"))
+}
+
+#[cfg(test)]
+thread_local! {
+	static TEST_DB_PATH: std::cell::RefCell<Option<PathBuf>> = const { std::cell::RefCell::new(None) };
+}
+
+/// 测试用：让本线程的 notes_db_path 指向给定文件
+#[cfg(test)]
+pub fn set_test_db_path(path: Option<PathBuf>) {
+	TEST_DB_PATH.with(|p| *p.borrow_mut() = path);
+}
+
+/// 测试用：本线程改用临时目录中的笔记数据库，守卫释放时删除
+#[cfg(test)]
+pub fn test_db() -> TestDb {
+	let dir = std::env::temp_dir().join(format!("notes-test-{}", uuid::Uuid::new_v4()));
+	std::fs::create_dir_all(&dir).unwrap();
+	set_test_db_path(Some(dir.join("note.db")));
+	TestDb { dir }
+}
+
+#[cfg(test)]
+pub struct TestDb {
+	dir: PathBuf,
+}
+
+#[cfg(test)]
+impl TestDb {
+	/// 打开测试数据库并建好 category 的表
+	pub fn open(&self, category: &str) -> rusqlite::Connection {
+		let conn = rusqlite::Connection::open(self.dir.join("note.db")).unwrap();
+		ensure_notes_schema(&conn, category).unwrap();
+		conn
+	}
+}
+
+#[cfg(test)]
+impl Drop for TestDb {
+	fn drop(&mut self) {
+		set_test_db_path(None);
+		let _ = std::fs::remove_dir_all(&self.dir);
+	}
+}
+
+/// 按 NOTE_COLUMNS 的顺序读取一行笔记
+pub fn note_from_row(row: &rusqlite::Row) -> rusqlite::Result<Notedb> {
+	Ok(Notedb {
+		id: row.get(0)?,
+		book_num: row.get(1)?,
+		book_name: row.get(2)?,
+		chapter: row.get(3)?,
+		verse_start: row.get(4)?,
+		char_offset: row.get(5)?,
+		title: row.get(6)?,
+		keywords: row.get(7)?,
+		reference: row.get(8)?,
+		body: row.get::<_, Option<String>>(9)?.map(|b| crypto::decrypt_field(&b)),
+		subject: row.get(10)?,
+		version: row.get(11)?,
+		created_at: row.get(12)?,
+		updated_at: row.get(13)?,
+		notebook_id: row.get(14)?,
+		deleted_at: row.get(15)?,
+		tags: Vec::new(),
+	})
+}
+
+/// 按 id 读取一条笔记（包括回收站中的），附带标签
+pub fn load_note(conn: &rusqlite::Connection, category: &str, note_id: &str) -> rusqlite::Result<Option<Notedb>> {
+	let sql = format!("SELECT {} FROM {} WHERE id = ?1", NOTE_COLUMNS, category);
+	let note = rusqlite::OptionalExtension::optional(conn.query_row(&sql, [note_id], note_from_row))?;
+	Ok(note.map(|n| {
+		let mut notes = vec![n];
+		tags::attach_tags(conn, category, &mut notes);
+		notes.remove(0)
+	}))
+}
+
+/// 建立笔记表及其全文索引
+///
+/// 全文索引使用 trigram 分词（中文无需分词即可检索），由触发器与笔记表保持同步。
+/// 索引表首次创建时会把已有笔记全部写入。
+pub fn ensure_notes_schema(conn: &rusqlite::Connection, category: &str) -> rusqlite::Result<()> {
+	conn.execute_batch(&format!(
+		"CREATE TABLE IF NOT EXISTS {} (
+			id TEXT PRIMARY KEY,
+			book_num INTEGER,
+			book_name TEXT,
+			chapter TEXT,
+			verse_start INTEGER,
+			char_offset INTEGER,
+			title TEXT,
+			keywords TEXT,
+			reference TEXT,
+			body TEXT,
+			subject TEXT,
+			version TEXT,
+			created_at TEXT,
+			updated_at TEXT,
+			notebook_id TEXT,
+			deleted_at TEXT
+		);",
+		category
+	))?;
+
+	// 旧数据库没有笔记本字段
+	if !column_exists(conn, category, "notebook_id") {
+		conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN notebook_id TEXT;", category))?;
+	}
+	// 旧数据库没有回收站字段
+	if !column_exists(conn, category, "deleted_at") {
+		conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN deleted_at TEXT;", category))?;
+	}
+	// 旧版本把缺少的创建时间写成空字符串，改为 NULL 以便按时间排序；只有日期的旧值保持不变
+	let schema_version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+	if schema_version < 1 {
+		conn.execute_batch(&format!(
+			"UPDATE {cat} SET created_at = NULL WHERE created_at = '';
+			UPDATE {cat} SET updated_at = NULL WHERE updated_at = '';
+			PRAGMA user_version = 1;",
+			cat = category
+		))?;
+	}
+	// 旧触发器会把加密后的正文写进全文索引，换成跳过密文的触发器
+	if schema_version < 2 {
+		conn.execute_batch(&format!(
+			"DROP TRIGGER IF EXISTS {cat}_fts_ai;
+			DROP TRIGGER IF EXISTS {cat}_fts_au;
+			PRAGMA user_version = 2;",
+			cat = category
+		))?;
+	}
+	notebooks::ensure_notebook_schema(conn, category)?;
+	revisions::ensure_revision_schema(conn, category)?;
+	crate::drafts::ensure_draft_schema(conn, category)?;
+
+	let fts_table = format!("{}_fts", category);
+	let fts_is_new = !table_exists(conn, &fts_table);
+
+	// INSERT OR REPLACE 删除旧行时不会触发 DELETE 触发器，所以插入前先清掉同 id 的索引；
+	// 加密的正文以空字符串写入索引，解锁后在内存中搜索
+	conn.execute_batch(&format!(
+		"CREATE VIRTUAL TABLE IF NOT EXISTS {fts} USING fts5(
+			id UNINDEXED, title, subject, keywords, reference, body,
+			tokenize = 'trigram'
+		);
+		CREATE TRIGGER IF NOT EXISTS {cat}_fts_ai AFTER INSERT ON {cat} BEGIN
+			DELETE FROM {fts} WHERE id = new.id;
+			INSERT INTO {fts} (id, title, subject, keywords, reference, body)
+			VALUES (new.id, new.title, new.subject, new.keywords, new.reference, {new_body});
+		END;
+		CREATE TRIGGER IF NOT EXISTS {cat}_fts_au AFTER UPDATE ON {cat} BEGIN
+			DELETE FROM {fts} WHERE id = old.id;
+			INSERT INTO {fts} (id, title, subject, keywords, reference, body)
+			VALUES (new.id, new.title, new.subject, new.keywords, new.reference, {new_body});
+		END;
+		CREATE TRIGGER IF NOT EXISTS {cat}_fts_ad AFTER DELETE ON {cat} BEGIN
+			DELETE FROM {fts} WHERE id = old.id;
+		END;",
+		fts = fts_table,
+		cat = category,
+		new_body = fts_body("new.body"),
+	))?;
+
+	if fts_is_new {
+		conn.execute_batch(&format!(
+			"INSERT INTO {fts} (id, title, subject, keywords, reference, body)
+			SELECT id, title, subject, keywords, reference, {body} FROM {cat};",
+			fts = fts_table,
+			cat = category,
+			body = fts_body("body"),
+		))?;
+	} else if schema_version < 2 {
+		conn.execute(
+			&format!("UPDATE {} SET body = '' WHERE body LIKE ?1", fts_table),
+			[format!("{}%", crypto::ENCRYPTED_PREFIX)],
+		)?;
+	}
+
+	tags::ensure_tag_schema(conn, category)
+}
+
 //追加笔记样式
 impl BibleApp {
 pub fn show_appended_notes(
@@ -99,6 +376,7 @@ impl BibleApp {
 			return;
 		}
 		let note = self.current_note.clone().unwrap();
+		let mut go_to: Option<ScriptureRef> = None;
 
 		egui::Area::new("note_window_area".into())
 			.default_pos([300.0, 200.0])
@@ -112,16 +390,37 @@ impl BibleApp {
 							ui.heading(note.title.as_deref().unwrap_or("笔记"));
 							if let Some(reference) = note.reference.as_deref() {
 								if !reference.is_empty() {
-									ui.label(
+									let resp = ui.add(egui::Label::new(
 										egui::RichText::new(format!("引用：{}", reference))
 										.size(10.0)
 										.color(colors.comment_text_color),
-									);
+									).sense(egui::Sense::click()));
+									if let Some(r) = parse_reference(reference)
+										&& resp.on_hover_text(format!("跳转到 {}", r.display())).clicked()
+									{
+										go_to = Some(r);
+									}
 								}
 							}
+							if !note.tags.is_empty() {
+								ui.label(
+									egui::RichText::new(format!("🏷 {}", note.tags.join(TAG_JOINER)))
+									.size(10.0)
+									.color(colors.comment_text_color),
+								);
+							}
 						});
 						ui.separator();
-						ui.label(note.body.as_deref().unwrap_or("<无内容>"));
+						match note.body.as_deref() {
+							Some(body) if !body.trim().is_empty() => {
+								if let Some(r) = render_markdown(ui, body) {
+									go_to = Some(r);
+								}
+							}
+							_ => {
+								ui.label("<无内容>");
+							}
+						}
 					});
 
 					ui.add_space(20.0);
@@ -134,12 +433,15 @@ impl BibleApp {
 
 							// 删除按钮
 							if let Some(note_id) = self.current_note.as_ref().map(|n| n.id.clone()) {
-								if ui.add_sized([btn_w, btn_h], egui::Button::new("🗑删除")).clicked() {
+								if ui.add_sized([btn_w, btn_h], egui::Button::new("🗑删除"))
+									.on_hover_text("移到回收站，可在笔记列表的回收站中恢复")
+									.clicked() {
 									if let Err(e) = delete_note("notes", &note_id) {
 										eprintln!("删除笔记失败 id={}: {:?}", note_id, e);
 									} else {
 										self.current_note = None;
 										self.note_window_open = false;
+										self.last_appended_notes_chapter = None;
 									}
 								}
 							}
@@ -148,7 +450,7 @@ impl BibleApp {
 
 							if let Some(created) = &note.created_at {
 								ui.label(
-									egui::RichText::new(format!("创建: {}", created))
+									egui::RichText::new(format!("创建: {}", display_timestamp(created)))
 									.size(10.0)
 									.color(colors.comment_text_color)
 								);
@@ -158,17 +460,22 @@ impl BibleApp {
 
 							// 编辑按钮
 							if ui.add_sized([btn_w, btn_h], egui::Button::new("编辑")).clicked() {
-								self.open_noteapp_window(self.current_note.as_ref());
+								self.open_noteapp_window(self.current_note.clone());
 								self.current_note = None;
 								self.note_window_open = false;
 							}
 
+							// 历史版本
+							if ui.add_sized([btn_w, btn_h], egui::Button::new("🕘历史")).clicked() {
+								self.open_note_history(&note);
+							}
+
 							ui.add_space(15.0);
 
 							// 修改时间
 							if let Some(updated) = &note.updated_at {
 								ui.label(
-									egui::RichText::new(format!("修改: {}", updated))
+									egui::RichText::new(format!("修改: {}", display_timestamp(updated)))
 									.size(10.0)
 									.color(colors.comment_text_color)
 								);
@@ -187,32 +494,190 @@ impl BibleApp {
 					});
 				});
 			});
+
+		if let Some(r) = go_to {
+			self.go_to_reference(&r);
+		}
 	}
 }
 
+/// 回收站中等待确认的操作
+#[derive(Debug, Clone)]
+pub enum TrashConfirm {
+	Purge(String),
+	Empty,
+}
+
+/// 回收站列表中的操作
+enum TrashAction {
+	Restore(String),
+	Purge(String),
+	Empty,
+}
+
+/// 回收站列表（彻底删除前需要确认）
+fn draw_trash_list(
+	ui: &mut egui::Ui,
+	colors: &ThemeColors,
+	notes: &[Notedb],
+	confirm: &mut Option<TrashConfirm>,
+) -> Option<TrashAction> {
+	let mut action = None;
+
+	ui.horizontal(|ui| {
+		ui.label(egui::RichText::new(format!("回收站（{} 条）", notes.len())).strong());
+		ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+			if ui.add_enabled(!notes.is_empty(), egui::Button::new("清空回收站")).clicked() {
+				*confirm = Some(TrashConfirm::Empty);
+			}
+		});
+	});
+
+	if let Some(pending) = confirm.clone() {
+		let question = match &pending {
+			TrashConfirm::Empty => "确定要清空回收站吗？此操作无法撤销。".to_string(),
+			TrashConfirm::Purge(id) => {
+				let title = notes.iter()
+					.find(|n| &n.id == id)
+					.and_then(|n| n.title.clone())
+					.unwrap_or_else(|| "<无标题>".to_string());
+				format!("确定要彻底删除「{}」吗？此操作无法撤销。", title)
+			}
+		};
+		egui::Frame::group(ui.style()).show(ui, |ui| {
+			ui.label(egui::RichText::new(question).color(egui::Color32::from_rgb(200, 60, 40)));
+			ui.horizontal(|ui| {
+				if ui.button("确定").clicked() {
+					action = Some(match pending {
+						TrashConfirm::Empty => TrashAction::Empty,
+						TrashConfirm::Purge(id) => TrashAction::Purge(id),
+					});
+					*confirm = None;
+				}
+				if ui.button("取消").clicked() {
+					*confirm = None;
+				}
+			});
+		});
+	}
+
+	ui.separator();
+
+	if notes.is_empty() {
+		ui.label("回收站是空的");
+		return action;
+	}
+
+	for note in notes {
+		ui.horizontal(|ui| {
+			ui.label(format!("📝「{}」", note.title.as_deref().unwrap_or("<无标题>")));
+			ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+				if ui.button("彻底删除").clicked() {
+					*confirm = Some(TrashConfirm::Purge(note.id.clone()));
+				}
+				if ui.button("恢复").clicked() {
+					action = Some(TrashAction::Restore(note.id.clone()));
+				}
+			});
+		});
+		ui.add(
+			egui::Label::new(
+				egui::RichText::new(format!("删除于 {}", display_timestamp(note.deleted_at.as_deref().unwrap_or(""))))
+				.size(10.0)
+				.color(colors.comment_text_color)
+			)
+		);
+		ui.separator();
+	}
+
+	action
+}
+
+/// 笔记列表中的操作
+enum NoteListAction {
+	Open(String),
+	MoveToNotebook(String, Option<String>),
+	GoTo(ScriptureRef),
+}
+
 //笔记列表样式
 fn draw_notes_list(
 	ui: &mut egui::Ui,
 	colors: &ThemeColors,
-	notes: &Vec<Notedb>,
-	current_note: &mut Option<Notedb>,
-	note_window_open: &mut bool,
-) -> bool {
+	notes: &[&Notedb],
+	highlight_terms: &[String],
+	notebooks: &[Notebook],
+	view: &NoteListView,
+) -> Option<NoteListAction> {
 	if notes.is_empty() {
 		ui.label("暂无笔记");
-		return false;
+		return None;
 	}
 
-	let mut request_close = false;
+	let mut action = None;
+
+	if !view.grouped {
+		for note in notes {
+			draw_note_item(ui, colors, note, highlight_terms, notebooks, &mut action);
+		}
+		return action;
+	}
+
+	// ===== 按书卷、章分组，点击组标题跳转到该章 =====
+	for (book_num, chapter, group) in view.group(notes) {
+		let id = ui.make_persistent_id(("notes_group", book_num, chapter));
+		egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+			.show_header(ui, |ui| {
+				let text = egui::RichText::new(format!("{}（{}）", group_title(book_num, chapter), group.len())).strong();
+				match book_num {
+					Some(book_num) => {
+						if ui.link(text).on_hover_text("在阅读区打开").clicked() {
+							action = Some(NoteListAction::GoTo(ScriptureRef {
+								book_num,
+								chapter: chapter.unwrap_or(1),
+								verse_start: None,
+								verse_end: None,
+							}));
+						}
+					}
+					None => {
+						ui.label(text);
+					}
+				}
+			})
+			.body(|ui| {
+				for note in &group {
+					draw_note_item(ui, colors, note, highlight_terms, notebooks, &mut action);
+				}
+			});
+	}
+
+	action
+}
+
+fn draw_note_item(
+	ui: &mut egui::Ui,
+	colors: &ThemeColors,
+	note: &Notedb,
+	highlight_terms: &[String],
+	notebooks: &[Notebook],
+	action: &mut Option<NoteListAction>,
+) {
+	let body_font_id = ui.style().text_styles[&egui::TextStyle::Body].clone();
 
-    for note in notes {
         let title = note.title.as_deref().unwrap_or("<无标题>");
         let subject = note.subject.as_deref().unwrap_or("");
 				let body = note.body.as_deref().unwrap_or("");
 				let version = version_display_name(note.version.as_deref().unwrap_or(""));
 				let book_name = note.book_name.as_deref().unwrap_or("");
 				let chapter = note.chapter.as_deref().unwrap_or("");
-				let note_location = format!("（{}:{}:{}）", version, book_name, chapter);
+				let mut note_location = format!("（{}:{}:{}）", version, book_name, chapter);
+				if let Some(nb_id) = &note.notebook_id {
+					note_location.push_str(&format!(" 📁 {}", notebooks::notebook_path(notebooks, nb_id)));
+				}
+				if !note.tags.is_empty() {
+					note_location.push_str(&format!(" 🏷 {}", note.tags.join(TAG_JOINER)));
+				}
 
         let title_text = if subject.is_empty() {
             format!("📝「{}」", title)
@@ -227,11 +692,23 @@ fn draw_notes_list(
 				//	request_close = true;
 				//}
 
-				let title_response = ui.link(&title_text);
+				let title_response = if highlight_terms.is_empty() {
+					ui.link(&title_text)
+				} else {
+					let mut job = egui::text::LayoutJob::default();
+					highlight_search_terms(&title_text, highlight_terms, colors, &mut job, &body_font_id);
+					ui.link(job)
+				};
 
         // ===== 第二行：正文预览（单行） =====
+				// 搜索时显示第一处命中所在的行
+				let preview = body.lines()
+					.find(|l| highlight_terms.iter().any(|t| l.contains(t.as_str())))
+					.unwrap_or(body);
+				let mut preview_job = egui::text::LayoutJob::default();
+				highlight_search_terms(preview, highlight_terms, colors, &mut preview_job, &body_font_id);
         let _body_response = ui.add(
-            egui::Label::new(body)
+            egui::Label::new(preview_job)
                 .truncate()   // 只显示第一行
         );
 				ui.add(
@@ -244,16 +721,29 @@ fn draw_notes_list(
         // ===== 点击任意一行都打开 =====
         //if title_response || body_response.clicked() {
         if title_response.clicked() {
-            *current_note = Some(note.clone());
-            *note_window_open = true;
-            request_close = true;
+            *action = Some(NoteListAction::Open(note.id.clone()));
         }
 
+				// ===== 右键移动到笔记本 =====
+				title_response.context_menu(|ui| {
+					ui.menu_button("📁 移动到笔记本", |ui| {
+						if ui.add_enabled(note.notebook_id.is_some(), egui::Button::new("未归档")).clicked() {
+							*action = Some(NoteListAction::MoveToNotebook(note.id.clone(), None));
+							ui.close_kind(egui::UiKind::Menu);
+						}
+						for (depth, nb) in notebooks::notebooks_in_tree_order(notebooks) {
+							let is_current = note.notebook_id.as_deref() == Some(nb.id.as_str());
+							let text = format!("{}📁 {}", "　".repeat(depth), nb.name);
+							if ui.add_enabled(!is_current, egui::Button::new(text)).clicked() {
+								*action = Some(NoteListAction::MoveToNotebook(note.id.clone(), Some(nb.id.clone())));
+								ui.close_kind(egui::UiKind::Menu);
+							}
+						}
+					});
+				});
+
 				//ui.add_space(6.0);
         ui.separator();
-    }
-
-    request_close
 }
 
 //笔记列表窗口
@@ -267,24 +757,39 @@ impl BibleApp {
             return;
         }
 
-        let mut close_window = false;
-				let mut open_note = false;
 				let mut do_search = false; 
+				let mut list_action = None;
+				let mut tag_action = TagAction::None;
+				let mut notebook_action = NotebookAction::None;
+				let mut trash_action = None;
+				let mut toggle_trash = false;
 
         egui::Window::new(egui::RichText::new("📒 笔记列表").size(14.0))
             .open(&mut self.show_notes_list_window)
             .resizable(true)
             .default_width(500.0)
             .show(ctx, |ui| {
-							let response = ui.add(
-								egui::TextEdit::singleline(&mut self.notes_search_keyword)
-								.hint_text(
-									egui::RichText::new("搜索笔记")
-									.color(colors.comment_text_color)
-									.size(14.0),
+							let response = ui.horizontal(|ui| {
+								let trash_text = if self.notes_show_trash { "返回笔记" } else { "🗑 回收站" };
+								if ui.button(trash_text).clicked() {
+									toggle_trash = true;
+								}
+								if ui.button("📤 导出").clicked() && self.export_dialog.is_none() {
+									self.export_dialog = Some(ExportDialog::default());
+								}
+								if ui.button("📥 导入").clicked() && self.import_dialog.is_none() {
+									self.import_dialog = Some(ImportDialog::default());
+								}
+								ui.add(
+									egui::TextEdit::singleline(&mut self.notes_search_keyword)
+									.hint_text(
+										egui::RichText::new("搜索笔记")
+										.color(colors.comment_text_color)
+										.size(14.0),
+									)
+									.desired_width(f32::INFINITY),
 								)
-								.desired_width(f32::INFINITY),
-							);
+							}).inner;
 
 							if response.clicked() || response.gained_focus() || response.has_focus(){
 								self.active_search_type = "notes".to_string();
@@ -300,80 +805,221 @@ impl BibleApp {
 
 							ui.separator();
 
+							if self.notes_show_trash {
+								egui::ScrollArea::vertical()
+									.auto_shrink([false; 2])
+									.show(ui, |ui| {
+										trash_action = draw_trash_list(ui, colors, &self.trash_cache, &mut self.trash_confirm);
+									});
+								return;
+							}
+
+							egui::SidePanel::left("notes_tag_sidebar")
+								.resizable(true)
+								.default_width(130.0)
+								.show_inside(ui, |ui| {
+									notebook_action = notebooks::draw_notebook_sidebar(
+										ui,
+										colors,
+										&self.notebooks,
+										&self.notes_cache,
+										&self.notes_notebook_filter,
+										&mut self.notebook_edit,
+									);
+									ui.add_space(10.0);
+									tag_action = tags::draw_tag_sidebar(
+										ui,
+										colors,
+										&self.notes_tag_counts,
+										self.notes_tag_filter.as_deref(),
+										&mut self.tag_rename,
+									);
+								});
+
+							draw_list_toolbar(ui, colors, &mut self.notes_list_view, &self.notes_cache);
+							ui.separator();
+
+							let view = &self.notes_list_view;
+							let visible = listed_notes(
+								&self.notes_cache,
+								&self.notes_notebook_filter,
+								self.notes_tag_filter.as_deref(),
+								&self.notebooks,
+								view,
+							);
+
 							egui::ScrollArea::vertical()
 								.auto_shrink([false; 2])
 								.show(ui, |ui| {
-									if draw_notes_list(
+									list_action = draw_notes_list(
 										ui,
 										colors,
-										&self.notes_cache,
-										&mut self.current_note,
-										&mut open_note,
-									) {
-										close_window = true;
-									}
+										&visible,
+										&self.notes_highlight_terms,
+										&self.notebooks,
+										view,
+									);
 								});
             });
 
         // 在 closure 结束之后再关窗口
-        if close_window {
-            self.show_notes_list_window = false;
-        }
-
-				if open_note {
-					self.note_window_open = true; 
+				let mut moved = false;
+				match list_action {
+					Some(NoteListAction::Open(note_id)) => {
+						self.current_note = self.notes_cache.iter().find(|n| n.id == note_id).cloned();
+						self.note_window_open = self.current_note.is_some();
+						self.show_notes_list_window = false;
+					}
+					Some(NoteListAction::MoveToNotebook(note_id, notebook_id)) => {
+						if let Err(e) = notebooks::move_note_to_notebook("notes", &note_id, notebook_id.as_deref()) {
+							eprintln!("移动笔记失败 id={}: {:?}", note_id, e);
+						}
+						moved = true;
+					}
+					Some(NoteListAction::GoTo(r)) => self.go_to_reference(&r),
+					None => {}
 				}
 
 				if do_search {
-					let query = parse_search_input(&self.notes_search_keyword);
-					self.notes_cache = self.search_notes_from_db("notes", &query);
+					self.reload_notes_list();
+				}
+
+				match tag_action {
+					TagAction::None => {}
+					TagAction::Filter(tag) => self.notes_tag_filter = tag,
+					TagAction::Rename(old_name, new_name) => {
+						if let Err(e) = tags::rename_tag("notes", &old_name, &new_name) {
+							eprintln!("重命名标签失败: {:?}", e);
+						} else {
+							if self.notes_tag_filter.as_deref() == Some(old_name.as_str()) {
+								self.notes_tag_filter = Some(new_name);
+							}
+							self.reload_notes_list();
+						}
+					}
+				}
+
+				let notebook_result = match notebook_action {
+					NotebookAction::None => None,
+					NotebookAction::Select(filter) => {
+						self.notes_notebook_filter = filter;
+						None
+					}
+					NotebookAction::Create(parent_id, name) => Some(
+						notebooks::create_notebook("notes", parent_id.as_deref(), &name).map(|_| ())
+					),
+					NotebookAction::Rename(id, name) => Some(notebooks::rename_notebook("notes", &id, &name)),
+					NotebookAction::Delete(id) => {
+						if self.notes_notebook_filter == NotebookFilter::Notebook(id.clone()) {
+							self.notes_notebook_filter = NotebookFilter::All;
+						}
+						Some(notebooks::delete_notebook("notes", &id))
+					}
+				};
+				if let Some(Err(e)) = &notebook_result {
+					eprintln!("笔记本操作失败: {:?}", e);
+				}
+
+				let trash_result = match trash_action {
+					None => None,
+					Some(TrashAction::Restore(id)) => Some(restore_note("notes", &id)),
+					Some(TrashAction::Purge(id)) => Some(purge_note("notes", &id)),
+					Some(TrashAction::Empty) => Some(empty_trash("notes").map(|n| println!("已清空回收站（{} 条）", n))),
+				};
+				if let Some(Err(e)) = &trash_result {
+					eprintln!("回收站操作失败: {:?}", e);
 				}
+				if trash_result.is_some() {
+					// 恢复的笔记可能属于当前章节
+					self.last_appended_notes_chapter = None;
+				}
+
+				if toggle_trash {
+					self.notes_show_trash = !self.notes_show_trash;
+					self.trash_confirm = None;
+				}
+
+				if moved || notebook_result.is_some() || trash_result.is_some() || toggle_trash {
+					self.reload_notes_list();
+				}
+    }
+
+		/// 笔记列表中当前显示的笔记
+		pub fn listed_notes(&self) -> Vec<&Notedb> {
+			listed_notes(
+				&self.notes_cache,
+				&self.notes_notebook_filter,
+				self.notes_tag_filter.as_deref(),
+				&self.notebooks,
+				&self.notes_list_view,
+			)
+		}
+
+		/// 重新读取笔记列表及标签统计（保留当前搜索条件）
+		pub fn reload_notes_list(&mut self) {
+			if self.notes_search_keyword.trim().is_empty() {
+				self.notes_cache = self.load_notes("notes", "all");
+				self.notes_highlight_terms.clear();
+			} else {
+				let query = parse_search_input(&self.notes_search_keyword);
+				self.notes_highlight_terms = query.highlight_terms();
+				self.notes_cache = self.search_notes_from_db("notes", &query);
+			}
+			self.notes_tag_counts = tags::load_tag_counts("notes");
+			self.notebooks = notebooks::load_notebooks("notes");
+			self.trash_cache = if self.notes_show_trash {
+				self.load_notes("notes", "trash")
+			} else {
+				Vec::new()
+			};
     }
 }
 
 
 //保存笔记
-pub fn save_note(category: &str, note: &Notedb) {
-	let notes_dir = dirs::data_dir().unwrap().join("bible_reader/notes");
-	if let Err(e) = std::fs::create_dir_all(&notes_dir) {
-		eprintln!("无法创建 notes 目录 {:?}: {:?}", notes_dir, e);
-		return;
-	}
-	let db_path = notes_dir.join("note.db");
-	let conn = rusqlite::Connection::open(&db_path).unwrap();
+pub fn save_note(category: &str, note: &Notedb) -> Result<(), String> {
+	write_note(category, note, &now_timestamp())
+}
 
-	let create_sql = format!(
-		"CREATE TABLE IF NOT EXISTS {} (
-						id TEXT PRIMARY KEY,
-						book_num INTEGER,
-						book_name TEXT,
-						chapter TEXT,
-						verse_start INTEGER,
-						char_offset INTEGER,
-						title TEXT,
-						keywords TEXT,
-						reference TEXT,
-						body TEXT,
-						subject TEXT,
-						version TEXT,
-						created_at TEXT,
-						updated_at TEXT
-				);",
-				category
-		);
+/// 写入笔记并更新标签和历史版本；导入时保留原来的修改时间
+pub fn write_note(category: &str, note: &Notedb, updated_at: &str) -> Result<(), String> {
+	if crypto::is_locked() {
+		eprintln!("笔记已加密且未解锁，不能保存 id={}", note.id);
+		return Err("笔记已加密且未解锁，不能保存".to_string());
+	}
+	// 锁定期间读出的正文是占位文字，写入会覆盖真正的密文
+	if note.body.as_deref() == Some(crypto::LOCKED_PLACEHOLDER) {
+		eprintln!("笔记正文是未解锁时的占位文字，不能保存 id={}", note.id);
+		return Err("笔记正文是未解锁时的占位文字，不能保存".to_string());
+	}
+	let db_path = notes_db_path().ok_or("找不到笔记数据库")?;
+	let conn = rusqlite::Connection::open(&db_path).map_err(|e| {
+		eprintln!("打开笔记数据库失败: {:?}", e);
+		format!("无法打开笔记数据库：{}", e)
+	})?;
 
-		if let Err(e) = conn.execute_batch(&create_sql) {
+		if let Err(e) = ensure_notes_schema(&conn, category) {
 				eprintln!("创建表 {} 失败: {:?}", category, e);
-				return;
+				return Err(format!("无法创建笔记表：{}", e));
 		}
 
-		let now = Utc::now().format("%Y-%m-%d").to_string();
+		// 标签为准；旧调用方只填了 keywords 时从中拆分
+		let note_tags = if note.tags.is_empty() {
+			tags::split_keywords(note.keywords.as_deref().unwrap_or(""))
+		} else {
+			note.tags.clone()
+		};
+		let keywords = note_tags.join(TAG_JOINER);
+
+		// 加密失败时不保存，绝不退回写入明文
+		let body = crypto::encrypt_field(note.body.as_deref().unwrap_or(""))?;
 
 		let insert_sql = format!(
 				"INSERT OR REPLACE INTO {} (
 					id, book_num, book_name, chapter, verse_start, char_offset,
-						title, keywords, reference, body, subject, version, created_at, updated_at
-				) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+						title, keywords, reference, body, subject, version, created_at, updated_at,
+						notebook_id
+				) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
 				category
 		);
 
@@ -387,45 +1033,109 @@ pub fn save_note(category: &str, note: &Notedb) {
 						note.verse_start,
 						note.char_offset,
 						note.title.as_deref().unwrap_or(""),
-						note.keywords.as_deref().unwrap_or(""),
+						keywords,
 						note.reference.as_deref().unwrap_or(""),
-						note.body.as_deref().unwrap_or(""),
+						body,
 						note.subject.as_deref().unwrap_or(""),
 						note.version.as_deref().unwrap_or(""),
-						note.created_at.as_deref().unwrap_or(""),
-						now, // updated_at
+						note.created_at.as_deref().filter(|c| !c.is_empty()),
+						updated_at,
+						note.notebook_id,
 				],
 		);
 
 		match res {
 				Ok(_) => println!("已保存笔记 id={}", note.id),
-				Err(e) => eprintln!("保存笔记失败: {:?}", e),
+				Err(e) => {
+					eprintln!("保存笔记失败: {:?}", e);
+					return Err(format!("保存笔记失败：{}", e));
+				}
+		}
+
+		if let Err(e) = tags::set_note_tags(&conn, category, &note.id, &note_tags) {
+				eprintln!("保存笔记标签失败: {:?}", e);
 		}
+
+		let mut saved = note.clone();
+		saved.keywords = Some(keywords);
+		let saved_at = now_timestamp();
+		if let Err(e) = revisions::record_revision(&conn, category, &saved, &saved_at) {
+				eprintln!("记录笔记历史版本失败: {:?}", e);
+		}
+		backup::note_changed();
+		Ok(())
 }
 
-//删除笔记
+//删除笔记（移到回收站）
 pub fn delete_note(category: &str, note_id: &str) -> Result<(), rusqlite::Error> {
-		let notes_dir = dirs::data_dir().unwrap().join("bible_reader/notes");
-		let db_path = notes_dir.join("note.db");
+		let Some(db_path) = notes_db_path() else { return Ok(()) };
+		let conn = rusqlite::Connection::open(&db_path)?;
+		ensure_notes_schema(&conn, category)?;
+
+		let sql = format!("UPDATE {} SET deleted_at = ?1 WHERE id = ?2", category);
+		let now = now_timestamp();
+		conn.execute(&sql, [now.as_str(), note_id])?;
+
+		println!("已将笔记移到回收站 id={}", note_id);
+		backup::note_changed();
+		Ok(())
+}
+
+//从回收站恢复
+pub fn restore_note(category: &str, note_id: &str) -> Result<(), rusqlite::Error> {
+		let Some(db_path) = notes_db_path() else { return Ok(()) };
+		let conn = rusqlite::Connection::open(&db_path)?;
+
+		let sql = format!("UPDATE {} SET deleted_at = NULL WHERE id = ?1", category);
+		conn.execute(&sql, [note_id])?;
+
+		println!("已恢复笔记 id={}", note_id);
+		Ok(())
+}
+
+//彻底删除（连同标签与历史版本）
+pub fn purge_note(category: &str, note_id: &str) -> Result<(), rusqlite::Error> {
+		let Some(db_path) = notes_db_path() else { return Ok(()) };
 		let conn = rusqlite::Connection::open(&db_path)?;
 
 		let sql = format!("DELETE FROM {} WHERE id = ?1", category);
 		conn.execute(&sql, [note_id])?;
+		tags::remove_note_tags(&conn, category, note_id)?;
+		revisions::remove_revisions(&conn, category, note_id)?;
 
-		println!("已删除笔记 id={}", note_id);
+		println!("已彻底删除笔记 id={}", note_id);
 		Ok(())
 }
 
+//清空回收站
+pub fn empty_trash(category: &str) -> Result<usize, rusqlite::Error> {
+		let Some(db_path) = notes_db_path() else { return Ok(0) };
+		let conn = rusqlite::Connection::open(&db_path)?;
+		if !table_exists(&conn, category) {
+			return Ok(0);
+		}
+
+		let ids: Vec<String> = {
+			let sql = format!("SELECT id FROM {} WHERE deleted_at IS NOT NULL", category);
+			let mut stmt = conn.prepare(&sql)?;
+			let rows = stmt.query_map([], |row| row.get(0))?;
+			rows.flatten().collect()
+		};
+		for id in &ids {
+			purge_note(category, id)?;
+		}
+		Ok(ids.len())
+}
+
 //读取笔记
 impl BibleApp {
     pub fn load_notes(&self, category: &str, mode: &str) -> Vec<Notedb> {
         let mut notes = Vec::new();
 
-        let notes_dir = match dirs::data_dir() {
-            Some(d) => d.join("bible_reader/notes"),
+        let db_path = match notes_db_path() {
+            Some(p) => p,
             None => return notes,
         };
-        let db_path = notes_dir.join("note.db");
 
         let conn = match rusqlite::Connection::open(&db_path) {
             Ok(c) => c,
@@ -438,6 +1148,9 @@ impl BibleApp {
         if !table_exists(&conn, category) {
             return notes;
         }
+				if let Err(e) = ensure_notes_schema(&conn, category) {
+					eprintln!("更新笔记表结构失败: {:?}", e);
+				}
 
         match mode {
             // ===============================
@@ -457,6 +1170,7 @@ impl BibleApp {
                     "book_num = ?1",
                     "chapter = ?2",
                     "version = ?3",
+                    "deleted_at IS NULL",
                 ];
 
                 if category != self.current_version {
@@ -466,24 +1180,10 @@ impl BibleApp {
                 let where_clause = conditions.join(" AND ");
 
                 let sql = format!(
-                    "SELECT
-                        id,
-                        book_num,
-                        book_name,
-                        chapter,
-                        verse_start,
-                        char_offset,
-                        title,
-                        keywords,
-                        reference,
-                        body,
-                        subject,
-                        version,
-                        created_at,
-                        updated_at
+                    "SELECT {}
                      FROM {}
-                     WHERE {}
-										 ORDER BY COALESCE(updated_at, created_at) DESC;",
+                     WHERE {};",
+                    NOTE_COLUMNS,
                     category,
                     where_clause
                 );
@@ -502,24 +1202,7 @@ impl BibleApp {
                         chapter,
                         self.current_version
                     ],
-                    |row| {
-                        Ok(Notedb {
-                            id: row.get(0)?,
-                            book_num: row.get(1)?,
-                            book_name: row.get(2)?,
-                            chapter: row.get(3)?,
-                            verse_start: row.get(4)?,
-                            char_offset: row.get(5)?,
-                            title: row.get(6)?,
-                            keywords: row.get(7)?,
-                            reference: row.get(8)?,
-                            body: row.get(9)?,
-                            subject: row.get(10)?,
-                            version: row.get(11)?,
-                            created_at: row.get(12)?,
-                            updated_at: row.get(13)?,
-                        })
-                    },
+                    note_from_row,
                 );
 
                 if let Ok(iter) = rows {
@@ -527,6 +1210,7 @@ impl BibleApp {
                         notes.push(note);
                     }
                 }
+                sort_notes_by_time(&mut notes);
             }
 
             // ===============================
@@ -534,23 +1218,10 @@ impl BibleApp {
             // ===============================
             "all" => {
                 let sql = format!(
-                    "SELECT
-                        id,
-                        book_num,
-                        book_name,
-                        chapter,
-                        verse_start,
-                        char_offset,
-                        title,
-                        keywords,
-                        reference,
-                        body,
-                        subject,
-                        version,
-                        created_at,
-                        updated_at
+                    "SELECT {}
                      FROM {}
-										 ORDER BY COALESCE(updated_at, created_at) DESC;",
+                     WHERE deleted_at IS NULL;",
+                    NOTE_COLUMNS,
                     category
                 );
 
@@ -562,30 +1233,40 @@ impl BibleApp {
                     }
                 };
 
-                let rows = stmt.query_map([], |row| {
-                    Ok(Notedb {
-                        id: row.get(0)?,
-                        book_num: row.get(1)?,
-                        book_name: row.get(2)?,
-                        chapter: row.get(3)?,
-                        verse_start: row.get(4)?,
-                        char_offset: row.get(5)?,
-                        title: row.get(6)?,
-                        keywords: row.get(7)?,
-                        reference: row.get(8)?,
-                        body: row.get(9)?,
-                        subject: row.get(10)?,
-                        version: row.get(11)?,
-                        created_at: row.get(12)?,
-                        updated_at: row.get(13)?,
-                    })
-                });
+                let rows = stmt.query_map([], note_from_row);
 
                 if let Ok(iter) = rows {
                     for note in iter.flatten() {
                         notes.push(note);
                     }
                 }
+                sort_notes_by_time(&mut notes);
+            }
+
+            // ===============================
+            // 回收站中的笔记
+            // ===============================
+            "trash" => {
+                let sql = format!(
+                    "SELECT {}
+                     FROM {}
+                     WHERE deleted_at IS NOT NULL
+                     ORDER BY deleted_at DESC;",
+                    NOTE_COLUMNS,
+                    category
+                );
+
+                let mut stmt = match conn.prepare(&sql) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        eprintln!("SQL 解析失败: {:?}", e);
+                        return notes;
+                    }
+                };
+
+                if let Ok(iter) = stmt.query_map([], note_from_row) {
+                    notes.extend(iter.flatten());
+                }
             }
 
             // ===============================
@@ -596,10 +1277,17 @@ impl BibleApp {
             }
         }
 
+				tags::attach_tags(&conn, category, &mut notes);
         notes
     }
 }
-fn table_exists(conn: &rusqlite::Connection, table: &str) -> bool {
+pub fn column_exists(conn: &rusqlite::Connection, table: &str, column: &str) -> bool {
+	let sql = format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table);
+	conn.query_row(&sql, [column], |_| Ok(()))
+		.is_ok()
+}
+
+pub fn table_exists(conn: &rusqlite::Connection, table: &str) -> bool {
     let sql = r#"
         SELECT 1
         FROM sqlite_master
@@ -630,8 +1318,9 @@ fn parse_search_input(input: &str) -> SearchQuery {
             let mode = match prefix.trim().to_lowercase().as_str() {
                 "title" | "标题" => SearchMode::Title,
                 "content" | "内容" | "" => SearchMode::Content,
-                "keyword" | "keywords" | "关键词" => SearchMode::Keyword,
+                "keyword" | "keywords" | "关键词" | "tag" | "标签" => SearchMode::Keyword,
 								"subject" | "主题" => SearchMode::Subject,
+								"reference" | "引用" | "经文" => SearchMode::Reference,
                 _ => SearchMode::Default,
             };
 
@@ -648,24 +1337,86 @@ fn parse_search_input(input: &str) -> SearchQuery {
     SearchQuery { terms }
 }
 
+/// 全文索引查询中的短语，双引号需转义
+fn fts_phrase(text: &str) -> String {
+	format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// 笔记中与全文索引列同名的字段
+fn note_field<'a>(note: &'a Notedb, name: &str) -> &'a str {
+	match name {
+		"title" => note.title.as_deref(),
+		"subject" => note.subject.as_deref(),
+		"keywords" => note.keywords.as_deref(),
+		"reference" => note.reference.as_deref(),
+		_ => note.body.as_deref(),
+	}.unwrap_or("")
+}
+
+/// 简单相关度：各字段命中次数按权重累加（用于无法走全文索引的短词搜索）
+fn note_relevance(note: &Notedb, terms: &[SearchTerm]) -> usize {
+	let weight = |name: &str| match name {
+		"title" => 10,
+		"subject" | "keywords" => 5,
+		"reference" => 2,
+		_ => 1,
+	};
+
+	terms.iter()
+		.flat_map(|t| t.mode.columns().iter().map(move |c| (c, t.text.trim())))
+		.filter(|(_, text)| !text.is_empty())
+		.map(|(c, text)| note_field(note, c).matches(text).count() * weight(c))
+		.sum()
+}
+
+/// 每个搜索词都在其对应的某个字段中出现（不区分大小写）
+fn note_matches(note: &Notedb, terms: &[SearchTerm]) -> bool {
+	terms.iter()
+		.map(|t| t.text.trim().to_lowercase())
+		.zip(terms)
+		.filter(|(text, _)| !text.is_empty())
+		.all(|(text, t)| t.mode.columns().iter().any(|c| note_field(note, c).to_lowercase().contains(&text)))
+}
+
+/// 加密后正文不在全文索引中：解密全部笔记后在内存中搜索，按命中次数排序
+fn search_notes_in_memory(conn: &rusqlite::Connection, category: &str, query: &SearchQuery) -> Vec<Notedb> {
+	let sql = format!(
+		"SELECT {} FROM {} WHERE deleted_at IS NULL",
+		NOTE_COLUMNS, category
+	);
+	let mut notes: Vec<Notedb> = match conn.prepare(&sql) {
+		Ok(mut stmt) => match stmt.query_map([], note_from_row) {
+			Ok(rows) => rows.flatten().filter(|n| note_matches(n, &query.terms)).collect(),
+			Err(_) => Vec::new(),
+		},
+		Err(e) => {
+			eprintln!("SQL 解析失败: {:?}", e);
+			return Vec::new();
+		}
+	};
+	tags::attach_tags(conn, category, &mut notes);
+	sort_notes_by_time(&mut notes);
+	notes.sort_by_key(|n| std::cmp::Reverse(note_relevance(n, &query.terms)));
+	notes
+}
+
 impl BibleApp {
- fn search_notes_from_db(
+ pub fn search_notes_from_db(
     &self,
     category: &str,
     query: &SearchQuery,
 ) -> Vec<Notedb> {
-    let mut notes = Vec::new();
+    let notes = Vec::new();
 
     if query.terms.is_empty() {
 			let notes = self.load_notes("notes", "all");
         return notes;
     }
 
-    let notes_dir = match dirs::data_dir() {
-        Some(d) => d.join("bible_reader/notes"),
+    let db_path = match notes_db_path() {
+        Some(p) => p,
         None => return notes,
     };
-    let db_path = notes_dir.join("note.db");
 
     let conn = match rusqlite::Connection::open(&db_path) {
         Ok(c) => c,
@@ -675,7 +1426,25 @@ impl BibleApp {
     if !table_exists(&conn, category) {
         return notes;
     }
+		if let Err(e) = ensure_notes_schema(&conn, category) {
+			eprintln!("建立笔记全文索引失败: {:?}", e);
+			return notes;
+		}
+		search_notes(&conn, category, query)
+}
+}
 
+/// 在已建好全文索引的笔记表中搜索
+fn search_notes(conn: &rusqlite::Connection, category: &str, query: &SearchQuery) -> Vec<Notedb> {
+		let mut notes = Vec::new();
+		if crypto::is_encrypted() {
+			return search_notes_in_memory(conn, category, query);
+		}
+
+		let fts_table = format!("{}_fts", category);
+
+		// 三个字符以上的词走全文索引，更短的词退回 LIKE
+    let mut match_parts: Vec<String> = Vec::new();
     let mut clauses: Vec<String> = Vec::new();
     let mut params: Vec<String> = Vec::new();
 
@@ -685,85 +1454,169 @@ impl BibleApp {
             continue;
         }
 
-        let pat = format!("%{}%", text);
-
-        match term.mode {
-            SearchMode::Title => {
-                clauses.push("title LIKE ?".to_string());
-                params.push(pat);
-            }
-            SearchMode::Content => {
-                clauses.push("body LIKE ?".to_string());
-                params.push(pat);
-            }
-            SearchMode::Keyword => {
-                clauses.push("keywords LIKE ?".to_string());
-                params.push(pat);
-            }
-						SearchMode::Subject => {
-							clauses.push("subject LIKE ?".to_string());
-							params.push(pat);
-						}
-            SearchMode::Default => {
-                clauses.push("(title LIKE ? OR keywords LIKE ?)".to_string());
-                params.push(pat.clone());
-                params.push(pat);
-            }
-        }
+				let columns = term.mode.columns();
+				if text.chars().count() >= FTS_MIN_TERM_CHARS {
+					match_parts.push(format!("{{{}}} : {}", columns.join(" "), fts_phrase(text)));
+				} else {
+					let pat = format!("%{}%", text);
+					let likes: Vec<String> = columns.iter()
+						.map(|c| format!("n.{} LIKE ?", c))
+						.collect();
+					clauses.push(format!("({})", likes.join(" OR ")));
+					params.extend(std::iter::repeat_n(pat, columns.len()));
+				}
     }
 
-    if clauses.is_empty() {
+    if match_parts.is_empty() && clauses.is_empty() {
         return notes;
     }
 
-    let where_clause = clauses.join(" AND ");
-
-    let sql = format!(
-        "SELECT
-            id, book_num, book_name, chapter, verse_start, char_offset,
-            title, keywords, reference, body, subject, version,
-            created_at, updated_at
-         FROM {}
-         WHERE {}
-         ORDER BY COALESCE(updated_at, created_at) DESC;",
-        category,
-        where_clause
-    );
+		let columns = NOTE_COLUMNS.split(',')
+			.map(|c| format!("n.{}", c.trim()))
+			.collect::<Vec<_>>()
+			.join(", ");
+
+		clauses.push("n.deleted_at IS NULL".to_string());
+
+		let sql = if match_parts.is_empty() {
+			format!(
+				"SELECT {}, 0.0
+				 FROM {} n
+				 WHERE {};",
+				columns,
+				category,
+				clauses.join(" AND ")
+			)
+		} else {
+			params.insert(0, match_parts.join(" AND "));
+			clauses.insert(0, format!("{} MATCH ?", fts_table));
+			format!(
+				"SELECT {}, bm25({fts}, {})
+				 FROM {fts} JOIN {} n ON n.id = {fts}.id
+				 WHERE {};",
+				columns,
+				FTS_WEIGHTS,
+				category,
+				clauses.join(" AND "),
+				fts = fts_table
+			)
+		};
 
     let mut stmt = match conn.prepare(&sql) {
         Ok(s) => s,
-        Err(_) => return notes,
+        Err(e) => {
+					eprintln!("SQL 解析失败: {:?}", e);
+					return notes;
+				}
     };
 
+    // 每行附带 bm25 得分（越小越相关）；没有全文索引时为 0
     let rows = stmt.query_map(
         rusqlite::params_from_iter(params.iter()),
-        |row| {
-            Ok(Notedb {
-                id: row.get(0)?,
-                book_num: row.get(1)?,
-                book_name: row.get(2)?,
-                chapter: row.get(3)?,
-                verse_start: row.get(4)?,
-                char_offset: row.get(5)?,
-                title: row.get(6)?,
-                keywords: row.get(7)?,
-                reference: row.get(8)?,
-                body: row.get(9)?,
-                subject: row.get(10)?,
-                version: row.get(11)?,
-                created_at: row.get(12)?,
-                updated_at: row.get(13)?,
-            })
-        },
+        |row| Ok((note_from_row(row)?, row.get::<_, f64>(16)?)),
     );
 
-    if let Ok(iter) = rows {
-        for note in iter.flatten() {
-            notes.push(note);
-        }
-    }
+    let mut ranked: Vec<(Notedb, f64)> = match rows {
+        Ok(iter) => iter.flatten().collect(),
+        Err(_) => Vec::new(),
+    };
+		// 先按时间排，再按相关度稳定排序，同分保持时间顺序
+		ranked.sort_by_cached_key(|(n, _)| std::cmp::Reverse(timestamp_sort_key(note_time(n))));
+		ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+		notes = ranked.into_iter().map(|(n, _)| n).collect();
+		tags::attach_tags(conn, category, &mut notes);
+
+		// 没有全文索引排序时按字段命中次数排序
+		if match_parts.is_empty() {
+			notes.sort_by_key(|n| std::cmp::Reverse(note_relevance(n, &query.terms)));
+		}
 
     notes
 }
-}
 
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn insert_note(conn: &rusqlite::Connection, id: &str, title: &str, body: &str) {
+		conn.execute(
+			"INSERT INTO notes (id, verse_start, title, body, updated_at) VALUES (?1, -1, ?2, ?3, ?4)",
+			rusqlite::params![id, title, crypto::encrypt_field(body).unwrap(), now_timestamp()],
+		).unwrap();
+	}
+
+	fn search_ids(conn: &rusqlite::Connection, input: &str) -> Vec<String> {
+		search_notes(conn, "notes", &parse_search_input(input)).into_iter().map(|n| n.id).collect()
+	}
+
+	#[test]
+	fn date_only_values_sort_as_local_midnight() {
+		let _encryption = crypto::test_encryption(None);
+		let conn = rusqlite::Connection::open_in_memory().unwrap();
+		ensure_notes_schema(&conn, "notes").unwrap();
+		// 当地时间 5 月 1 日 00:30 晚于当地零点，无论本机在哪个时区
+		let just_after_midnight = chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+			.and_hms_opt(0, 30, 0).unwrap()
+			.and_local_timezone(chrono::Local).unwrap()
+			.to_rfc3339();
+		for (id, updated_at) in [("old", "2024-05-01"), ("new", just_after_midnight.as_str())] {
+			conn.execute(
+				"INSERT INTO notes (id, verse_start, title, body, updated_at) VALUES (?1, -1, '标题内容', '', ?2)",
+				[id, updated_at],
+			).unwrap();
+		}
+		assert_eq!(search_ids(&conn, "标题内容"), vec!["new", "old"]);
+	}
+
+	#[test]
+	fn searches_plain_bodies_through_the_index() {
+		let _encryption = crypto::test_encryption(None);
+		let conn = rusqlite::Connection::open_in_memory().unwrap();
+		ensure_notes_schema(&conn, "notes").unwrap();
+		insert_note(&conn, "a", "约翰福音", "神爱世人，甚至将他的独生子赐给他们");
+		insert_note(&conn, "b", "罗马书", "因为罪的工价乃是死");
+
+		assert_eq!(search_ids(&conn, "独生子"), vec!["a"]);
+		assert_eq!(search_ids(&conn, "工价"), vec!["b"]);
+		assert!(search_ids(&conn, "律法书").is_empty());
+	}
+
+	#[test]
+	fn searches_encrypted_bodies_after_unlock() {
+		let _encryption = crypto::test_encryption(Some("密码"));
+		let conn = rusqlite::Connection::open_in_memory().unwrap();
+		ensure_notes_schema(&conn, "notes").unwrap();
+		insert_note(&conn, "a", "约翰福音", "神爱世人，甚至将他的独生子赐给他们");
+		insert_note(&conn, "b", "罗马书", "因为罪的工价乃是死");
+
+		// 密文不进入全文索引
+		let stored: String = conn.query_row("SELECT body FROM notes WHERE id = 'a'", [], |r| r.get(0)).unwrap();
+		assert!(stored.starts_with(crypto::ENCRYPTED_PREFIX));
+		let indexed: String = conn.query_row("SELECT body FROM notes_fts WHERE id = 'a'", [], |r| r.get(0)).unwrap();
+		assert_eq!(indexed, "");
+
+		assert_eq!(search_ids(&conn, "独生子"), vec!["a"]);
+		assert_eq!(search_ids(&conn, "内容:工价"), vec!["b"]);
+		assert_eq!(search_ids(&conn, "标题:罗马"), vec!["b"]);
+		assert!(search_ids(&conn, "标题:独生子").is_empty());
+		assert!(search_ids(&conn, "律法书").is_empty());
+	}
+
+	#[test]
+	fn schema_upgrade_removes_ciphertext_from_the_index() {
+		let _encryption = crypto::test_encryption(Some("密码"));
+		let conn = rusqlite::Connection::open_in_memory().unwrap();
+		ensure_notes_schema(&conn, "notes").unwrap();
+		insert_note(&conn, "a", "约翰福音", "神爱世人");
+		// 模拟旧版本：索引中留有密文
+		conn.execute_batch(
+			"UPDATE notes_fts SET body = (SELECT body FROM notes WHERE id = 'a') WHERE id = 'a';
+			PRAGMA user_version = 1;",
+		).unwrap();
+
+		ensure_notes_schema(&conn, "notes").unwrap();
+		let indexed: String = conn.query_row("SELECT body FROM notes_fts WHERE id = 'a'", [], |r| r.get(0)).unwrap();
+		assert_eq!(indexed, "");
+	}
+}
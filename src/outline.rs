@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use rusqlite::{Connection, OpenFlags};
+use serde::Deserialize;
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::reference::{ScriptureRef, book_full_name, parse_reference};
+use crate::utils::{load_chapter_content, chapter_display_name, version_display_name};
+
+/// 一卷书的大纲：章 → 该章的小标题（起始节, 标题）
+pub type BookOutline = BTreeMap<i32, Vec<(i32, String)>>;
+
+#[derive(Deserialize)]
+struct OutlineEntry {
+	/// 小标题开始的经文，例如 "Gen 1:1" 或 "创 1:26"
+	r#ref: String,
+	title: String,
+}
+
+/// 本地大纲文件：~/.local/share/bible_reader/outline.json，
+/// 格式为 [{"ref": "创 1:1", "title": "神创造天地"}, ...]，与译本无关
+pub fn outline_file_path() -> Option<PathBuf> {
+	Some(dirs::data_dir()?.join("bible_reader/outline.json"))
+}
+
+/// 译本数据库中的小标题：可选的 headings 表（reference_osis 如 "Gen.1"、verse、title）
+fn load_db_outline(db_path: &Path, book_num: i32) -> rusqlite::Result<BookOutline> {
+	let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+	let has_headings: bool = conn.query_row(
+		"SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'headings'",
+		[],
+		|row| row.get(0),
+	)?;
+	let mut outline = BookOutline::new();
+	if !has_headings {
+		return Ok(outline);
+	}
+	let osis: String = conn.query_row("SELECT osis FROM books WHERE number = ?1", [book_num], |row| row.get(0))?;
+	let mut stmt = conn.prepare(
+		"SELECT reference_osis, verse, title FROM headings WHERE reference_osis LIKE ?1 || '.%' ORDER BY verse",
+	)?;
+	let rows = stmt.query_map([osis], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?, row.get::<_, String>(2)?)))?;
+	for (reference, verse, title) in rows.flatten() {
+		let Some(chapter) = reference.rsplit('.').next().and_then(|c| c.parse().ok()) else { continue };
+		outline.entry(chapter).or_default().push((verse, title));
+	}
+	Ok(outline)
+}
+
+fn load_file_outline(book_num: i32) -> BookOutline {
+	match outline_file_path().and_then(|p| fs::read_to_string(p).ok()) {
+		Some(text) => parse_file_outline(&text, book_num),
+		None => BookOutline::new(),
+	}
+}
+
+/// 从大纲文件内容中取出一卷书的小标题
+fn parse_file_outline(text: &str, book_num: i32) -> BookOutline {
+	let mut outline = BookOutline::new();
+	let entries: Vec<OutlineEntry> = match serde_json::from_str(text) {
+		Ok(entries) => entries,
+		Err(e) => {
+			eprintln!("大纲文件格式不正确: {:?}", e);
+			return outline;
+		}
+	};
+	for entry in entries {
+		match parse_reference(&entry.r#ref) {
+			Some(r) if r.book_num == book_num => {
+				outline.entry(r.chapter).or_default().push((r.verse_start.unwrap_or(1), entry.title));
+			}
+			Some(_) => {}
+			None => eprintln!("大纲文件中无法识别的经文：{}", entry.r#ref),
+		}
+	}
+	for headings in outline.values_mut() {
+		headings.sort_by_key(|(verse, _)| *verse);
+	}
+	outline
+}
+
+/// 一卷书的小标题：优先用译本自带的，没有时用本地大纲文件
+pub fn load_book_outline(db_path: &Path, book_num: i32) -> BookOutline {
+	match load_db_outline(db_path, book_num) {
+		Ok(outline) if !outline.is_empty() => outline,
+		Ok(_) => load_file_outline(book_num),
+		Err(e) => {
+			eprintln!("读取译本小标题失败: {:?}", e);
+			load_file_outline(book_num)
+		}
+	}
+}
+
+//书卷大纲与概览
+impl BibleApp {
+	/// 换书卷或译本后重新读取大纲
+	pub fn ensure_book_outline(&mut self) {
+		let Some(book) = self.current_book else { return };
+		let key = (self.current_version.clone(), book);
+		if self.book_outline_key.as_ref() == Some(&key) {
+			return;
+		}
+		let db_path = self.bible_root.join(&self.current_version);
+		self.book_outline = load_book_outline(&db_path, book);
+		// 第 0 章是书卷简介
+		self.book_intro = self.chapters.iter().any(|c| c == "0").then(|| load_chapter_content(&db_path, book, 0));
+		self.book_outline_key = Some(key);
+	}
+
+	/// 书卷概览：简介（第 0 章）与全书大纲
+	pub fn show_book_overview_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		if !self.show_book_overview {
+			return;
+		}
+		let Some(book) = self.current_book else { return };
+		self.ensure_book_outline();
+
+		let mut open = true;
+		let mut go_to: Option<ScriptureRef> = None;
+
+		egui::Window::new(egui::RichText::new(format!("📋 {} 概览", book_full_name(book))).size(14.0))
+			.open(&mut open)
+			.resizable(true)
+			.default_size([420.0, 520.0])
+			.show(ctx, |ui| {
+				egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+					ui.label(egui::RichText::new("简介").strong());
+					match &self.book_intro {
+						Some(text) => {
+							ui.label(egui::RichText::new(text).color(colors.text_color));
+						}
+						None => {
+							ui.label(egui::RichText::new(format!("{} 没有这卷书的简介", version_display_name(&self.current_version)))
+								.color(colors.comment_text_color));
+						}
+					}
+					ui.separator();
+
+					ui.label(egui::RichText::new("大纲").strong());
+					if self.book_outline.is_empty() {
+						ui.label(egui::RichText::new("没有小标题（可在译本数据库的 headings 表或 outline.json 中提供）")
+							.size(12.0).color(colors.comment_text_color));
+					}
+					for (chapter, headings) in &self.book_outline {
+						if ui.link(chapter_display_name(&chapter.to_string())).clicked() {
+							go_to = Some(ScriptureRef { book_num: book, chapter: *chapter, verse_start: None, verse_end: None });
+						}
+						for (verse, title) in headings {
+							ui.horizontal(|ui| {
+								ui.add_space(16.0);
+								ui.label(egui::RichText::new(format!("{}:{}", chapter, verse)).size(12.0).color(colors.comment_text_color));
+								ui.label(egui::RichText::new(title).color(colors.text_color));
+							});
+						}
+					}
+				});
+			});
+
+		if let Some(r) = go_to {
+			self.go_to_reference(&r);
+		}
+		self.show_book_overview = open;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reads_one_book_from_the_outline_file() {
+		let text = r#"[
+			{"ref": "创 1:26", "title": "造人"},
+			{"ref": "Gen 1:1", "title": "神创造天地"},
+			{"ref": "创 2:8", "title": "伊甸园"},
+			{"ref": "出 1:1", "title": "以色列人在埃及"},
+			{"ref": "不是经文", "title": "跳过"}
+		]"#;
+		let outline = parse_file_outline(text, 1);
+		assert_eq!(outline, BookOutline::from([
+			(1, vec![(1, "神创造天地".to_string()), (26, "造人".to_string())]),
+			(2, vec![(8, "伊甸园".to_string())]),
+		]));
+		assert!(parse_file_outline("不是 JSON", 1).is_empty());
+	}
+
+	#[test]
+	fn reads_headings_from_the_version_database() {
+		let path = std::env::temp_dir().join(format!("outline-test-{}.db", uuid::Uuid::new_v4()));
+		let conn = Connection::open(&path).unwrap();
+		conn.execute_batch("CREATE TABLE books (number INTEGER, osis TEXT); INSERT INTO books VALUES (1, 'Gen'), (2, 'Exod');").unwrap();
+		assert!(load_db_outline(&path, 1).unwrap().is_empty());
+
+		conn.execute_batch(
+			"CREATE TABLE headings (reference_osis TEXT, verse INTEGER, title TEXT);
+			 INSERT INTO headings VALUES ('Gen.1', 26, '造人'), ('Gen.1', 1, '创造'), ('Gen.12', 1, '呼召亚伯兰'), ('Exod.1', 1, '出埃及');",
+		).unwrap();
+		let outline = load_db_outline(&path, 1).unwrap();
+		assert_eq!(outline, BookOutline::from([
+			(1, vec![(1, "创造".to_string()), (26, "造人".to_string())]),
+			(12, vec![(1, "呼召亚伯兰".to_string())]),
+		]));
+		let _ = fs::remove_file(&path);
+	}
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+use crate::reference::ScriptureRef;
+use crate::utils::{find_chapter_content, split_verses, version_display_name};
+
+/// 已安装的译本，供笔记编辑窗口引用经文
+#[derive(Clone, Default)]
+pub struct BibleSource {
+	pub bible_root: PathBuf,
+	pub versions: Vec<String>,
+	pub current_version: String,
+}
+
+/// 读取引用范围内的经文；书卷、章或节不存在时返回 None
+pub fn load_passage(source: &BibleSource, version: &str, r: &ScriptureRef) -> Option<Vec<(i32, String)>> {
+	let db_path = source.bible_root.join(version);
+	if !db_path.exists() {
+		return None;
+	}
+	let content = find_chapter_content(&db_path, r.book_num, r.chapter)?;
+	let verses = split_verses(&content);
+
+	let passage: Vec<(i32, String)> = match r.verse_start {
+		Some(start) => {
+			let end = r.verse_end.unwrap_or(start).max(start);
+			verses.into_iter().filter(|(n, _)| (start..=end).contains(n)).collect()
+		}
+		None => verses,
+	};
+	if passage.is_empty() { None } else { Some(passage) }
+}
+
+/// 排成 Markdown 引用块，首行的引用在笔记中显示为可点击的链接
+///
+/// ```text
+/// > **约 3:16**（和合本）
+/// > 16 神爱世人……
+/// ```
+pub fn format_quotation(r: &ScriptureRef, version: &str, passage: &[(i32, String)]) -> String {
+	let mut text = format!("> **{}**（{}）\n", r.display(), version_display_name(version));
+	for (number, verse) in passage {
+		text.push_str(&format!("> {} {}\n", number, verse));
+	}
+	text
+}
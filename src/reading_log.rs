@@ -0,0 +1,362 @@
+use std::collections::HashSet;
+use std::time::Instant;
+use rusqlite::Connection;
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::reference::{ScriptureRef, book_full_name, chapter_count};
+use crate::userdata::open_user_db;
+use crate::utils::{now_timestamp, display_timestamp, version_display_name};
+
+/// 停留不到这么多秒的访问不记录（快速翻过的章）
+const MIN_VISIT_SECS: u64 = 3;
+/// 停留超过这么多秒算作读过
+pub const READ_SECS: i64 = 30;
+/// 单次停留最多计这么多秒（离开电脑时不再累加）
+const MAX_VISIT_SECS: u64 = 30 * 60;
+
+/// 正在阅读的章
+pub struct ChapterVisit {
+	pub version: String,
+	pub book_num: i32,
+	pub chapter: i32,
+	pub visited_at: String,
+	pub started: Instant,
+}
+
+/// 一条阅读记录
+#[derive(Clone)]
+pub struct ReadingEntry {
+	pub version: String,
+	pub book_num: i32,
+	pub chapter: i32,
+	pub visited_at: String,
+	pub seconds: i64,
+}
+
+pub fn ensure_reading_log_schema(conn: &Connection) -> rusqlite::Result<()> {
+	conn.execute_batch(
+		"CREATE TABLE IF NOT EXISTS reading_log (
+			id INTEGER PRIMARY KEY AUTOINCREMENT,
+			version TEXT,
+			book_num INTEGER NOT NULL,
+			chapter INTEGER NOT NULL,
+			visited_at TEXT NOT NULL,
+			seconds INTEGER NOT NULL DEFAULT 0
+		);
+		CREATE INDEX IF NOT EXISTS reading_log_chapter ON reading_log (book_num, chapter);",
+	)
+}
+
+/// 结束一次访问并写入记录
+pub fn finish_visit(visit: ChapterVisit) {
+	let seconds = visit.started.elapsed().as_secs().min(MAX_VISIT_SECS);
+	if seconds < MIN_VISIT_SECS {
+		return;
+	}
+	let result = open_user_db().and_then(|conn| {
+		let Some(conn) = conn else { return Ok(0) };
+		conn.execute(
+			"INSERT INTO reading_log (version, book_num, chapter, visited_at, seconds) VALUES (?1, ?2, ?3, ?4, ?5)",
+			rusqlite::params![visit.version, visit.book_num, visit.chapter, visit.visited_at, seconds as i64],
+		)
+	});
+	if let Err(e) = result {
+		eprintln!("保存阅读记录失败: {:?}", e);
+	}
+}
+
+/// 累计停留足够久、算作读过的章
+pub fn load_read_chapters() -> HashSet<(i32, i32)> {
+	let Ok(Some(conn)) = open_user_db() else { return HashSet::new() };
+	let Ok(mut stmt) = conn.prepare(
+		"SELECT book_num, chapter FROM reading_log WHERE chapter >= 1
+		 GROUP BY book_num, chapter HAVING SUM(seconds) >= ?1",
+	) else {
+		return HashSet::new();
+	};
+	match stmt.query_map([READ_SECS], |row| Ok((row.get(0)?, row.get(1)?))) {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => HashSet::new(),
+	}
+}
+
+/// 最近的阅读记录，新的在前
+pub fn load_recent_visits(limit: usize) -> Vec<ReadingEntry> {
+	let Ok(Some(conn)) = open_user_db() else { return Vec::new() };
+	let Ok(mut stmt) = conn.prepare(
+		"SELECT COALESCE(version, ''), book_num, chapter, visited_at, seconds FROM reading_log
+		 ORDER BY id DESC LIMIT ?1",
+	) else {
+		return Vec::new();
+	};
+	let rows = stmt.query_map([limit as i64], |row| {
+		Ok(ReadingEntry {
+			version: row.get(0)?,
+			book_num: row.get(1)?,
+			chapter: row.get(2)?,
+			visited_at: row.get(3)?,
+			seconds: row.get(4)?,
+		})
+	});
+	match rows {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
+pub fn clear_reading_log() -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	conn.execute("DELETE FROM reading_log", [])?;
+	Ok(())
+}
+
+/// 一卷书读过的章数
+pub fn book_read_count(read: &HashSet<(i32, i32)>, book_num: i32) -> i32 {
+	// 章号小于 1 的记录（如前言页）不计入进度
+	read.iter().filter(|(b, c)| *b == book_num && *c >= 1).count() as i32
+}
+
+/// 书卷栏中显示的阅读进度，例如「60%」，读完时为「✓」
+pub fn book_progress_label(read: &HashSet<(i32, i32)>, book_num: i32) -> Option<String> {
+	let total = chapter_count(book_num);
+	let count = book_read_count(read, book_num);
+	if count == 0 || total == 0 {
+		None
+	} else if count >= total {
+		Some("✓".to_string())
+	} else {
+		Some(format!("{}%", count * 100 / total))
+	}
+}
+
+fn format_duration(seconds: i64) -> String {
+	if seconds >= 3600 {
+		format!("{} 小时 {} 分", seconds / 3600, seconds % 3600 / 60)
+	} else if seconds >= 60 {
+		format!("{} 分 {} 秒", seconds / 60, seconds % 60)
+	} else {
+		format!("{} 秒", seconds)
+	}
+}
+
+/// 阅读历史窗口的状态
+#[derive(Default)]
+pub struct ReadingHistoryDialog {
+	entries: Vec<ReadingEntry>,
+	confirm_clear: bool,
+	/// 清空失败时的提示
+	error: Option<String>,
+}
+
+impl ReadingHistoryDialog {
+	pub fn new() -> Self {
+		Self { entries: load_recent_visits(500), ..Default::default() }
+	}
+}
+
+//阅读记录
+impl BibleApp {
+	/// 每帧调用：换章时结束上一章的访问，开始记录新的一章
+	pub fn track_reading(&mut self) {
+		let current = match (self.current_book, self.current_chapter.as_deref()) {
+			(Some(book), Some(chap)) => Some((self.current_version.clone(), book, chap.parse().unwrap_or(1))),
+			_ => None,
+		};
+		let same = match (&self.chapter_visit, &current) {
+			(Some(v), Some((ver, book, chap))) => v.version == *ver && v.book_num == *book && v.chapter == *chap,
+			(None, None) => true,
+			_ => false,
+		};
+		if same {
+			return;
+		}
+		if self.finish_chapter_visit() {
+			self.read_chapters = load_read_chapters();
+		}
+		self.chapter_visit = current.map(|(version, book_num, chapter)| ChapterVisit {
+			version,
+			book_num,
+			chapter,
+			visited_at: now_timestamp(),
+			started: Instant::now(),
+		});
+	}
+
+	/// 写入当前这一章的访问；返回是否有记录
+	pub fn finish_chapter_visit(&mut self) -> bool {
+		match self.chapter_visit.take() {
+			Some(visit) => {
+				finish_visit(visit);
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// 读过的章：阅读记录中停留够久的，加上读经计划中勾选的
+	pub fn is_chapter_read(&self, book_num: i32, chapter: i32) -> bool {
+		self.read_chapters.contains(&(book_num, chapter)) || self.plan_read_chapters.contains(&(book_num, chapter))
+	}
+
+	pub fn all_read_chapters(&self) -> HashSet<(i32, i32)> {
+		self.read_chapters
+			.union(&self.plan_read_chapters)
+			.filter(|(_, c)| *c >= 1)
+			.copied()
+			.collect()
+	}
+
+	pub fn show_reading_history_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(mut dialog) = self.reading_history.take() else { return };
+
+		let mut open = true;
+		let mut go_to: Option<ScriptureRef> = None;
+		let mut clear = false;
+		let read = self.all_read_chapters();
+
+		egui::Window::new(egui::RichText::new("🕘 阅读历史").size(14.0))
+			.open(&mut open)
+			.resizable(true)
+			.default_size([440.0, 480.0])
+			.show(ctx, |ui| {
+				let total_seconds: i64 = dialog.entries.iter().map(|e| e.seconds).sum();
+				let books_done = (1..=66)
+					.filter(|&b| chapter_count(b) > 0 && book_read_count(&read, b) >= chapter_count(b))
+					.count();
+				ui.label(egui::RichText::new(format!(
+					"已读 {} 章（共 1189 章）· 读完 {} 卷 · 最近 {} 次共 {}",
+					read.len(), books_done, dialog.entries.len(), format_duration(total_seconds),
+				)).size(12.0).color(colors.comment_text_color));
+				ui.horizontal(|ui| {
+					if ui.button("刷新").clicked() {
+						dialog.entries = load_recent_visits(500);
+					}
+					if ui.button("清空历史").clicked() {
+						dialog.confirm_clear = true;
+					}
+				});
+				if dialog.confirm_clear {
+					ui.horizontal(|ui| {
+						ui.label(egui::RichText::new("清空全部阅读记录？（读经计划的进度不受影响）").color(colors.text_color));
+						if ui.button("清空").clicked() {
+							clear = true;
+							dialog.confirm_clear = false;
+						}
+						if ui.button("取消").clicked() {
+							dialog.confirm_clear = false;
+						}
+					});
+				}
+				if let Some(error) = &dialog.error {
+					ui.label(egui::RichText::new(format!("⚠ {}", error)).color(egui::Color32::from_rgb(180, 30, 30)));
+				}
+				ui.separator();
+
+				if dialog.entries.is_empty() {
+					ui.label(egui::RichText::new("还没有阅读记录").color(colors.comment_text_color));
+				}
+				egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+					let mut last_day = String::new();
+					for e in &dialog.entries {
+						let shown = display_timestamp(&e.visited_at);
+						let day = shown.get(..10).unwrap_or(&shown).to_string();
+						if day != last_day {
+							ui.label(egui::RichText::new(&day).strong());
+							last_day = day;
+						}
+						ui.horizontal(|ui| {
+							ui.label(egui::RichText::new(shown.get(11..16).unwrap_or("")).size(12.0).color(colors.comment_text_color));
+							if ui.link(format!("{} {}", book_full_name(e.book_num), e.chapter)).clicked() {
+								go_to = Some(ScriptureRef { book_num: e.book_num, chapter: e.chapter, verse_start: None, verse_end: None });
+							}
+							ui.label(egui::RichText::new(format!("{} · {}", version_display_name(&e.version), format_duration(e.seconds)))
+								.size(12.0).color(colors.comment_text_color));
+						});
+					}
+				});
+			});
+
+		if clear {
+			match clear_reading_log() {
+				Ok(()) => {
+					println!("已清空阅读记录");
+					dialog.entries.clear();
+					dialog.error = None;
+					self.read_chapters.clear();
+				}
+				Err(e) => {
+					eprintln!("清空阅读记录失败: {:?}", e);
+					dialog.error = Some(format!("清空失败：{}", e));
+				}
+			}
+		}
+		if let Some(r) = go_to {
+			self.go_to_reference(&r);
+		}
+
+		if open {
+			self.reading_history = Some(dialog);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+	use crate::userdata::test_user_db;
+
+	/// 已在某章停留 secs 秒后离开
+	fn visit(book_num: i32, chapter: i32, secs: u64) {
+		finish_visit(ChapterVisit {
+			version: "cuvs".to_string(),
+			book_num,
+			chapter,
+			visited_at: now_timestamp(),
+			started: Instant::now().checked_sub(Duration::from_secs(secs)).unwrap(),
+		});
+	}
+
+	#[test]
+	fn records_visits_with_capped_duration() {
+		let _db = test_user_db();
+		visit(43, 1, 1);
+		visit(43, 2, 10);
+		visit(43, 3, 5 * 3600);
+		let seconds: Vec<(i32, i64)> = load_recent_visits(10).iter().map(|e| (e.chapter, e.seconds)).collect();
+		assert_eq!(seconds, vec![(3, MAX_VISIT_SECS as i64), (2, 10)]);
+		assert_eq!(load_recent_visits(1).len(), 1);
+	}
+
+	#[test]
+	fn counts_chapters_read_across_visits() {
+		let _db = test_user_db();
+		visit(8, 1, 20);
+		visit(8, 2, 20);
+		visit(8, 1, 20);
+		visit(8, 0, 60);
+		assert_eq!(load_read_chapters(), HashSet::from([(8, 1)]));
+
+		clear_reading_log().unwrap();
+		assert!(load_read_chapters().is_empty());
+		assert!(load_recent_visits(10).is_empty());
+	}
+
+	#[test]
+	fn labels_book_progress() {
+		// 路得记共 4 章
+		let read = HashSet::from([(8, 0), (8, 1), (8, 3), (43, 1)]);
+		assert_eq!(book_read_count(&read, 8), 2);
+		assert_eq!(book_progress_label(&read, 8).as_deref(), Some("50%"));
+		assert_eq!(book_progress_label(&read, 1), None);
+		let all = HashSet::from([(8, 1), (8, 2), (8, 3), (8, 4)]);
+		assert_eq!(book_progress_label(&all, 8).as_deref(), Some("✓"));
+	}
+
+	#[test]
+	fn formats_durations() {
+		assert_eq!(format_duration(45), "45 秒");
+		assert_eq!(format_duration(125), "2 分 5 秒");
+		assert_eq!(format_duration(3 * 3600 + 120), "3 小时 2 分");
+	}
+}
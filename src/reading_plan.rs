@@ -0,0 +1,696 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::Connection;
+use serde::Deserialize;
+use uuid::Uuid;
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::reference::{ScriptureRef, book_full_name, chapter_count, parse_standalone_reference};
+use crate::settings::save_settings;
+use crate::userdata::open_user_db;
+use crate::utils::now_timestamp;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 一章：（书卷, 章）
+pub type Chapter = (i32, i32);
+
+/// 内置读经计划
+#[derive(Clone, Copy, PartialEq)]
+pub enum BuiltinPlan {
+	/// 仿麦琴（M'Cheyne）四线读经：一年旧约一遍，新约与诗篇两遍
+	McCheyne,
+	/// 按历史顺序一年读完全本圣经
+	Chronological,
+	/// 九十天读完新约
+	NewTestament90,
+}
+
+impl BuiltinPlan {
+	pub const ALL: [BuiltinPlan; 3] = [BuiltinPlan::McCheyne, BuiltinPlan::Chronological, BuiltinPlan::NewTestament90];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			BuiltinPlan::McCheyne => "麦琴四线读经（一年）",
+			BuiltinPlan::Chronological => "历史顺序读经（一年）",
+			BuiltinPlan::NewTestament90 => "新约九十天",
+		}
+	}
+
+	pub fn description(self) -> &'static str {
+		match self {
+			BuiltinPlan::McCheyne => "每天四段：创世记—以斯帖记、新约、约伯记—玛拉基书、诗篇与新约",
+			BuiltinPlan::Chronological => "按书卷大致的历史顺序，每天三到四章",
+			BuiltinPlan::NewTestament90 => "马太福音到启示录，每天约三章",
+		}
+	}
+
+	pub fn days(self) -> Vec<Vec<Chapter>> {
+		match self {
+			BuiltinPlan::McCheyne => {
+				let streams = [
+					book_chapters(1..=17),
+					book_chapters(40..=66),
+					book_chapters(18..=39),
+					[book_chapters(19..=19), book_chapters(40..=66)].concat(),
+				];
+				let split: Vec<Vec<Vec<Chapter>>> = streams.iter().map(|s| spread(s, 365)).collect();
+				(0..365).map(|d| split.iter().flat_map(|s| s[d].clone()).collect()).collect()
+			}
+			BuiltinPlan::Chronological => {
+				let chapters: Vec<Chapter> = CHRONOLOGICAL_ORDER.iter().flat_map(|&b| book_chapters(b..=b)).collect();
+				spread(&chapters, 365)
+			}
+			BuiltinPlan::NewTestament90 => spread(&book_chapters(40..=66), 90),
+		}
+	}
+}
+
+/// 书卷大致的历史顺序（约伯记放在创世记之后，诗篇、箴言放在大卫、所罗门时期，先知书按其时代）
+const CHRONOLOGICAL_ORDER: [i32; 66] = [
+	1, 18, 2, 3, 4, 5, 6, 7, 8, 9, 10, 13, 19, 11, 20, 21, 22, 12, 14, 32, 30, 28, 23, 33,
+	34, 36, 35, 29, 24, 25, 31, 26, 27, 15, 37, 38, 17, 16, 39,
+	40, 41, 42, 43, 44, 59, 48, 52, 53, 46, 47, 45, 49, 50, 51, 57, 54, 56, 60, 55, 61, 58,
+	65, 62, 63, 64, 66,
+];
+
+fn book_chapters(books: std::ops::RangeInclusive<i32>) -> Vec<Chapter> {
+	books.flat_map(|b| (1..=chapter_count(b)).map(move |c| (b, c))).collect()
+}
+
+/// 把章平均分到若干天
+fn spread(chapters: &[Chapter], days: usize) -> Vec<Vec<Chapter>> {
+	let n = chapters.len();
+	(0..days).map(|d| chapters[d * n / days..(d + 1) * n / days].to_vec()).collect()
+}
+
+/// 解析一段读经，例如「创 1-3」「Gen 1」「约 3:16」（按整章计）；无法识别时返回 None
+pub fn parse_reading(text: &str) -> Option<Vec<Chapter>> {
+	let text = text.trim().replace(['–', '—'], "-");
+	if !text.contains(':') && !text.contains('：')
+		&& let Some((start, end)) = text.rsplit_once('-')
+		&& let Some(r) = parse_standalone_reference(start)
+		&& let Ok(end) = end.trim().parse::<i32>()
+	{
+		let end = end.min(chapter_count(r.book_num));
+		return Some((r.chapter..=end).map(|c| (r.book_num, c)).collect());
+	}
+	parse_standalone_reference(&text).map(|r| vec![(r.book_num, r.chapter)])
+}
+
+/// 解析一天中的一段读经；无法识别的记下来，留给调用方提示
+fn push_reading(day: &mut Vec<Chapter>, text: &str, unknown: &mut Vec<String>) {
+	let text = text.trim();
+	if text.is_empty() {
+		return;
+	}
+	match parse_reading(text) {
+		Some(chapters) => day.extend(chapters),
+		None => {
+			eprintln!("读经计划中无法识别的经文：{}", text);
+			unknown.push(text.to_string());
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct PlanFile {
+	name: Option<String>,
+	/// 每天的读经，例如 [["Gen 1-3", "Matt 1"], ...]
+	days: Vec<Vec<String>>,
+}
+
+/// 从文件读出的计划
+pub struct ImportedPlan {
+	pub name: String,
+	pub days: Vec<Vec<Chapter>>,
+	/// 无法识别的经文
+	pub unknown: Vec<String>,
+}
+
+/// 从 JSON（{"name": ..., "days": [[...], ...]}）或 CSV（每行「天数,经文」）读取计划
+pub fn read_plan_file(path: &Path) -> Result<ImportedPlan, String> {
+	let text = fs::read_to_string(path).map_err(|e| format!("无法读取文件：{}", e))?;
+	let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("自定义计划").to_string();
+	let is_json = path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("json"));
+	parse_plan_text(&text, &stem, is_json)
+}
+
+fn parse_plan_text(text: &str, stem: &str, is_json: bool) -> Result<ImportedPlan, String> {
+	let mut unknown = Vec::new();
+	let (name, days) = if is_json {
+		let file: PlanFile = serde_json::from_str(text).map_err(|e| format!("JSON 格式不正确：{}", e))?;
+		let days = file.days.iter()
+			.map(|readings| {
+				let mut day = Vec::new();
+				for r in readings {
+					push_reading(&mut day, r, &mut unknown);
+				}
+				day
+			})
+			.collect();
+		(file.name.unwrap_or_else(|| stem.to_string()), days)
+	} else {
+		let mut days: Vec<Vec<Chapter>> = Vec::new();
+		for line in text.lines() {
+			let Some((day, readings)) = line.split_once(',') else { continue };
+			// 跳过表头等非数字行
+			let Ok(day) = day.trim().trim_matches('"').parse::<usize>() else { continue };
+			if day == 0 {
+				continue;
+			}
+			if days.len() < day {
+				days.resize(day, Vec::new());
+			}
+			for r in readings.trim().trim_matches('"').split([';', '；', ',']) {
+				push_reading(&mut days[day - 1], r, &mut unknown);
+			}
+		}
+		(stem.to_string(), days)
+	};
+
+	if days.iter().all(|d: &Vec<Chapter>| d.is_empty()) {
+		return Err("文件中没有可识别的经文".to_string());
+	}
+	Ok(ImportedPlan { name, days, unknown })
+}
+
+/// 一个已开始的读经计划
+#[derive(Clone)]
+pub struct ReadingPlan {
+	pub id: String,
+	pub name: String,
+	pub days: Vec<Vec<Chapter>>,
+	pub start_date: NaiveDate,
+}
+
+impl ReadingPlan {
+	/// 今天是计划的第几天（从 1 开始）
+	pub fn today_index(&self) -> i64 {
+		(Local::now().date_naive() - self.start_date).num_days() + 1
+	}
+}
+
+pub fn ensure_reading_plan_schema(conn: &Connection) -> rusqlite::Result<()> {
+	conn.execute_batch(
+		"CREATE TABLE IF NOT EXISTS reading_plans (
+			id TEXT PRIMARY KEY,
+			name TEXT NOT NULL,
+			days TEXT NOT NULL,
+			start_date TEXT NOT NULL,
+			created_at TEXT
+		);
+		CREATE TABLE IF NOT EXISTS reading_plan_progress (
+			plan_id TEXT NOT NULL,
+			day INTEGER NOT NULL,
+			book_num INTEGER NOT NULL,
+			chapter INTEGER NOT NULL,
+			done_at TEXT NOT NULL,
+			PRIMARY KEY (plan_id, day, book_num, chapter)
+		);",
+	)
+}
+
+/// 开始一个计划，从今天算第一天
+pub fn create_plan(name: &str, days: &[Vec<Chapter>]) -> Result<String, String> {
+	let conn = open_user_db().map_err(|e| e.to_string())?.ok_or("找不到用户数据库")?;
+	let id = Uuid::new_v4().to_string();
+	let days_json = serde_json::to_string(days).map_err(|e| e.to_string())?;
+	conn.execute(
+		"INSERT INTO reading_plans (id, name, days, start_date, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+		rusqlite::params![id, name, days_json, Local::now().format(DATE_FORMAT).to_string(), now_timestamp()],
+	).map_err(|e| e.to_string())?;
+	Ok(id)
+}
+
+/// 所有计划（id, 名字），新的在前
+pub fn list_plans() -> Vec<(String, String)> {
+	let Ok(Some(conn)) = open_user_db() else { return Vec::new() };
+	let Ok(mut stmt) = conn.prepare("SELECT id, name FROM reading_plans ORDER BY created_at DESC") else { return Vec::new() };
+	match stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))) {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
+pub fn load_plan(id: &str) -> Option<ReadingPlan> {
+	let conn = open_user_db().ok()??;
+	let (name, days, start): (String, String, String) = conn.query_row(
+		"SELECT name, days, start_date FROM reading_plans WHERE id = ?1",
+		[id],
+		|row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+	).ok()?;
+	Some(ReadingPlan {
+		id: id.to_string(),
+		name,
+		days: serde_json::from_str(&days).unwrap_or_default(),
+		start_date: NaiveDate::parse_from_str(&start, DATE_FORMAT).unwrap_or_else(|_| Local::now().date_naive()),
+	})
+}
+
+pub fn delete_plan(id: &str) -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	conn.execute("DELETE FROM reading_plan_progress WHERE plan_id = ?1", [id])?;
+	conn.execute("DELETE FROM reading_plans WHERE id = ?1", [id])?;
+	Ok(())
+}
+
+/// 已读完的（天, 书卷, 章）及完成日期
+pub fn load_progress(plan_id: &str) -> Vec<(usize, i32, i32, String)> {
+	let Ok(Some(conn)) = open_user_db() else { return Vec::new() };
+	let Ok(mut stmt) = conn.prepare(
+		"SELECT day, book_num, chapter, done_at FROM reading_plan_progress WHERE plan_id = ?1",
+	) else {
+		return Vec::new();
+	};
+	match stmt.query_map([plan_id], |row| Ok((row.get::<_, i64>(0)? as usize, row.get(1)?, row.get(2)?, row.get(3)?))) {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
+pub fn set_reading_done(plan_id: &str, day: usize, chapter: Chapter, done: bool) -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	if done {
+		conn.execute(
+			"INSERT OR IGNORE INTO reading_plan_progress (plan_id, day, book_num, chapter, done_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+			rusqlite::params![plan_id, day as i64, chapter.0, chapter.1, now_timestamp()],
+		)?;
+	} else {
+		conn.execute(
+			"DELETE FROM reading_plan_progress WHERE plan_id = ?1 AND day = ?2 AND book_num = ?3 AND chapter = ?4",
+			rusqlite::params![plan_id, day as i64, chapter.0, chapter.1],
+		)?;
+	}
+	Ok(())
+}
+
+/// 补读：把计划顺延，使第一个没读完的日子成为今天
+pub fn reschedule_plan(plan: &ReadingPlan, first_unfinished: usize) -> rusqlite::Result<()> {
+	let Some(conn) = open_user_db()? else { return Ok(()) };
+	let start = Local::now().date_naive() - Duration::days(first_unfinished as i64 - 1);
+	conn.execute(
+		"UPDATE reading_plans SET start_date = ?1 WHERE id = ?2",
+		[start.format(DATE_FORMAT).to_string(), plan.id.clone()],
+	)?;
+	Ok(())
+}
+
+/// 连续读经的天数：从今天（今天还没读则从昨天）往前数
+pub fn reading_streak(done_dates: &HashSet<NaiveDate>) -> usize {
+	let today = Local::now().date_naive();
+	let mut day = if done_dates.contains(&today) { today } else { today - Duration::days(1) };
+	let mut streak = 0;
+	while done_dates.contains(&day) {
+		streak += 1;
+		day -= Duration::days(1);
+	}
+	streak
+}
+
+/// 一天的读经显示为「创世记 1-3；马太福音 1」
+pub fn describe_day(chapters: &[Chapter]) -> String {
+	let mut parts: Vec<String> = Vec::new();
+	let mut i = 0;
+	while i < chapters.len() {
+		let (book, start) = chapters[i];
+		let mut end = start;
+		while i + 1 < chapters.len() && chapters[i + 1] == (book, end + 1) {
+			end += 1;
+			i += 1;
+		}
+		parts.push(if end > start {
+			format!("{} {}-{}", book_full_name(book), start, end)
+		} else {
+			format!("{} {}", book_full_name(book), start)
+		});
+		i += 1;
+	}
+	parts.join("；")
+}
+
+/// 读经计划窗口的状态
+#[derive(Default)]
+pub struct ReadingPlanDialog {
+	plans: Vec<(String, String)>,
+	plan: Option<ReadingPlan>,
+	/// 已读完的（天, 书卷, 章）
+	done: HashSet<(usize, i32, i32)>,
+	done_dates: HashSet<NaiveDate>,
+	/// 正在查看的天（从 1 开始）
+	view_day: usize,
+	file_path: String,
+	status: Option<String>,
+}
+
+impl ReadingPlanDialog {
+	pub fn new(active: Option<&str>) -> Self {
+		let mut dialog = Self { plans: list_plans(), ..Default::default() };
+		dialog.open_plan(active.and_then(load_plan));
+		dialog
+	}
+
+	fn open_plan(&mut self, plan: Option<ReadingPlan>) {
+		self.plan = plan;
+		self.reload_progress();
+		if let Some(plan) = &self.plan {
+			self.view_day = plan.today_index().clamp(1, plan.days.len().max(1) as i64) as usize;
+		}
+	}
+
+	fn reload_progress(&mut self) {
+		let progress = self.plan.as_ref().map(|p| load_progress(&p.id)).unwrap_or_default();
+		self.done = progress.iter().map(|(d, b, c, _)| (*d, *b, *c)).collect();
+		self.done_dates = progress.iter()
+			.filter_map(|(_, _, _, at)| NaiveDate::parse_from_str(at.get(..10)?, DATE_FORMAT).ok())
+			.collect();
+	}
+
+	fn day_done(&self, day: usize) -> bool {
+		self.plan.as_ref().is_some_and(|p| {
+			p.days.get(day - 1).is_some_and(|chapters| chapters.iter().all(|(b, c)| self.done.contains(&(day, *b, *c))))
+		})
+	}
+
+	/// 第一个没读完的天，全部读完时为 None
+	fn first_unfinished(&self) -> Option<usize> {
+		let plan = self.plan.as_ref()?;
+		(1..=plan.days.len()).find(|&d| !self.day_done(d))
+	}
+}
+
+/// 计划中已读完的章（用于在章节栏中标记）
+pub fn read_chapters(plan_id: &str) -> HashSet<Chapter> {
+	load_progress(plan_id).into_iter().map(|(_, b, c, _)| (b, c)).collect()
+}
+
+/// 读经计划窗口中的操作
+enum PlanAction {
+	Start(String, Vec<Vec<Chapter>>),
+	Open(String),
+	Delete(String),
+	GoTo(Chapter),
+	SetDone(usize, Vec<Chapter>, bool),
+	Reschedule(usize),
+}
+
+//读经计划
+impl BibleApp {
+	/// 重新读取当前计划已读的章
+	pub fn reload_plan_read_chapters(&mut self) {
+		self.plan_read_chapters = match &self.settings.active_reading_plan {
+			Some(id) => read_chapters(id),
+			None => HashSet::new(),
+		};
+	}
+
+	pub fn show_reading_plan_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(mut dialog) = self.reading_plan_dialog.take() else { return };
+
+		let mut open = true;
+		let mut action: Option<PlanAction> = None;
+		// 导入的计划中有无法识别的经文
+		let mut warning: Option<String> = None;
+
+		egui::Window::new(egui::RichText::new("📅 读经计划").size(14.0))
+			.open(&mut open)
+			.resizable(true)
+			.default_size([460.0, 500.0])
+			.show(ctx, |ui| {
+				if let Some(plan) = dialog.plan.clone() {
+					let total = plan.days.len();
+					let today = plan.today_index();
+					let first_unfinished = dialog.first_unfinished();
+					let finished_days = (1..=total).filter(|&d| dialog.day_done(d)).count();
+
+					ui.label(egui::RichText::new(&plan.name).strong().size(15.0));
+					ui.label(egui::RichText::new(format!(
+						"开始于 {} · 已完成 {}/{} 天 · 连续读经 {} 天",
+						plan.start_date.format(DATE_FORMAT), finished_days, total, reading_streak(&dialog.done_dates),
+					)).size(12.0).color(colors.comment_text_color));
+
+					match first_unfinished {
+						None => {
+							ui.label(egui::RichText::new("🎉 计划已全部读完").color(colors.text_color));
+						}
+						Some(first) if (first as i64) < today => {
+							ui.horizontal(|ui| {
+								ui.label(egui::RichText::new(format!("落后 {} 天", today - first as i64)).color(colors.text_color));
+								if ui.button("顺延计划").on_hover_text("从第一个没读完的日子开始，把之后的安排整体往后推").clicked() {
+									action = Some(PlanAction::Reschedule(first));
+								}
+								if ui.button("去补读").clicked() {
+									dialog.view_day = first;
+								}
+							});
+						}
+						Some(_) => {}
+					}
+					ui.separator();
+
+					// ===== 某一天的读经 =====
+					ui.horizontal(|ui| {
+						if ui.add_enabled(dialog.view_day > 1, egui::Button::new("◀")).clicked() {
+							dialog.view_day -= 1;
+						}
+						let date = plan.start_date + Duration::days(dialog.view_day as i64 - 1);
+						let title = if dialog.view_day as i64 == today {
+							format!("今天 · 第 {} 天", dialog.view_day)
+						} else {
+							format!("{} · 第 {} 天", date.format(DATE_FORMAT), dialog.view_day)
+						};
+						ui.label(egui::RichText::new(title).strong());
+						if ui.add_enabled(dialog.view_day < total, egui::Button::new("▶")).clicked() {
+							dialog.view_day += 1;
+						}
+						if dialog.view_day as i64 != today && (1..=total as i64).contains(&today) && ui.button("回到今天").clicked() {
+							dialog.view_day = today as usize;
+						}
+					});
+
+					let day = dialog.view_day;
+					let chapters = plan.days.get(day - 1).cloned().unwrap_or_default();
+					if chapters.is_empty() {
+						ui.label(egui::RichText::new("这一天没有安排").color(colors.comment_text_color));
+					} else {
+						ui.label(egui::RichText::new(describe_day(&chapters)).size(12.0).color(colors.comment_text_color));
+					}
+					for &(book, chapter) in &chapters {
+						ui.horizontal(|ui| {
+							let mut done = dialog.done.contains(&(day, book, chapter));
+							if ui.checkbox(&mut done, "").changed() {
+								action = Some(PlanAction::SetDone(day, vec![(book, chapter)], done));
+							}
+							if ui.link(format!("{} {}", book_full_name(book), chapter)).clicked() {
+								action = Some(PlanAction::GoTo((book, chapter)));
+							}
+						});
+					}
+					if !chapters.is_empty() && !dialog.day_done(day) && ui.button("✔ 全部读完").clicked() {
+						action = Some(PlanAction::SetDone(day, chapters.clone(), true));
+					}
+					ui.separator();
+				}
+
+				// ===== 我的计划 =====
+				egui::CollapsingHeader::new("我的计划").default_open(dialog.plan.is_none()).show(ui, |ui| {
+					if dialog.plans.is_empty() {
+						ui.label(egui::RichText::new("还没有开始任何计划").color(colors.comment_text_color));
+					}
+					for (id, name) in &dialog.plans {
+						ui.horizontal(|ui| {
+							let is_active = dialog.plan.as_ref().is_some_and(|p| &p.id == id);
+							if ui.selectable_label(is_active, name).clicked() && !is_active {
+								action = Some(PlanAction::Open(id.clone()));
+							}
+							ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+								if ui.small_button("🗑").on_hover_text("删除计划及进度").clicked() {
+									action = Some(PlanAction::Delete(id.clone()));
+								}
+							});
+						});
+					}
+				});
+
+				// ===== 开始新计划 =====
+				egui::CollapsingHeader::new("开始新计划").default_open(dialog.plan.is_none()).show(ui, |ui| {
+					for builtin in BuiltinPlan::ALL {
+						ui.horizontal(|ui| {
+							if ui.button("开始").clicked() {
+								action = Some(PlanAction::Start(builtin.name().to_string(), builtin.days()));
+							}
+							ui.label(builtin.name());
+						});
+						ui.label(egui::RichText::new(builtin.description()).size(12.0).color(colors.comment_text_color));
+					}
+					ui.separator();
+					ui.label(egui::RichText::new("从文件导入：JSON（{\"name\": ..., \"days\": [[\"Gen 1-3\", \"Matt 1\"], ...]}）或 CSV（每行「天数,经文」）")
+						.size(12.0).color(colors.comment_text_color));
+					ui.horizontal(|ui| {
+						ui.add(egui::TextEdit::singleline(&mut dialog.file_path).hint_text("文件路径").desired_width(300.0));
+						if ui.button("开始").clicked() {
+							match read_plan_file(Path::new(dialog.file_path.trim())) {
+								Ok(ImportedPlan { name, days, unknown }) => {
+									if !unknown.is_empty() {
+										let shown: Vec<&str> = unknown.iter().take(5).map(String::as_str).collect();
+										warning = Some(format!(
+											"有 {} 段经文无法识别，已跳过：{}{}",
+											unknown.len(), shown.join("、"), if unknown.len() > 5 { "……" } else { "" },
+										));
+									}
+									action = Some(PlanAction::Start(name, days));
+								}
+								Err(e) => dialog.status = Some(e),
+							}
+						}
+					});
+				});
+
+				if let Some(status) = &dialog.status {
+					ui.label(egui::RichText::new(status).size(12.0).color(colors.comment_text_color));
+				}
+			});
+
+		let mut plan_changed = false;
+		match action {
+			Some(PlanAction::Start(name, days)) => match create_plan(&name, &days) {
+				Ok(id) => {
+					println!("已开始读经计划「{}」", name);
+					self.settings.active_reading_plan = Some(id.clone());
+					dialog.plans = list_plans();
+					dialog.open_plan(load_plan(&id));
+					dialog.status = warning;
+					plan_changed = true;
+				}
+				Err(e) => {
+					eprintln!("创建读经计划失败: {}", e);
+					dialog.status = Some(e);
+				}
+			},
+			Some(PlanAction::Open(id)) => {
+				self.settings.active_reading_plan = Some(id.clone());
+				dialog.open_plan(load_plan(&id));
+				plan_changed = true;
+			}
+			Some(PlanAction::Delete(id)) => {
+				if let Err(e) = delete_plan(&id) {
+					eprintln!("删除读经计划失败: {:?}", e);
+				}
+				if self.settings.active_reading_plan.as_deref() == Some(id.as_str()) {
+					self.settings.active_reading_plan = None;
+					dialog.open_plan(None);
+				}
+				dialog.plans = list_plans();
+				plan_changed = true;
+			}
+			// 通过正常的选章流程跳转
+			Some(PlanAction::GoTo((book, chapter))) => {
+				self.go_to_reference(&ScriptureRef { book_num: book, chapter, verse_start: None, verse_end: None });
+			}
+			Some(PlanAction::SetDone(day, chapters, done)) => {
+				if let Some(plan) = &dialog.plan {
+					for chapter in chapters {
+						if let Err(e) = set_reading_done(&plan.id, day, chapter, done) {
+							eprintln!("保存读经进度失败: {:?}", e);
+						}
+					}
+				}
+				dialog.reload_progress();
+				self.reload_plan_read_chapters();
+			}
+			Some(PlanAction::Reschedule(first)) => {
+				if let Some(plan) = &dialog.plan {
+					if let Err(e) = reschedule_plan(plan, first) {
+						eprintln!("顺延读经计划失败: {:?}", e);
+					}
+					let id = plan.id.clone();
+					dialog.open_plan(load_plan(&id));
+				}
+			}
+			None => {}
+		}
+		if plan_changed {
+			save_settings(&self.settings);
+			self.reload_plan_read_chapters();
+		}
+
+		if open {
+			self.reading_plan_dialog = Some(dialog);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_single_chapters_and_ranges() {
+		assert_eq!(parse_reading("创 1-3"), Some(vec![(1, 1), (1, 2), (1, 3)]));
+		assert_eq!(parse_reading("Gen 1–2"), Some(vec![(1, 1), (1, 2)]));
+		assert_eq!(parse_reading("太 5"), Some(vec![(40, 5)]));
+		assert_eq!(parse_reading("约一 1"), Some(vec![(62, 1)]));
+		// 带节的引用按整章计
+		assert_eq!(parse_reading("约 3:16-18"), Some(vec![(43, 3)]));
+	}
+
+	#[test]
+	fn clamps_ranges_to_the_book() {
+		assert_eq!(parse_reading("犹 1-5"), Some(vec![(65, 1)]));
+	}
+
+	#[test]
+	fn rejects_unknown_readings() {
+		assert_eq!(parse_reading("xx 1"), None);
+		assert_eq!(parse_reading(""), None);
+		assert_eq!(parse_reading("创世记 51"), None);
+	}
+
+	#[test]
+	fn parses_json_plans() {
+		let text = r#"{"name": "两天", "days": [["Gen 1-2", "Matt 1"], ["创 3", "不知道 1"]]}"#;
+		let plan = parse_plan_text(text, "file", true).unwrap();
+		assert_eq!(plan.name, "两天");
+		assert_eq!(plan.days, vec![vec![(1, 1), (1, 2), (40, 1)], vec![(1, 3)]]);
+		assert_eq!(plan.unknown, vec!["不知道 1".to_string()]);
+	}
+
+	#[test]
+	fn json_plan_without_name_uses_file_stem() {
+		let plan = parse_plan_text(r#"{"days": [["诗 1"]]}"#, "诗篇", true).unwrap();
+		assert_eq!(plan.name, "诗篇");
+	}
+
+	#[test]
+	fn parses_csv_plans() {
+		let text = "天,经文\n1,\"创 1-2; 太 1\"\n3,诗 1\n";
+		let plan = parse_plan_text(text, "csv 计划", false).unwrap();
+		assert_eq!(plan.name, "csv 计划");
+		assert_eq!(plan.days, vec![vec![(1, 1), (1, 2), (40, 1)], vec![], vec![(19, 1)]]);
+		assert!(plan.unknown.is_empty());
+	}
+
+	#[test]
+	fn rejects_plans_without_readings() {
+		assert!(parse_plan_text("天,经文\n1,xx 1\n", "x", false).is_err());
+		assert!(parse_plan_text("not json", "x", true).is_err());
+	}
+
+	#[test]
+	fn reads_plan_files_by_extension() {
+		let path = std::env::temp_dir().join(format!("plan-test-{}.json", Uuid::new_v4()));
+		fs::write(&path, r#"{"days": [["创 1"]]}"#).unwrap();
+		let plan = read_plan_file(&path);
+		fs::remove_file(&path).unwrap();
+		let plan = plan.unwrap();
+		assert_eq!(plan.days, vec![vec![(1, 1)]]);
+		assert!(plan.name.starts_with("plan-test-"));
+	}
+
+	#[test]
+	fn describes_consecutive_chapters_as_ranges() {
+		assert_eq!(describe_day(&[(1, 1), (1, 2), (1, 3), (40, 1)]), "创世记 1-3；马太福音 1");
+	}
+}
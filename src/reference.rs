@@ -0,0 +1,351 @@
+use std::ops::Range;
+
+/// 经文引用：书卷编号（1-66）、章、可选的节范围
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScriptureRef {
+	pub book_num: i32,
+	pub chapter: i32,
+	pub verse_start: Option<i32>,
+	pub verse_end: Option<i32>,
+}
+
+/// 和合本书卷全名
+pub const BOOK_NAMES_CN: [&str; 66] = [
+	"创世记", "出埃及记", "利未记", "民数记", "申命记", "约书亚记", "士师记", "路得记",
+	"撒母耳记上", "撒母耳记下", "列王纪上", "列王纪下", "历代志上", "历代志下",
+	"以斯拉记", "尼希米记", "以斯帖记", "约伯记", "诗篇", "箴言", "传道书", "雅歌",
+	"以赛亚书", "耶利米书", "耶利米哀歌", "以西结书", "但以理书", "何西阿书", "约珥书",
+	"阿摩司书", "俄巴底亚书", "约拿书", "弥迦书", "那鸿书", "哈巴谷书", "西番雅书",
+	"哈该书", "撒迦利亚书", "玛拉基书",
+	"马太福音", "马可福音", "路加福音", "约翰福音", "使徒行传", "罗马书", "哥林多前书",
+	"哥林多后书", "加拉太书", "以弗所书", "腓立比书", "歌罗西书", "帖撒罗尼迦前书",
+	"帖撒罗尼迦后书", "提摩太前书", "提摩太后书", "提多书", "腓利门书", "希伯来书",
+	"雅各书", "彼得前书", "彼得后书", "约翰一书", "约翰二书", "约翰三书", "犹大书", "启示录",
+];
+
+/// OSIS 书卷缩写
+pub const BOOK_OSIS: [&str; 66] = [
+	"Gen", "Exod", "Lev", "Num", "Deut", "Josh", "Judg", "Ruth", "1Sam", "2Sam",
+	"1Kgs", "2Kgs", "1Chr", "2Chr", "Ezra", "Neh", "Esth", "Job", "Ps", "Prov",
+	"Eccl", "Song", "Isa", "Jer", "Lam", "Ezek", "Dan", "Hos", "Joel", "Amos",
+	"Obad", "Jonah", "Mic", "Nah", "Hab", "Zeph", "Hag", "Zech", "Mal",
+	"Matt", "Mark", "Luke", "John", "Acts", "Rom", "1Cor", "2Cor", "Gal", "Eph",
+	"Phil", "Col", "1Thess", "2Thess", "1Tim", "2Tim", "Titus", "Phlm", "Heb",
+	"Jas", "1Pet", "2Pet", "1John", "2John", "3John", "Jude", "Rev",
+];
+
+/// 英文书卷全名
+const BOOK_NAMES_EN: [&str; 66] = [
+	"Genesis", "Exodus", "Leviticus", "Numbers", "Deuteronomy", "Joshua", "Judges", "Ruth",
+	"1 Samuel", "2 Samuel", "1 Kings", "2 Kings", "1 Chronicles", "2 Chronicles", "Ezra",
+	"Nehemiah", "Esther", "Job", "Psalms", "Proverbs", "Ecclesiastes", "Song of Songs",
+	"Isaiah", "Jeremiah", "Lamentations", "Ezekiel", "Daniel", "Hosea", "Joel", "Amos",
+	"Obadiah", "Jonah", "Micah", "Nahum", "Habakkuk", "Zephaniah", "Haggai", "Zechariah",
+	"Malachi",
+	"Matthew", "Mark", "Luke", "John", "Acts", "Romans", "1 Corinthians", "2 Corinthians",
+	"Galatians", "Ephesians", "Philippians", "Colossians", "1 Thessalonians",
+	"2 Thessalonians", "1 Timothy", "2 Timothy", "Titus", "Philemon", "Hebrews", "James",
+	"1 Peter", "2 Peter", "1 John", "2 John", "3 John", "Jude", "Revelation",
+];
+
+/// 常见的其他写法
+const BOOK_ALIASES: [(&str, i32); 4] = [
+	("创世纪", 1),
+	("Psalm", 19),
+	("Song of Solomon", 22),
+	("Revelations", 66),
+];
+
+/// 各书卷章数（新教 66 卷正典）
+pub const CHAPTER_COUNTS: [i32; 66] = [
+	50, 40, 27, 36, 34, 24, 21, 4, 31, 24, 22, 25, 29, 36, 10, 13, 10, 42, 150, 31,
+	12, 8, 66, 52, 5, 48, 12, 14, 3, 9, 1, 4, 7, 3, 3, 3, 2, 14, 4,
+	28, 16, 24, 21, 28, 16, 16, 13, 6, 6, 4, 4, 5, 3, 6, 4, 3, 1, 13, 5, 5, 3, 5, 1, 1, 1, 22,
+];
+
+/// 书卷章数，编号无效时为 0
+pub fn chapter_count(book_num: i32) -> i32 {
+	if (1..=66).contains(&book_num) {
+		CHAPTER_COUNTS[(book_num - 1) as usize]
+	} else {
+		0
+	}
+}
+
+/// 书卷中文全名
+pub fn book_full_name(book_num: i32) -> &'static str {
+	if (1..=66).contains(&book_num) {
+		BOOK_NAMES_CN[(book_num - 1) as usize]
+	} else {
+		"未知"
+	}
+}
+
+/// 所有可识别的书卷名（按长度降序，保证最长匹配优先）
+fn book_name_table() -> &'static [(String, i32)] {
+	static TABLE: std::sync::OnceLock<Vec<(String, i32)>> = std::sync::OnceLock::new();
+	TABLE.get_or_init(|| {
+		let mut names: Vec<(String, i32)> = Vec::new();
+		for i in 0..66 {
+			let num = i as i32 + 1;
+			names.push((BOOK_NAMES_CN[i].to_string(), num));
+			names.push((crate::utils::book_number_to_abbr(num).to_string(), num));
+			names.push((BOOK_OSIS[i].to_string(), num));
+			names.push((BOOK_NAMES_EN[i].to_string(), num));
+			// "1 Samuel" 也常写作 "1Samuel"
+			if BOOK_NAMES_EN[i].contains(' ') {
+				names.push((BOOK_NAMES_EN[i].replacen(' ', "", 1), num));
+			}
+		}
+		for (alias, num) in BOOK_ALIASES {
+			names.push((alias.to_string(), num));
+		}
+		names.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(&b.0)));
+		names.dedup();
+		names
+	})
+}
+
+fn skip_spaces(s: &str, mut i: usize) -> usize {
+	while let Some(c) = s[i..].chars().next() {
+		if c == ' ' || c == '\u{3000}' {
+			i += c.len_utf8();
+		} else {
+			break;
+		}
+	}
+	i
+}
+
+fn read_number(s: &str, i: usize) -> Option<(i32, usize)> {
+	let digits: String = s[i..].chars().take_while(|c| c.is_ascii_digit()).collect();
+	if digits.is_empty() || digits.len() > 3 {
+		return None;
+	}
+	digits.parse().ok().map(|n| (n, i + digits.len()))
+}
+
+fn read_any(s: &str, i: usize, options: &[char]) -> Option<usize> {
+	let c = s[i..].chars().next()?;
+	options.contains(&c).then(|| i + c.len_utf8())
+}
+
+/// 书卷名之后解析出的章节部分
+struct ChapterVerse {
+	chapter: i32,
+	verse_start: Option<i32>,
+	verse_end: Option<i32>,
+	end: usize,
+	/// 是否带有「:」或「章」，单字简称必须带上才算引用
+	explicit: bool,
+}
+
+/// 解析紧跟在书卷名之后的章节部分
+///
+/// 支持「3:16」「3：16-18」「3」以及「3章16节」「3章16-18节」等写法。
+fn parse_chapter_verse(s: &str, start: usize) -> Option<ChapterVerse> {
+	let i = skip_spaces(s, start);
+	let (chapter, mut i) = read_number(s, i)?;
+
+	let mut verse_start = None;
+	let mut verse_end = None;
+	let mut explicit = false;
+
+	if let Some(after_sep) = read_any(s, i, &[':', '：']) {
+		if let Some((v, j)) = read_number(s, skip_spaces(s, after_sep)) {
+			verse_start = Some(v);
+			explicit = true;
+			i = j;
+		}
+	} else if let Some(after_zhang) = read_any(s, i, &['章']) {
+		explicit = true;
+		i = after_zhang;
+		if let Some((v, j)) = read_number(s, i) {
+			verse_start = Some(v);
+			i = j;
+			if let Some(after_jie) = read_any(s, i, &['节']) {
+				i = after_jie;
+			}
+		}
+	}
+
+	if verse_start.is_some() {
+		let j = skip_spaces(s, i);
+		if let Some(after_dash) = read_any(s, j, &['-', '–', '—', '~', '～', '至'])
+			&& let Some((v, k)) = read_number(s, skip_spaces(s, after_dash))
+		{
+			verse_end = Some(v);
+			i = k;
+			if let Some(after_jie) = read_any(s, i, &['节']) {
+				i = after_jie;
+			}
+		}
+	}
+
+	Some(ChapterVerse { chapter, verse_start, verse_end, end: i, explicit })
+}
+
+/// 在文本中查找所有经文引用及其字节范围
+pub fn find_references(text: &str) -> Vec<(Range<usize>, ScriptureRef)> {
+	let table = book_name_table();
+	let mut found = Vec::new();
+	let mut i = 0;
+
+	while i < text.len() {
+		let rest = &text[i..];
+		let prev = text[..i].chars().next_back();
+		let mut matched = None;
+
+		for (name, num) in table {
+			if !rest.starts_with(name.as_str()) {
+				continue;
+			}
+			// 英文书名前后不能紧挨字母，避免把 "remark 2" 识别成 "Mark 2"
+			if name.chars().next().is_some_and(|c| c.is_ascii_alphanumeric())
+				&& prev.is_some_and(|c| c.is_ascii_alphanumeric())
+			{
+				continue;
+			}
+			let after_name = i + name.len();
+			if name.chars().next_back().is_some_and(|c| c.is_ascii_alphabetic())
+				&& text[after_name..].chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+			{
+				continue;
+			}
+			let single_cjk = name.chars().count() == 1 && !name.is_ascii();
+			if let Some(cv) = parse_chapter_verse(text, after_name)
+				&& cv.chapter >= 1 && cv.chapter <= chapter_count(*num)
+				&& (cv.explicit || !single_cjk)
+			{
+				matched = Some((
+					i..cv.end,
+					ScriptureRef {
+						book_num: *num,
+						chapter: cv.chapter,
+						verse_start: cv.verse_start,
+						verse_end: cv.verse_end,
+					},
+				));
+				break;
+			}
+		}
+
+		match matched {
+			Some((range, r)) => {
+				i = range.end;
+				found.push((range, r));
+			}
+			None => {
+				i += rest.chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+			}
+		}
+	}
+
+	found
+}
+
+/// 解析一个完整的引用（如「约 3:16」「John 3:16」），文本中只取第一个
+pub fn parse_reference(text: &str) -> Option<ScriptureRef> {
+	find_references(text.trim()).into_iter().next().map(|(_, r)| r)
+}
+
+/// 整段文本就是一个引用（如读经计划中的「创 1」「太 5」），
+/// 与在正文中查找不同，单字简称不必带「:」或「章」
+pub fn parse_standalone_reference(text: &str) -> Option<ScriptureRef> {
+	let text = text.trim();
+	book_name_table().iter().find_map(|(name, num)| {
+		let rest = text.strip_prefix(name.as_str())?;
+		if name.chars().next_back().is_some_and(|c| c.is_ascii_alphabetic())
+			&& rest.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+		{
+			return None;
+		}
+		let cv = parse_chapter_verse(text, name.len())?;
+		(cv.chapter >= 1 && cv.chapter <= chapter_count(*num) && text[cv.end..].trim().is_empty()).then_some(ScriptureRef {
+			book_num: *num,
+			chapter: cv.chapter,
+			verse_start: cv.verse_start,
+			verse_end: cv.verse_end,
+		})
+	})
+}
+
+impl ScriptureRef {
+	/// 简写显示，例如「约 3:16-18」
+	pub fn display(&self) -> String {
+		let abbr = crate::utils::book_number_to_abbr(self.book_num);
+		match (self.verse_start, self.verse_end) {
+			(Some(s), Some(e)) if e != s => format!("{} {}:{}-{}", abbr, self.chapter, s, e),
+			(Some(s), _) => format!("{} {}:{}", abbr, self.chapter, s),
+			_ => format!("{} {}", abbr, self.chapter),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn r(book_num: i32, chapter: i32, verse_start: Option<i32>, verse_end: Option<i32>) -> ScriptureRef {
+		ScriptureRef { book_num, chapter, verse_start, verse_end }
+	}
+
+	#[test]
+	fn parses_chinese_and_english_references() {
+		assert_eq!(parse_reference("约 3:16"), Some(r(43, 3, Some(16), None)));
+		assert_eq!(parse_reference("约翰福音 3：16-18"), Some(r(43, 3, Some(16), Some(18))));
+		assert_eq!(parse_reference("罗马书8章28节"), Some(r(45, 8, Some(28), None)));
+		assert_eq!(parse_reference("John 3:16"), Some(r(43, 3, Some(16), None)));
+		assert_eq!(parse_reference("1 John 1:9"), Some(r(62, 1, Some(9), None)));
+		assert_eq!(parse_reference("Ps 23"), Some(r(19, 23, None, None)));
+	}
+
+	#[test]
+	fn longest_book_name_wins() {
+		assert_eq!(parse_reference("约一 1:9"), Some(r(62, 1, Some(9), None)));
+		assert_eq!(parse_reference("撒上 17:45"), Some(r(9, 17, Some(45), None)));
+	}
+
+	#[test]
+	fn rejects_chapters_out_of_range() {
+		assert_eq!(parse_reference("犹 2:1"), None);
+		assert_eq!(parse_reference("创世记 51"), None);
+		assert_eq!(parse_reference("创世记 0"), None);
+	}
+
+	#[test]
+	fn single_character_abbreviation_needs_verse_or_zhang() {
+		// 正文中「约 3」可能只是普通文字，单字简称需要带「:」或「章」
+		assert!(find_references("约 3 个人").is_empty());
+		assert_eq!(parse_reference("太 5:3"), Some(r(40, 5, Some(3), None)));
+		assert_eq!(parse_reference("太5章"), Some(r(40, 5, None, None)));
+		// 单独的引用不受此限制
+		assert_eq!(parse_standalone_reference("太 5"), Some(r(40, 5, None, None)));
+		assert_eq!(parse_standalone_reference("创 1"), Some(r(1, 1, None, None)));
+		assert_eq!(parse_standalone_reference("太 5 以后"), None);
+	}
+
+	#[test]
+	fn english_names_need_word_boundaries() {
+		assert!(find_references("remark 2").is_empty());
+		assert!(find_references("Markus 2").is_empty());
+	}
+
+	#[test]
+	fn finds_all_references_with_byte_ranges() {
+		let text = "参看 约 3:16 和 罗 8:28。";
+		let found = find_references(text);
+		assert_eq!(found.len(), 2);
+		assert_eq!(&text[found[0].0.clone()], "约 3:16");
+		assert_eq!(found[0].1, r(43, 3, Some(16), None));
+		assert_eq!(&text[found[1].0.clone()], "罗 8:28");
+		assert_eq!(found[1].1, r(45, 8, Some(28), None));
+	}
+
+	#[test]
+	fn display_uses_abbreviations() {
+		assert_eq!(r(43, 3, Some(16), Some(18)).display(), "约 3:16-18");
+		assert_eq!(r(43, 3, Some(16), Some(16)).display(), "约 3:16");
+		assert_eq!(r(19, 23, None, None).display(), "诗 23");
+	}
+}
@@ -0,0 +1,342 @@
+use rusqlite::Connection;
+use similar::{ChangeTag, TextDiff};
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::notes::{Notedb, notes_db_path, table_exists, save_note};
+use crate::tags::split_keywords;
+use crate::crypto::{encrypt_field, decrypt_field};
+use crate::utils::display_timestamp;
+
+/// 笔记的一个历史版本
+#[derive(Debug, Clone)]
+pub struct Revision {
+	pub rev_id: i64,
+	pub note_id: String,
+	pub title: Option<String>,
+	pub subject: Option<String>,
+	pub keywords: Option<String>,
+	pub reference: Option<String>,
+	pub body: Option<String>,
+	pub saved_at: String,
+}
+
+fn revisions_table(category: &str) -> String {
+	format!("{}_revisions", category)
+}
+
+/// 建立历史版本表
+pub fn ensure_revision_schema(conn: &Connection, category: &str) -> rusqlite::Result<()> {
+	conn.execute_batch(&format!(
+		"CREATE TABLE IF NOT EXISTS {table} (
+			rev_id INTEGER PRIMARY KEY AUTOINCREMENT,
+			note_id TEXT NOT NULL,
+			title TEXT,
+			subject TEXT,
+			keywords TEXT,
+			reference TEXT,
+			body TEXT,
+			saved_at TEXT
+		);
+		CREATE INDEX IF NOT EXISTS {table}_note ON {table} (note_id, rev_id);",
+		table = revisions_table(category)
+	))
+}
+
+/// 保存笔记后记录一个版本；与最近一个版本内容相同则跳过
+pub fn record_revision(conn: &Connection, category: &str, note: &Notedb, saved_at: &str) -> rusqlite::Result<()> {
+	let table = revisions_table(category);
+	let fields = (
+		note.title.clone().unwrap_or_default(),
+		note.subject.clone().unwrap_or_default(),
+		note.keywords.clone().unwrap_or_default(),
+		note.reference.clone().unwrap_or_default(),
+		note.body.clone().unwrap_or_default(),
+	);
+
+	let latest: Option<(String, String, String, String, String)> = conn.query_row(
+		&format!(
+			"SELECT COALESCE(title, ''), COALESCE(subject, ''), COALESCE(keywords, ''),
+				COALESCE(reference, ''), COALESCE(body, '')
+			 FROM {} WHERE note_id = ?1 ORDER BY rev_id DESC LIMIT 1",
+			table
+		),
+		[&note.id],
+		|row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, decrypt_field(&row.get::<_, String>(4)?))),
+	).ok();
+
+	if latest.as_ref() == Some(&fields) {
+		return Ok(());
+	}
+
+	let body = encrypt_field(&fields.4).map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+	conn.execute(
+		&format!(
+			"INSERT INTO {} (note_id, title, subject, keywords, reference, body, saved_at)
+			 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+			table
+		),
+		rusqlite::params![note.id, fields.0, fields.1, fields.2, fields.3, body, saved_at],
+	)?;
+	Ok(())
+}
+
+/// 删除笔记的全部历史版本（彻底删除笔记时）
+pub fn remove_revisions(conn: &Connection, category: &str, note_id: &str) -> rusqlite::Result<()> {
+	if !table_exists(conn, &revisions_table(category)) {
+		return Ok(());
+	}
+	conn.execute(&format!("DELETE FROM {} WHERE note_id = ?1", revisions_table(category)), [note_id])?;
+	Ok(())
+}
+
+/// 读取笔记的历史版本（新的在前）
+pub fn load_revisions(category: &str, note_id: &str) -> Vec<Revision> {
+	let Some(db_path) = notes_db_path() else { return Vec::new() };
+	let conn = match Connection::open(&db_path) {
+		Ok(c) => c,
+		Err(_) => return Vec::new(),
+	};
+	if !table_exists(&conn, &revisions_table(category)) {
+		return Vec::new();
+	}
+
+	let sql = format!(
+		"SELECT rev_id, note_id, title, subject, keywords, reference, body, saved_at
+		 FROM {} WHERE note_id = ?1 ORDER BY rev_id DESC",
+		revisions_table(category)
+	);
+	let mut stmt = match conn.prepare(&sql) {
+		Ok(s) => s,
+		Err(_) => return Vec::new(),
+	};
+	let rows = stmt.query_map([note_id], |row| {
+		Ok(Revision {
+			rev_id: row.get(0)?,
+			note_id: row.get(1)?,
+			title: row.get(2)?,
+			subject: row.get(3)?,
+			keywords: row.get(4)?,
+			reference: row.get(5)?,
+			body: row.get::<_, Option<String>>(6)?.map(|b| decrypt_field(&b)),
+			saved_at: row.get::<_, Option<String>>(7)?.unwrap_or_default(),
+		})
+	});
+	match rows {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
+/// 逐行比较两段文本，删除的行标红、新增的行标绿
+fn draw_text_diff(ui: &mut egui::Ui, colors: &ThemeColors, old: &str, new: &str) {
+	let diff = TextDiff::from_lines(old, new);
+	let font_id = egui::TextStyle::Body.resolve(ui.style());
+	let mut job = egui::text::LayoutJob::default();
+
+	for change in diff.iter_all_changes() {
+		let (prefix, color, background) = match change.tag() {
+			ChangeTag::Delete => ("- ", egui::Color32::from_rgb(180, 30, 30), egui::Color32::from_rgba_unmultiplied(255, 0, 0, 30)),
+			ChangeTag::Insert => ("+ ", egui::Color32::from_rgb(30, 130, 30), egui::Color32::from_rgba_unmultiplied(0, 255, 0, 30)),
+			ChangeTag::Equal => ("  ", colors.text_color, egui::Color32::TRANSPARENT),
+		};
+		let mut line = format!("{}{}", prefix, change.value());
+		if !line.ends_with('\n') {
+			line.push('\n');
+		}
+		job.append(
+			&line,
+			0.0,
+			egui::TextFormat {
+				font_id: font_id.clone(),
+				color,
+				background,
+				..Default::default()
+			},
+		);
+	}
+
+	ui.label(job);
+}
+
+fn field_changed(ui: &mut egui::Ui, colors: &ThemeColors, name: &str, old: Option<&str>, new: Option<&str>) {
+	let old = old.unwrap_or("");
+	let new = new.unwrap_or("");
+	if old != new {
+		ui.label(
+			egui::RichText::new(format!("{}：「{}」→「{}」", name, old, new))
+			.size(12.0)
+			.color(colors.comment_text_color),
+		);
+	}
+}
+
+//历史版本窗口
+impl BibleApp {
+	/// 打开某条笔记的历史版本窗口
+	pub fn open_note_history(&mut self, note: &Notedb) {
+		self.history_revisions = load_revisions("notes", &note.id);
+		self.history_selected = if self.history_revisions.is_empty() { None } else { Some(0) };
+		self.history_note = Some(note.clone());
+		self.history_error = None;
+	}
+
+	pub fn show_note_history_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(note) = self.history_note.clone() else { return };
+
+		let mut open = true;
+		let mut restore: Option<Revision> = None;
+
+		egui::Window::new(egui::RichText::new(format!(
+			"🕘 历史版本：{}",
+			note.title.as_deref().unwrap_or("<无标题>")
+		)).size(14.0))
+			.open(&mut open)
+			.resizable(true)
+			.default_size([700.0, 500.0])
+			.show(ctx, |ui| {
+				if self.history_revisions.is_empty() {
+					ui.label("暂无历史版本（保存笔记后会自动记录）");
+					return;
+				}
+
+				egui::SidePanel::left("note_history_list")
+					.resizable(true)
+					.default_width(170.0)
+					.show_inside(ui, |ui| {
+						egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+							for (i, rev) in self.history_revisions.iter().enumerate() {
+								let label = if i == 0 {
+									format!("{}（最新）", display_timestamp(&rev.saved_at))
+								} else {
+									display_timestamp(&rev.saved_at)
+								};
+								if ui.selectable_label(self.history_selected == Some(i), label).clicked() {
+									self.history_selected = Some(i);
+								}
+							}
+						});
+					});
+
+				let Some(idx) = self.history_selected else { return };
+				let rev = &self.history_revisions[idx];
+
+				if let Some(err) = &self.history_error {
+					ui.label(egui::RichText::new(format!("⚠ 恢复失败：{}", err)).color(egui::Color32::from_rgb(180, 30, 30)));
+				}
+
+				ui.horizontal(|ui| {
+					ui.label(egui::RichText::new("与当前版本比较").strong());
+					ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+						if ui.button("↩ 恢复此版本").clicked() {
+							restore = Some(rev.clone());
+						}
+					});
+				});
+				ui.separator();
+
+				egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+					field_changed(ui, colors, "标题", rev.title.as_deref(), note.title.as_deref());
+					field_changed(ui, colors, "主题", rev.subject.as_deref(), note.subject.as_deref());
+					field_changed(ui, colors, "关键词", rev.keywords.as_deref(), note.keywords.as_deref());
+					field_changed(ui, colors, "引用经文", rev.reference.as_deref(), note.reference.as_deref());
+					draw_text_diff(
+						ui,
+						colors,
+						rev.body.as_deref().unwrap_or(""),
+						note.body.as_deref().unwrap_or(""),
+					);
+				});
+			});
+
+		if let Some(rev) = restore {
+			let mut restored = note.clone();
+			restored.title = rev.title;
+			restored.subject = rev.subject;
+			restored.tags = split_keywords(rev.keywords.as_deref().unwrap_or(""));
+			restored.keywords = rev.keywords;
+			restored.reference = rev.reference;
+			restored.body = rev.body;
+			match save_note("notes", &restored) {
+				Ok(()) => {
+					println!("已恢复笔记 id={} 到版本 {}", rev.note_id, rev.rev_id);
+					// 刷新显示
+					self.last_appended_notes_chapter = None;
+					if self.current_note.as_ref().is_some_and(|n| n.id == restored.id) {
+						self.current_note = Some(restored.clone());
+					}
+					self.open_note_history(&restored);
+					if self.show_notes_list_window {
+						self.reload_notes_list();
+					}
+				}
+				Err(e) => {
+					eprintln!("恢复笔记历史版本失败 id={}: {}", rev.note_id, e);
+					self.history_error = Some(e);
+				}
+			}
+		}
+
+		if !open {
+			self.history_note = None;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::notes::{self, write_note};
+
+	fn save(body: &str) {
+		let note = Notedb {
+			id: "n".to_string(),
+			verse_start: -1,
+			title: Some("讲章".to_string()),
+			body: Some(body.to_string()),
+			..Default::default()
+		};
+		write_note("notes", &note, "2024-05-01T10:00:00+00:00").unwrap();
+	}
+
+	fn bodies() -> Vec<String> {
+		load_revisions("notes", "n").into_iter().map(|r| r.body.unwrap_or_default()).collect()
+	}
+
+	#[test]
+	fn records_only_changed_versions_newest_first() {
+		let _encryption = crate::crypto::test_encryption(None);
+		let db = notes::test_db();
+		let conn = db.open("notes");
+		save("初稿");
+		save("初稿");
+		save("二稿");
+		assert_eq!(bodies(), vec!["二稿", "初稿"]);
+
+		remove_revisions(&conn, "notes", "n").unwrap();
+		assert!(bodies().is_empty());
+	}
+
+	#[test]
+	fn stores_encrypted_bodies() {
+		let _encryption = crate::crypto::test_encryption(Some("密码"));
+		let db = notes::test_db();
+		let conn = db.open("notes");
+		save("不公开的正文");
+
+		let stored: String = conn.query_row("SELECT body FROM notes_revisions", [], |row| row.get(0)).unwrap();
+		assert!(stored.starts_with(crate::crypto::ENCRYPTED_PREFIX));
+		assert_eq!(bodies(), vec!["不公开的正文"]);
+	}
+
+	#[test]
+	fn deleted_notes_can_be_restored() {
+		let _encryption = crate::crypto::test_encryption(None);
+		let db = notes::test_db();
+		let conn = db.open("notes");
+		save("正文");
+		notes::delete_note("notes", "n").unwrap();
+		assert!(notes::load_note(&conn, "notes", "n").unwrap().unwrap().deleted_at.is_some());
+		notes::restore_note("notes", "n").unwrap();
+		assert!(notes::load_note(&conn, "notes", "n").unwrap().unwrap().deleted_at.is_none());
+	}
+}
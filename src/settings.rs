@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use crate::tabs::ReaderTab;
+
+/// 保存在 settings.json 中的用户设置
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+	/// 本机标识，同步时用作变更日志的文件名
+	pub device_id: String,
+	/// 同步文件夹（例如 Syncthing / Nextcloud 目录），为空表示不同步
+	pub sync_folder: Option<String>,
+	/// 每保存多少次笔记自动备份一次（0 表示只在启动时备份）
+	pub backup_every_saves: u32,
+	/// 保留最近几天的每日备份
+	pub backup_keep_daily: usize,
+	/// 另外保留最近几周的每周备份
+	pub backup_keep_weekly: usize,
+	/// 正在进行的读经计划
+	pub active_reading_plan: Option<String>,
+	/// 上次退出时打开的阅读标签页
+	pub open_tabs: Vec<ReaderTab>,
+	pub active_tab: usize,
+	/// 改过的快捷键：操作名 → 按键写法（如 "Ctrl+Shift+Tab"），空字符串表示不使用
+	pub shortcuts: BTreeMap<String, String>,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
+			device_id: String::new(),
+			sync_folder: None,
+			backup_every_saves: 20,
+			backup_keep_daily: 7,
+			backup_keep_weekly: 4,
+			active_reading_plan: None,
+			open_tabs: Vec::new(),
+			active_tab: 0,
+			shortcuts: BTreeMap::new(),
+		}
+	}
+}
+
+fn settings_path() -> Option<PathBuf> {
+	let dir = dirs::data_dir()?.join("bible_reader");
+	if let Err(e) = fs::create_dir_all(&dir) {
+		eprintln!("无法创建设置目录 {:?}: {:?}", dir, e);
+		return None;
+	}
+	Some(dir.join("settings.json"))
+}
+
+/// 读取设置；首次运行时生成本机标识并写回
+pub fn load_settings() -> Settings {
+	let mut settings: Settings = settings_path()
+		.and_then(|p| fs::read_to_string(p).ok())
+		.and_then(|text| match serde_json::from_str(&text) {
+			Ok(s) => Some(s),
+			Err(e) => {
+				eprintln!("设置文件格式不正确，使用默认设置: {:?}", e);
+				None
+			}
+		})
+		.unwrap_or_default();
+
+	if settings.device_id.is_empty() {
+		settings.device_id = Uuid::new_v4().to_string();
+		save_settings(&settings);
+	}
+	settings
+}
+
+pub fn save_settings(settings: &Settings) {
+	let Some(path) = settings_path() else { return };
+	match serde_json::to_string_pretty(settings) {
+		Ok(json) => {
+			if let Err(e) = fs::write(&path, json) {
+				eprintln!("保存设置失败 {:?}: {:?}", path, e);
+			}
+		}
+		Err(e) => eprintln!("保存设置失败: {:?}", e),
+	}
+}
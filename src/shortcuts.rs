@@ -0,0 +1,572 @@
+use std::collections::BTreeMap;
+use crate::theme::{Theme, ThemeColors};
+use crate::BibleApp;
+use crate::reference::parse_reference;
+use crate::settings::save_settings;
+use crate::utils::{load_chapters, chapter_number};
+
+/// 可以设置快捷键的操作
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+	Back,
+	Forward,
+	NextChapter,
+	PrevChapter,
+	NextBook,
+	PrevBook,
+	FocusSearch,
+	GoToReference,
+	ToggleBookmark,
+	ToggleNotes,
+	ToggleTheme,
+	CopyChapter,
+	NewTab,
+	CloseTab,
+	NextTab,
+	PrevTab,
+	ShowShortcuts,
+}
+
+impl ShortcutAction {
+	pub const ALL: [ShortcutAction; 17] = [
+		Self::Back,
+		Self::Forward,
+		Self::NextChapter,
+		Self::PrevChapter,
+		Self::NextBook,
+		Self::PrevBook,
+		Self::FocusSearch,
+		Self::GoToReference,
+		Self::ToggleBookmark,
+		Self::ToggleNotes,
+		Self::ToggleTheme,
+		Self::CopyChapter,
+		Self::NewTab,
+		Self::CloseTab,
+		Self::NextTab,
+		Self::PrevTab,
+		Self::ShowShortcuts,
+	];
+
+	/// 保存在 settings.json 中的名字
+	pub fn id(self) -> &'static str {
+		match self {
+			Self::Back => "back",
+			Self::Forward => "forward",
+			Self::NextChapter => "next_chapter",
+			Self::PrevChapter => "prev_chapter",
+			Self::NextBook => "next_book",
+			Self::PrevBook => "prev_book",
+			Self::FocusSearch => "focus_search",
+			Self::GoToReference => "go_to_reference",
+			Self::ToggleBookmark => "toggle_bookmark",
+			Self::ToggleNotes => "toggle_notes",
+			Self::ToggleTheme => "toggle_theme",
+			Self::CopyChapter => "copy_chapter",
+			Self::NewTab => "new_tab",
+			Self::CloseTab => "close_tab",
+			Self::NextTab => "next_tab",
+			Self::PrevTab => "prev_tab",
+			Self::ShowShortcuts => "show_shortcuts",
+		}
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Back => "后退",
+			Self::Forward => "前进",
+			Self::NextChapter => "下一章",
+			Self::PrevChapter => "上一章",
+			Self::NextBook => "下一卷",
+			Self::PrevBook => "上一卷",
+			Self::FocusSearch => "搜索经文",
+			Self::GoToReference => "转到经文",
+			Self::ToggleBookmark => "添加 / 取消书签",
+			Self::ToggleNotes => "显示 / 隐藏笔记",
+			Self::ToggleTheme => "切换主题",
+			Self::CopyChapter => "复制整章",
+			Self::NewTab => "新标签页",
+			Self::CloseTab => "关闭标签页",
+			Self::NextTab => "下一个标签页",
+			Self::PrevTab => "上一个标签页",
+			Self::ShowShortcuts => "快捷键一览",
+		}
+	}
+
+	pub fn default_binding(self) -> &'static str {
+		match self {
+			Self::Back => "Ctrl+O",
+			Self::Forward => "Ctrl+I",
+			Self::NextChapter => "Ctrl+Right",
+			Self::PrevChapter => "Ctrl+Left",
+			Self::NextBook => "Ctrl+Down",
+			Self::PrevBook => "Ctrl+Up",
+			Self::FocusSearch => "Ctrl+F",
+			Self::GoToReference => "Ctrl+L",
+			Self::ToggleBookmark => "Ctrl+B",
+			Self::ToggleNotes => "Ctrl+Shift+N",
+			Self::ToggleTheme => "Ctrl+Shift+L",
+			Self::CopyChapter => "Ctrl+Shift+A",
+			Self::NewTab => "Ctrl+T",
+			Self::CloseTab => "Ctrl+W",
+			Self::NextTab => "Ctrl+Tab",
+			Self::PrevTab => "Ctrl+Shift+Tab",
+			Self::ShowShortcuts => "F1",
+		}
+	}
+}
+
+/// 一个按键组合，例如 Ctrl+Shift+Tab
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+	pub ctrl: bool,
+	pub shift: bool,
+	pub alt: bool,
+	pub key: egui::Key,
+}
+
+impl KeyBinding {
+	/// 解析「Ctrl+Shift+Tab」「Alt+Right」「F1」这样的写法，不区分大小写
+	pub fn parse(text: &str) -> Result<Self, String> {
+		let mut binding = KeyBinding { ctrl: false, shift: false, alt: false, key: egui::Key::Escape };
+		let mut key = None;
+		for part in text.split('+').map(str::trim) {
+			match part.to_ascii_lowercase().as_str() {
+				"ctrl" | "cmd" | "command" => binding.ctrl = true,
+				"shift" => binding.shift = true,
+				"alt" | "option" => binding.alt = true,
+				"" => return Err(format!("「{}」格式不正确", text)),
+				_ if key.is_some() => return Err(format!("「{}」中有多个按键", text)),
+				_ => key = Some(parse_key(part).ok_or_else(|| format!("无法识别的按键「{}」", part))?),
+			}
+		}
+		binding.key = key.ok_or_else(|| format!("「{}」中没有按键", text))?;
+		// Ctrl+C / X / V 由窗口系统转成复制、剪切、粘贴，收不到按键
+		if binding.ctrl && matches!(binding.key, egui::Key::C | egui::Key::X | egui::Key::V) {
+			return Err(format!("{} 是系统的复制 / 剪切 / 粘贴，不能用作快捷键", binding.display()));
+		}
+		Ok(binding)
+	}
+
+	pub fn display(&self) -> String {
+		let mut text = String::new();
+		if self.ctrl {
+			text.push_str("Ctrl+");
+		}
+		if self.shift {
+			text.push_str("Shift+");
+		}
+		if self.alt {
+			text.push_str("Alt+");
+		}
+		text.push_str(self.key.name());
+		text
+	}
+
+	pub fn matches(&self, key: egui::Key, modifiers: &egui::Modifiers) -> bool {
+		self.key == key
+			&& self.ctrl == (modifiers.ctrl || modifiers.command)
+			&& self.shift == modifiers.shift
+			&& self.alt == modifiers.alt
+	}
+}
+
+fn parse_key(name: &str) -> Option<egui::Key> {
+	egui::Key::from_name(name).or_else(|| {
+		// 字母和功能键按大写识别，其余按首字母大写（right → Right）
+		let upper = name.to_ascii_uppercase();
+		let mut chars = name.chars();
+		let capitalized: String = chars.next().map(|c| c.to_ascii_uppercase()).into_iter().chain(chars).collect();
+		egui::Key::from_name(&upper).or_else(|| egui::Key::from_name(&capitalized))
+	})
+}
+
+/// 某操作当前的按键写法：设置中改过的，否则为默认
+pub fn binding_text(overrides: &BTreeMap<String, String>, action: ShortcutAction) -> String {
+	overrides.get(action.id()).cloned().unwrap_or_else(|| action.default_binding().to_string())
+}
+
+/// 全部快捷键；设置中写错的按默认处理，留空的表示不使用
+pub fn load_bindings(overrides: &BTreeMap<String, String>) -> Vec<(ShortcutAction, KeyBinding)> {
+	ShortcutAction::ALL.iter().filter_map(|&action| {
+		let text = binding_text(overrides, action);
+		if text.trim().is_empty() {
+			return None;
+		}
+		match KeyBinding::parse(&text) {
+			Ok(binding) => Some((action, binding)),
+			Err(e) => {
+				eprintln!("快捷键设置有误（{}），使用默认值: {}", action.label(), e);
+				KeyBinding::parse(action.default_binding()).ok().map(|b| (action, b))
+			}
+		}
+	}).collect()
+}
+
+/// 检查一组按键写法：返回设置中要保存的改动（与默认相同的不保存）
+fn validate_bindings(inputs: &[String]) -> Result<BTreeMap<String, String>, String> {
+	let mut overrides = BTreeMap::new();
+	let mut used: Vec<(KeyBinding, ShortcutAction)> = Vec::new();
+	for (&action, input) in ShortcutAction::ALL.iter().zip(inputs) {
+		let input = input.trim();
+		if input.is_empty() {
+			overrides.insert(action.id().to_string(), String::new());
+			continue;
+		}
+		let binding = KeyBinding::parse(input).map_err(|e| format!("{}：{}", action.label(), e))?;
+		if let Some((_, other)) = used.iter().find(|(b, _)| *b == binding) {
+			return Err(format!("{} 同时用于「{}」和「{}」", binding.display(), other.label(), action.label()));
+		}
+		used.push((binding, action));
+		let text = binding.display();
+		if KeyBinding::parse(action.default_binding()).ok() != Some(binding) {
+			overrides.insert(action.id().to_string(), text);
+		}
+	}
+	Ok(overrides)
+}
+
+/// 快捷键一览（可修改）窗口的状态
+pub struct ShortcutsDialog {
+	/// 与 ShortcutAction::ALL 一一对应的按键写法
+	inputs: Vec<String>,
+	status: Option<String>,
+}
+
+impl ShortcutsDialog {
+	pub fn new(overrides: &BTreeMap<String, String>) -> Self {
+		Self {
+			inputs: ShortcutAction::ALL.iter().map(|&a| binding_text(overrides, a)).collect(),
+			status: None,
+		}
+	}
+}
+
+/// 转到经文（Ctrl+L）窗口的状态
+#[derive(Default)]
+pub struct GoToDialog {
+	input: String,
+	error: Option<String>,
+	focused: bool,
+}
+
+/// 是否有文本框正在输入（此时不响应快捷键）
+fn text_edit_focused(ctx: &egui::Context) -> bool {
+	let focused = ctx.memory(|m| m.focused());
+	focused.is_some_and(|id| egui::TextEdit::load_state(ctx, id).is_some())
+}
+
+//快捷键
+impl BibleApp {
+	/// 每帧检测快捷键与鼠标侧键
+	pub fn check_shortcuts(&mut self, ctx: &egui::Context) {
+		let typing = text_edit_focused(ctx);
+		let mut actions = Vec::new();
+		// 遍历当前帧所有键事件
+		for event in &ctx.input(|i| i.events.clone()) {
+			match event {
+				egui::Event::Key { key, pressed: true, modifiers, .. } if !typing => {
+					if let Some((action, _)) = self.shortcut_bindings.iter().find(|(_, b)| b.matches(*key, modifiers)) {
+						actions.push(*action);
+					}
+				}
+				// 鼠标侧键：后退 / 前进
+				egui::Event::PointerButton { button: egui::PointerButton::Extra1, pressed: true, .. } => actions.push(ShortcutAction::Back),
+				egui::Event::PointerButton { button: egui::PointerButton::Extra2, pressed: true, .. } => actions.push(ShortcutAction::Forward),
+				_ => {}
+			}
+		}
+		for action in actions {
+			self.run_shortcut(ctx, action);
+		}
+	}
+
+	fn run_shortcut(&mut self, ctx: &egui::Context, action: ShortcutAction) {
+		match action {
+			ShortcutAction::Back => self.jump_back(),
+			ShortcutAction::Forward => self.jump_forward(),
+			ShortcutAction::NextChapter => self.step_chapter(1),
+			ShortcutAction::PrevChapter => self.step_chapter(-1),
+			ShortcutAction::NextBook => self.step_book(1),
+			ShortcutAction::PrevBook => self.step_book(-1),
+			ShortcutAction::FocusSearch => {
+				self.focus_search = true;
+				ctx.request_repaint();
+			}
+			ShortcutAction::GoToReference => {
+				self.goto_dialog = Some(GoToDialog::default());
+			}
+			ShortcutAction::ToggleBookmark => self.toggle_current_bookmark(),
+			ShortcutAction::ToggleNotes => {
+				self.show_notes = !self.show_notes;
+				if self.show_notes {
+					self.show_highlight = false;
+				}
+			}
+			ShortcutAction::ToggleTheme => {
+				self.theme = match self.theme {
+					Theme::Dark => Theme::Light,
+					Theme::Light => Theme::Dark,
+				};
+			}
+			ShortcutAction::CopyChapter => ctx.copy_text(self.content.clone()),
+			ShortcutAction::NewTab => self.new_tab(),
+			ShortcutAction::CloseTab => self.close_tab(self.active_tab),
+			ShortcutAction::NextTab => self.cycle_tab(1),
+			ShortcutAction::PrevTab => self.cycle_tab(-1),
+			ShortcutAction::ShowShortcuts => {
+				self.shortcuts_dialog = match self.shortcuts_dialog {
+					Some(_) => None,
+					None => Some(ShortcutsDialog::new(&self.settings.shortcuts)),
+				};
+			}
+		}
+	}
+
+	/// 按钮提示中的快捷键，例如「 (Ctrl+O)」；没有设置时为空
+	pub fn shortcut_hint(&self, action: ShortcutAction) -> String {
+		self.shortcut_bindings.iter()
+			.find(|(a, _)| *a == action)
+			.map(|(_, b)| format!(" ({})", b.display()))
+			.unwrap_or_default()
+	}
+
+	/// 上一章 / 下一章；到了书卷的头尾时接着上一卷的末章或下一卷的首章
+	fn step_chapter(&mut self, delta: isize) {
+		let (Some(book), Some(chap)) = (self.current_book, self.current_chapter.clone()) else { return };
+		let Some(index) = self.chapters.iter().position(|c| *c == chap) else { return };
+		let next = index as isize + delta;
+		if next >= 0 && (next as usize) < self.chapters.len() {
+			let ch = self.chapters[next as usize].clone();
+			self.on_chapter_selected(book, ch);
+			return;
+		}
+		let Some(other) = self.adjacent_book(book, delta) else { return };
+		if delta > 0 {
+			self.on_book_selected(other);
+		} else {
+			let mut chapters = load_chapters(&self.bible_root.join(&self.current_version), other);
+			chapters.sort_by_key(|c| chapter_number(c));
+			let Some(last) = chapters.last().cloned() else { return };
+			self.chapters = chapters;
+			self.on_chapter_selected(other, last);
+		}
+	}
+
+	/// 上一卷 / 下一卷，从第一章开始
+	fn step_book(&mut self, delta: isize) {
+		let Some(book) = self.current_book else { return };
+		if let Some(other) = self.adjacent_book(book, delta) {
+			self.on_book_selected(other);
+		}
+	}
+
+	/// 当前译本中前后相邻的书卷
+	fn adjacent_book(&self, book: i32, delta: isize) -> Option<i32> {
+		let index = self.books.iter().position(|(num, _)| *num == book)? as isize + delta;
+		if index < 0 {
+			return None;
+		}
+		self.books.get(index as usize).map(|(num, _)| *num)
+	}
+
+	/// 转到经文（Ctrl+L）：输入「约 3:16」「罗马书 8」等，回车跳转
+	pub fn show_goto_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(mut dialog) = self.goto_dialog.take() else { return };
+
+		let mut open = true;
+		let mut go = false;
+		let mut cancel = false;
+
+		egui::Window::new(egui::RichText::new("转到经文").size(14.0))
+			.open(&mut open)
+			.collapsible(false)
+			.resizable(false)
+			.anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+			.show(ctx, |ui| {
+				let input = ui.add(
+					egui::TextEdit::singleline(&mut dialog.input)
+						.hint_text("例如：约 3:16、John 3、罗马书8章")
+						.desired_width(260.0)
+				);
+				if !dialog.focused {
+					input.request_focus();
+					dialog.focused = true;
+				}
+				if input.lost_focus() {
+					if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+						go = true;
+					} else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+						cancel = true;
+					}
+				}
+				if input.changed() {
+					dialog.error = None;
+				}
+				if let Some(error) = &dialog.error {
+					ui.label(egui::RichText::new(error).size(12.0).color(colors.comment_text_color));
+				}
+				ui.horizontal(|ui| {
+					if ui.button("转到").clicked() {
+						go = true;
+					}
+					if ui.button("取消").clicked() {
+						cancel = true;
+					}
+				});
+			});
+
+		if go {
+			match parse_reference(dialog.input.trim()) {
+				Some(r) => {
+					self.go_to_reference(&r);
+					return;
+				}
+				None => {
+					dialog.error = Some(format!("无法识别的经文：{}", dialog.input.trim()));
+					dialog.focused = false;
+				}
+			}
+		}
+
+		if open && !cancel {
+			self.goto_dialog = Some(dialog);
+		}
+	}
+
+	/// 快捷键一览：列出全部快捷键，可直接修改
+	pub fn show_shortcuts_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(mut dialog) = self.shortcuts_dialog.take() else { return };
+
+		let mut open = true;
+		let mut save = false;
+
+		egui::Window::new(egui::RichText::new("⌨ 快捷键").size(14.0))
+			.open(&mut open)
+			.resizable(true)
+			.default_size([380.0, 520.0])
+			.show(ctx, |ui| {
+				ui.label(egui::RichText::new("写法如 Ctrl+Shift+Tab、Alt+Right、F2，留空表示不使用；在文本框中输入时快捷键不起作用")
+					.size(12.0).color(colors.comment_text_color));
+				ui.separator();
+				egui::ScrollArea::vertical().auto_shrink([false, true]).max_height(400.0).show(ui, |ui| {
+					egui::Grid::new("shortcuts_grid").num_columns(3).striped(true).show(ui, |ui| {
+						for (action, input) in ShortcutAction::ALL.iter().zip(dialog.inputs.iter_mut()) {
+							ui.label(egui::RichText::new(action.label()).color(colors.text_color));
+							ui.add(egui::TextEdit::singleline(input).desired_width(140.0));
+							ui.label(egui::RichText::new(format!("默认 {}", action.default_binding()))
+								.size(12.0).color(colors.comment_text_color));
+							ui.end_row();
+						}
+						ui.label(egui::RichText::new("后退 / 前进").color(colors.text_color));
+						ui.label(egui::RichText::new("鼠标侧键").color(colors.comment_text_color));
+						ui.end_row();
+					});
+				});
+				ui.separator();
+				ui.horizontal(|ui| {
+					if ui.button("保存").clicked() {
+						save = true;
+					}
+					if ui.button("恢复默认").clicked() {
+						dialog.inputs = ShortcutAction::ALL.iter().map(|a| a.default_binding().to_string()).collect();
+						dialog.status = Some("已填入默认快捷键，点“保存”生效".to_string());
+					}
+				});
+				if let Some(status) = &dialog.status {
+					ui.label(egui::RichText::new(status).size(12.0).color(colors.comment_text_color));
+				}
+			});
+
+		if save {
+			match validate_bindings(&dialog.inputs) {
+				Ok(overrides) => {
+					self.settings.shortcuts = overrides;
+					save_settings(&self.settings);
+					self.shortcut_bindings = load_bindings(&self.settings.shortcuts);
+					dialog.inputs = ShortcutAction::ALL.iter().map(|&a| binding_text(&self.settings.shortcuts, a)).collect();
+					dialog.status = Some("快捷键已保存".to_string());
+					println!("快捷键已保存");
+				}
+				Err(e) => dialog.status = Some(e),
+			}
+		}
+
+		if open {
+			self.shortcuts_dialog = Some(dialog);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn defaults() -> Vec<String> {
+		ShortcutAction::ALL.iter().map(|a| a.default_binding().to_string()).collect()
+	}
+
+	fn index(action: ShortcutAction) -> usize {
+		ShortcutAction::ALL.iter().position(|&a| a == action).unwrap()
+	}
+
+	#[test]
+	fn default_bindings_are_valid_and_distinct() {
+		assert_eq!(validate_bindings(&defaults()), Ok(BTreeMap::new()));
+		let mut ids: Vec<&str> = ShortcutAction::ALL.iter().map(|a| a.id()).collect();
+		ids.sort();
+		ids.dedup();
+		assert_eq!(ids.len(), ShortcutAction::ALL.len());
+	}
+
+	#[test]
+	fn parses_key_bindings() {
+		let binding = KeyBinding::parse("ctrl + shift + tab").unwrap();
+		assert_eq!(binding.display(), "Ctrl+Shift+Tab");
+		assert_eq!(KeyBinding::parse("alt+right").unwrap().display(), "Alt+Right");
+		assert_eq!(KeyBinding::parse("f1").unwrap().display(), "F1");
+		assert_eq!(KeyBinding::parse("Cmd+l").unwrap().display(), "Ctrl+L");
+		for bad in ["Ctrl+", "Ctrl", "Ctrl+A+B", "Ctrl+Foo", "Ctrl+C", "Ctrl+V"] {
+			assert!(KeyBinding::parse(bad).is_err(), "{}", bad);
+		}
+	}
+
+	#[test]
+	fn matches_ctrl_or_command() {
+		let binding = KeyBinding::parse("Ctrl+T").unwrap();
+		assert!(binding.matches(egui::Key::T, &egui::Modifiers::CTRL));
+		assert!(binding.matches(egui::Key::T, &egui::Modifiers::COMMAND));
+		assert!(!binding.matches(egui::Key::T, &(egui::Modifiers::CTRL | egui::Modifiers::SHIFT)));
+		assert!(!binding.matches(egui::Key::W, &egui::Modifiers::CTRL));
+	}
+
+	#[test]
+	fn loads_overrides_with_fallbacks() {
+		let overrides = BTreeMap::from([
+			("new_tab".to_string(), "Alt+N".to_string()),
+			("close_tab".to_string(), "Ctrl+Nope".to_string()),
+			("show_shortcuts".to_string(), String::new()),
+		]);
+		let bindings = load_bindings(&overrides);
+		let text = |action| bindings.iter().find(|(a, _)| *a == action).map(|(_, b)| b.display());
+		assert_eq!(text(ShortcutAction::NewTab).as_deref(), Some("Alt+N"));
+		assert_eq!(text(ShortcutAction::CloseTab).as_deref(), Some("Ctrl+W"));
+		assert_eq!(text(ShortcutAction::ShowShortcuts), None);
+		assert_eq!(bindings.len(), ShortcutAction::ALL.len() - 1);
+	}
+
+	#[test]
+	fn saves_only_changed_bindings_and_rejects_conflicts() {
+		let mut inputs = defaults();
+		inputs[index(ShortcutAction::NewTab)] = "alt+n".to_string();
+		inputs[index(ShortcutAction::ShowShortcuts)] = " ".to_string();
+		assert_eq!(validate_bindings(&inputs), Ok(BTreeMap::from([
+			("new_tab".to_string(), "Alt+N".to_string()),
+			("show_shortcuts".to_string(), String::new()),
+		])));
+
+		inputs[index(ShortcutAction::CloseTab)] = "Alt+N".to_string();
+		let err = validate_bindings(&inputs).err().unwrap();
+		assert!(err.contains("Alt+N"), "{}", err);
+	}
+}
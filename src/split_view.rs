@@ -0,0 +1,289 @@
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::reference::book_full_name;
+use crate::utils::{load_books, load_chapters, load_chapter_content, chapter_number, chapter_display_name, version_display_name};
+
+/// 平行经文对照表（按章）：撒母耳记、列王纪与历代志，符类福音等。
+/// 每组中的章记载同一段事件；联动模式下副窗格跟随主窗格跳到对应的章
+const PARALLEL_PASSAGES: &[&[(i32, i32)]] = &[
+	// 撒母耳记 / 列王纪 / 历代志
+	&[(9, 31), (13, 10)],
+	&[(10, 5), (13, 11)],
+	&[(10, 6), (13, 13)],
+	&[(10, 7), (13, 17)],
+	&[(10, 8), (13, 18)],
+	&[(10, 10), (13, 19)],
+	&[(10, 12), (13, 20)],
+	&[(10, 22), (19, 18)],
+	&[(10, 24), (13, 21)],
+	&[(11, 3), (14, 1)],
+	&[(11, 5), (14, 2)],
+	&[(11, 6), (14, 3)],
+	&[(11, 7), (14, 4)],
+	&[(11, 8), (14, 6)],
+	&[(11, 9), (14, 7)],
+	&[(11, 10), (14, 9)],
+	&[(11, 12), (14, 10)],
+	&[(11, 14), (14, 12)],
+	&[(11, 15), (14, 13)],
+	&[(11, 22), (14, 18)],
+	&[(12, 8), (14, 21)],
+	&[(12, 11), (14, 23)],
+	&[(12, 12), (14, 24)],
+	&[(12, 14), (14, 25)],
+	&[(12, 15), (14, 26)],
+	&[(12, 16), (14, 28)],
+	&[(12, 18), (14, 32), (23, 36)],
+	&[(12, 19), (23, 37)],
+	&[(12, 20), (23, 38)],
+	&[(12, 21), (14, 33)],
+	&[(12, 22), (14, 34)],
+	&[(12, 23), (14, 35)],
+	&[(12, 24), (14, 36)],
+	&[(12, 25), (24, 52)],
+	// 诗篇中的重复
+	&[(19, 14), (19, 53)],
+	&[(19, 105), (13, 16)],
+	// 符类福音（及约翰福音）
+	&[(40, 3), (41, 1), (42, 3)],
+	&[(40, 4), (42, 4)],
+	&[(40, 5), (42, 6)],
+	&[(40, 6), (42, 11)],
+	&[(40, 8), (41, 5), (42, 8)],
+	&[(40, 9), (41, 2), (42, 5)],
+	&[(40, 11), (42, 7)],
+	&[(40, 12), (41, 3)],
+	&[(40, 13), (41, 4)],
+	&[(40, 14), (41, 6), (42, 9), (43, 6)],
+	&[(40, 15), (41, 7)],
+	&[(40, 16), (41, 8)],
+	&[(40, 17), (41, 9)],
+	&[(40, 19), (41, 10), (42, 18)],
+	&[(40, 21), (41, 11), (42, 19)],
+	&[(40, 22), (41, 12), (42, 20)],
+	&[(40, 24), (41, 13), (42, 21)],
+	&[(40, 26), (41, 14), (42, 22)],
+	&[(40, 27), (41, 15), (42, 23), (43, 19)],
+	&[(40, 28), (41, 16), (42, 24), (43, 20)],
+];
+
+/// 与某章平行的章；有多处时优先选 prefer_book 中的
+pub fn parallel_chapter(book_num: i32, chapter: i32, prefer_book: Option<i32>) -> Option<(i32, i32)> {
+	let group = PARALLEL_PASSAGES.iter().find(|g| g.contains(&(book_num, chapter)))?;
+	let others: Vec<(i32, i32)> = group.iter().copied().filter(|&c| c != (book_num, chapter)).collect();
+	others.iter().copied()
+		.find(|(b, _)| Some(*b) == prefer_book)
+		.or_else(|| others.first().copied())
+}
+
+/// 分屏中的副窗格（主窗口右侧的面板）：独立的译本、书卷和章
+pub struct SplitPane {
+	pub version: String,
+	pub book_num: i32,
+	pub chapter: String,
+	pub content: String,
+	/// 联动：主窗格换章时跳到平行经文
+	pub linked: bool,
+	books: Vec<(i32, String)>,
+	chapters: Vec<String>,
+	/// 上次联动时主窗格所在的章
+	followed: Option<(i32, String)>,
+	/// 联动状态说明
+	link_status: Option<String>,
+}
+
+impl SplitPane {
+	pub fn new(app: &BibleApp) -> Self {
+		let mut pane = Self {
+			version: app.current_version.clone(),
+			book_num: 0,
+			chapter: String::new(),
+			content: String::new(),
+			linked: false,
+			books: Vec::new(),
+			chapters: Vec::new(),
+			followed: None,
+			link_status: None,
+		};
+		pane.set_version(app, app.current_version.clone());
+		let book = app.current_book.unwrap_or(1);
+		let chapter = app.current_chapter.clone().unwrap_or_else(|| "1".to_string());
+		// 默认不联动，先打开主窗格的这一章；勾选联动后才跳到平行经文
+		pane.go_to(app, book, chapter);
+		pane
+	}
+
+	fn set_version(&mut self, app: &BibleApp, version: String) {
+		self.version = version;
+		self.books = load_books(&app.bible_root.join(&self.version));
+	}
+
+	/// 跳到某章；本译本没有这一章时保持原位
+	fn go_to(&mut self, app: &BibleApp, book_num: i32, chapter: String) {
+		let db_path = app.bible_root.join(&self.version);
+		let mut chapters = load_chapters(&db_path, book_num);
+		chapters.sort_by_key(|c| chapter_number(c));
+		let chapter = if chapters.contains(&chapter) {
+			chapter
+		} else if let Some(first) = chapters.first() {
+			first.clone()
+		} else {
+			eprintln!("{} 中找不到 {} {}", version_display_name(&self.version), book_full_name(book_num), chapter);
+			return;
+		};
+		self.content = load_chapter_content(&db_path, book_num, chapter.parse().unwrap_or(1));
+		self.book_num = book_num;
+		self.chapter = chapter;
+		self.chapters = chapters;
+	}
+
+	/// 联动：主窗格换到新的一章时跳到平行经文
+	fn follow(&mut self, app: &BibleApp) {
+		let (Some(book), Some(chapter)) = (app.current_book, app.current_chapter.clone()) else { return };
+		if self.followed.as_ref() == Some(&(book, chapter.clone())) {
+			return;
+		}
+		self.followed = Some((book, chapter.clone()));
+		match parallel_chapter(book, chapter.parse().unwrap_or(0), Some(self.book_num)) {
+			Some((b, c)) => {
+				self.go_to(app, b, c.to_string());
+				self.link_status = Some(format!("{} {} ↔ {} {}", book_full_name(book), chapter, book_full_name(b), c));
+			}
+			None => self.link_status = Some(format!("{} {} 没有平行经文", book_full_name(book), chapter)),
+		}
+	}
+}
+
+//分屏对照
+impl BibleApp {
+	pub fn toggle_split_pane(&mut self) {
+		self.split_pane = match self.split_pane {
+			Some(_) => None,
+			None => Some(SplitPane::new(self)),
+		};
+	}
+
+	pub fn ui_split_pane(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		let Some(mut pane) = self.split_pane.take() else { return };
+		if pane.linked {
+			pane.follow(self);
+		}
+
+		let mut close = false;
+		let mut version_choice: Option<String> = None;
+		let mut target: Option<(i32, String)> = None;
+
+		egui::SidePanel::right("split_pane")
+			.resizable(true)
+			.default_width(ctx.content_rect().width() * 0.35)
+			.show(ctx, |ui| {
+				ui.horizontal_wrapped(|ui| {
+					egui::ComboBox::from_id_salt("split_version")
+						.selected_text(version_display_name(&pane.version))
+						.width(90.0)
+						.show_ui(ui, |ui| {
+							for v in &self.versions {
+								if ui.selectable_label(*v == pane.version, version_display_name(v)).clicked() {
+									version_choice = Some(v.clone());
+								}
+							}
+						});
+					egui::ComboBox::from_id_salt("split_book")
+						.selected_text(book_full_name(pane.book_num))
+						.width(90.0)
+						.height(400.0)
+						.show_ui(ui, |ui| {
+							for (num, name) in &pane.books {
+								if ui.selectable_label(*num == pane.book_num, name).clicked() {
+									target = Some((*num, "1".to_string()));
+								}
+							}
+						});
+					let index = pane.chapters.iter().position(|c| *c == pane.chapter);
+					if ui.add_enabled(index.is_some_and(|i| i > 0), egui::Button::new("◀")).clicked()
+						&& let Some(i) = index
+					{
+						target = Some((pane.book_num, pane.chapters[i - 1].clone()));
+					}
+					egui::ComboBox::from_id_salt("split_chapter")
+						.selected_text(chapter_display_name(&pane.chapter))
+						.width(70.0)
+						.height(400.0)
+						.show_ui(ui, |ui| {
+							for c in &pane.chapters {
+								if ui.selectable_label(*c == pane.chapter, chapter_display_name(c)).clicked() {
+									target = Some((pane.book_num, c.clone()));
+								}
+							}
+						});
+					if ui.add_enabled(index.is_some_and(|i| i + 1 < pane.chapters.len()), egui::Button::new("▶")).clicked()
+						&& let Some(i) = index
+					{
+						target = Some((pane.book_num, pane.chapters[i + 1].clone()));
+					}
+					if ui.checkbox(&mut pane.linked, "🔗 联动")
+						.on_hover_text("主窗格换章时，这里跳到平行经文（列王纪 / 历代志、符类福音等）")
+						.changed()
+					{
+						pane.followed = None;
+						pane.link_status = None;
+					}
+					if ui.small_button("✖").on_hover_text("关闭分屏").clicked() {
+						close = true;
+					}
+				});
+				if pane.linked && let Some(status) = &pane.link_status {
+					ui.label(egui::RichText::new(status).size(12.0).color(colors.comment_text_color));
+				}
+				ui.separator();
+
+				egui::ScrollArea::vertical().id_salt("split_content").auto_shrink([false; 2]).show(ui, |ui| {
+					ui.add(egui::Label::new(egui::RichText::new(&pane.content).color(colors.text_color)).selectable(true));
+				});
+			});
+
+		if let Some(version) = version_choice {
+			pane.set_version(self, version);
+			let (book, chapter) = (pane.book_num, pane.chapter.clone());
+			pane.go_to(self, book, chapter);
+		}
+		if let Some((book, chapter)) = target {
+			pane.go_to(self, book, chapter);
+		}
+
+		if !close {
+			self.split_pane = Some(pane);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::reference::chapter_count;
+
+	#[test]
+	fn finds_parallel_chapters() {
+		assert_eq!(parallel_chapter(10, 5, None), Some((13, 11)));
+		assert_eq!(parallel_chapter(13, 11, None), Some((10, 5)));
+		// 有多处时优先选副窗格当前的书卷
+		assert_eq!(parallel_chapter(40, 3, None), Some((41, 1)));
+		assert_eq!(parallel_chapter(40, 3, Some(42)), Some((42, 3)));
+		assert_eq!(parallel_chapter(40, 3, Some(1)), Some((41, 1)));
+		assert_eq!(parallel_chapter(19, 14, None), Some((19, 53)));
+		assert_eq!(parallel_chapter(1, 1, None), None);
+	}
+
+	#[test]
+	fn parallel_table_is_consistent() {
+		let mut seen = std::collections::HashSet::new();
+		for group in PARALLEL_PASSAGES {
+			assert!(group.len() >= 2, "{:?}", group);
+			for &(book, chapter) in group.iter() {
+				assert!(chapter >= 1 && chapter <= chapter_count(book), "{} {}", book, chapter);
+				// 每章只在一组中，否则后面的组永远用不到
+				assert!(seen.insert((book, chapter)), "{} {}", book, chapter);
+			}
+		}
+	}
+}
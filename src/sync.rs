@@ -0,0 +1,711 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::notes::{self, Notedb, NOTE_COLUMNS, note_from_row, notes_db_path, ensure_notes_schema, write_note, load_note};
+use crate::notebooks::{self, Notebook, read_notebooks};
+use crate::settings::save_settings;
+use crate::tags;
+use crate::utils::compare_timestamps;
+
+/// 同步文件夹中存放各设备变更日志的子目录
+const SYNC_SUBDIR: &str = "bible_reader_sync";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeOp {
+	Upsert,
+	Delete,
+}
+
+/// 变更日志中的一条记录（每行一个 JSON）
+#[derive(Serialize, Deserialize)]
+pub struct ChangeEntry {
+	pub device: String,
+	pub op: ChangeOp,
+	pub id: String,
+	/// 写入日志的时间
+	pub at: String,
+	/// 笔记的修改时间（删除时为删除时间）
+	#[serde(default)]
+	pub updated_at: Option<String>,
+	#[serde(default)]
+	pub note: Option<Notedb>,
+	/// 笔记本路径，例如「讲道 / 2024」
+	#[serde(default)]
+	pub notebook: Option<String>,
+}
+
+/// 一次同步的结果
+#[derive(Default)]
+pub struct SyncReport {
+	pub exported: usize,
+	pub applied: usize,
+	pub deleted: usize,
+	pub conflicts: usize,
+}
+
+impl SyncReport {
+	fn summary(&self) -> String {
+		format!(
+			"已同步：收到更新 {} 条，删除 {} 条，冲突 {} 条（已保留冲突副本），发出变更 {} 条",
+			self.applied, self.deleted, self.conflicts, self.exported
+		)
+	}
+}
+
+/// 每条笔记上次同步时的内容哈希与是否已删除
+type SyncState = HashMap<String, (String, bool)>;
+
+fn ensure_sync_schema(conn: &Connection, category: &str) -> rusqlite::Result<()> {
+	conn.execute_batch(&format!(
+		"CREATE TABLE IF NOT EXISTS {cat}_sync_state (
+			note_id TEXT PRIMARY KEY,
+			hash TEXT NOT NULL,
+			deleted INTEGER NOT NULL DEFAULT 0
+		);
+		CREATE TABLE IF NOT EXISTS {cat}_sync_peers (
+			device TEXT PRIMARY KEY,
+			applied_lines INTEGER NOT NULL DEFAULT 0
+		);",
+		cat = category
+	))
+}
+
+fn load_state(conn: &Connection, category: &str) -> rusqlite::Result<SyncState> {
+	let mut stmt = conn.prepare(&format!("SELECT note_id, hash, deleted FROM {}_sync_state", category))?;
+	let rows = stmt.query_map([], |row| {
+		Ok((row.get::<_, String>(0)?, (row.get::<_, String>(1)?, row.get::<_, bool>(2)?)))
+	})?;
+	Ok(rows.flatten().collect())
+}
+
+fn set_state(conn: &Connection, category: &str, note_id: &str, state: Option<&(String, bool)>) -> rusqlite::Result<()> {
+	match state {
+		Some((hash, deleted)) => conn.execute(
+			&format!("INSERT OR REPLACE INTO {}_sync_state (note_id, hash, deleted) VALUES (?1, ?2, ?3)", category),
+			rusqlite::params![note_id, hash, deleted],
+		)?,
+		None => conn.execute(&format!("DELETE FROM {}_sync_state WHERE note_id = ?1", category), [note_id])?,
+	};
+	Ok(())
+}
+
+/// 读取全部笔记（包括回收站），附带标签
+fn load_all_notes(conn: &Connection, category: &str) -> rusqlite::Result<Vec<Notedb>> {
+	let mut notes: Vec<Notedb> = {
+		let mut stmt = conn.prepare(&format!("SELECT {} FROM {}", NOTE_COLUMNS, category))?;
+		let rows = stmt.query_map([], note_from_row)?;
+		rows.flatten().collect()
+	};
+	tags::attach_tags(conn, category, &mut notes);
+	Ok(notes)
+}
+
+/// 写入日志时笔记的样子：笔记本换成路径，去掉只在本机有意义的字段
+fn portable_note(note: &Notedb, notebooks: &[Notebook]) -> (Notedb, Option<String>) {
+	let notebook = note.notebook_id.as_deref()
+		.map(|id| notebooks::notebook_path(notebooks, id))
+		.filter(|p| !p.is_empty());
+	let mut portable = note.clone();
+	portable.keywords = None;
+	portable.notebook_id = None;
+	portable.deleted_at = None;
+	(portable, notebook)
+}
+
+fn portable_hash(portable: &Notedb, notebook: Option<&str>) -> String {
+	let mut hasher = Sha256::new();
+	hasher.update(serde_json::to_string(portable).unwrap_or_default().as_bytes());
+	hasher.update(notebook.unwrap_or_default().as_bytes());
+	format!("{:x}", hasher.finalize())
+}
+
+fn sync_hash(note: &Notedb, notebooks: &[Notebook]) -> String {
+	let (portable, notebook) = portable_note(note, notebooks);
+	portable_hash(&portable, notebook.as_deref())
+}
+
+fn current_state(note: Option<&Notedb>, notebooks: &[Notebook]) -> Option<(String, bool)> {
+	note.map(|n| (sync_hash(n, notebooks), n.deleted_at.is_some()))
+}
+
+/// 自上次同步以来本机是否改动过这条笔记
+fn changed_locally(current: Option<&(String, bool)>, recorded: Option<&(String, bool)>) -> bool {
+	match (current, recorded) {
+		(None, None) => false,
+		(None, Some((_, deleted))) => !deleted,
+		(Some(_), None) => true,
+		(Some(c), Some(r)) => c != r,
+	}
+}
+
+fn notebook_id_for(category: &str, path: Option<&str>) -> Option<String> {
+	let path = path?;
+	notebooks::ensure_notebook_path(category, path).unwrap_or_else(|e| {
+		eprintln!("创建笔记本「{}」失败: {:?}", path, e);
+		None
+	})
+}
+
+/// 另存一份冲突副本（新 id），避免被覆盖的一方丢失内容
+fn write_conflict_copy(category: &str, note: &Notedb, notebook: Option<&str>) {
+	let mut copy = note.clone();
+	copy.id = Uuid::new_v4().to_string();
+	copy.title = Some(format!("{}（冲突副本）", note.title.as_deref().unwrap_or("")));
+	copy.notebook_id = notebook_id_for(category, notebook);
+	if let Err(e) = write_note(category, &copy, note.updated_at.as_deref().unwrap_or("")) {
+		eprintln!("保存冲突副本失败 id={}: {}", note.id, e);
+	}
+}
+
+/// 应用其他设备的一条变更
+fn apply_entry(conn: &Connection, category: &str, entry: ChangeEntry, report: &mut SyncReport) -> rusqlite::Result<()> {
+	let notebooks = read_notebooks(conn, category);
+	let local = load_note(conn, category, &entry.id)?;
+	let current = current_state(local.as_ref(), &notebooks);
+	let recorded = load_state(conn, category)?.remove(&entry.id);
+	let local_changed = changed_locally(current.as_ref(), recorded.as_ref());
+
+	match entry.op {
+		ChangeOp::Upsert => {
+			let Some(mut remote) = entry.note else { return Ok(()) };
+			remote.id = entry.id.clone();
+			remote.keywords = None;
+			remote.notebook_id = None;
+			remote.deleted_at = None;
+
+			// 两边内容相同，只记下同步状态
+			if let Some((hash, false)) = &current
+				&& *hash == portable_hash(&remote, entry.notebook.as_deref())
+			{
+				set_state(conn, category, &entry.id, current.as_ref())?;
+				return Ok(());
+			}
+
+			let remote_wins = match &local {
+				Some(local) if local_changed && local.deleted_at.is_none() => {
+					report.conflicts += 1;
+					// 修改时间相同时以收到的为准
+					let wins = compare_timestamps(remote.updated_at.as_deref(), local.updated_at.as_deref()).is_ge();
+					if wins {
+						let (_, notebook) = portable_note(local, &notebooks);
+						write_conflict_copy(category, local, notebook.as_deref());
+					} else {
+						write_conflict_copy(category, &remote, entry.notebook.as_deref());
+					}
+					wins
+				}
+				_ => true,
+			};
+			if remote_wins {
+				remote.notebook_id = notebook_id_for(category, entry.notebook.as_deref());
+				let updated_at = remote.updated_at.clone().unwrap_or_default();
+				if let Err(e) = write_note(category, &remote, &updated_at) {
+					eprintln!("应用同步的笔记失败 id={}: {}", entry.id, e);
+					return Ok(());
+				}
+				report.applied += 1;
+
+				let notebooks = read_notebooks(conn, category);
+				let written = load_note(conn, category, &entry.id)?;
+				set_state(conn, category, &entry.id, current_state(written.as_ref(), &notebooks).as_ref())?;
+			}
+		}
+		ChangeOp::Delete => {
+			let Some(local) = local else {
+				set_state(conn, category, &entry.id, None)?;
+				return Ok(());
+			};
+			if local.deleted_at.is_some() {
+				set_state(conn, category, &entry.id, current.as_ref())?;
+			} else if !local_changed {
+				notes::delete_note(category, &entry.id)?;
+				report.deleted += 1;
+				let deleted = load_note(conn, category, &entry.id)?;
+				set_state(conn, category, &entry.id, current_state(deleted.as_ref(), &notebooks).as_ref())?;
+			}
+			// 本机在对方删除后又修改过：保留本机的笔记，下次同步时重新发出
+		}
+	}
+	Ok(())
+}
+
+/// 读取其他设备的变更日志，只处理上次之后新增的行
+fn merge_peer_logs(conn: &Connection, category: &str, dir: &Path, device_id: &str, report: &mut SyncReport) -> Result<(), String> {
+	let entries = fs::read_dir(dir).map_err(|e| format!("无法读取同步文件夹：{}", e))?;
+	let mut logs: Vec<_> = entries
+		.flatten()
+		.map(|e| e.path())
+		.filter(|p| p.extension().is_some_and(|e| e == "jsonl"))
+		.filter(|p| p.file_stem().is_some_and(|s| s != device_id))
+		.collect();
+	logs.sort();
+
+	for path in logs {
+		let device = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+		let applied: usize = conn
+			.query_row(
+				&format!("SELECT applied_lines FROM {}_sync_peers WHERE device = ?1", category),
+				[&device],
+				|row| row.get::<_, i64>(0),
+			)
+			.optional()
+			.map_err(|e| e.to_string())?
+			.unwrap_or(0) as usize;
+
+		let text = match fs::read_to_string(&path) {
+			Ok(t) => t,
+			Err(e) => {
+				eprintln!("跳过无法读取的同步日志 {:?}: {:?}", path, e);
+				continue;
+			}
+		};
+
+		let lines: Vec<&str> = text.lines().collect();
+		// 文件不以换行结尾时，最后一行可能还在传输中
+		let last_incomplete = !text.ends_with('\n');
+		let mut count = applied;
+		for (i, line) in lines.iter().enumerate().skip(applied) {
+			match serde_json::from_str::<ChangeEntry>(line) {
+				Ok(entry) => {
+					if let Err(e) = apply_entry(conn, category, entry, report) {
+						eprintln!("应用同步记录失败 {:?} 第 {} 行: {:?}", path, i + 1, e);
+					}
+				}
+				// 没写完的最后一行留到下次再读
+				Err(_) if last_incomplete && i + 1 == lines.len() => break,
+				Err(_) if line.trim().is_empty() => {}
+				Err(e) => eprintln!("跳过无法解析的同步记录 {:?} 第 {} 行: {}", path, i + 1, e),
+			}
+			count = i + 1;
+		}
+
+		if count != applied {
+			conn.execute(
+				&format!("INSERT OR REPLACE INTO {}_sync_peers (device, applied_lines) VALUES (?1, ?2)", category),
+				rusqlite::params![device, count as i64],
+			)
+			.map_err(|e| e.to_string())?;
+		}
+	}
+	Ok(())
+}
+
+/// 把本机自上次同步以来的新建、修改和删除追加到本机的日志
+fn append_local_changes(conn: &Connection, category: &str, dir: &Path, device_id: &str, report: &mut SyncReport) -> Result<(), String> {
+	let notes = load_all_notes(conn, category).map_err(|e| e.to_string())?;
+	let notebooks = read_notebooks(conn, category);
+	let mut state = load_state(conn, category).map_err(|e| e.to_string())?;
+	let at = Utc::now().to_rfc3339();
+
+	let mut changes: Vec<(ChangeEntry, Option<(String, bool)>)> = Vec::new();
+	for note in &notes {
+		let current = current_state(Some(note), &notebooks);
+		let recorded = state.remove(&note.id);
+		if !changed_locally(current.as_ref(), recorded.as_ref()) {
+			continue;
+		}
+		let entry = if note.deleted_at.is_some() {
+			ChangeEntry {
+				device: device_id.to_string(),
+				op: ChangeOp::Delete,
+				id: note.id.clone(),
+				at: at.clone(),
+				updated_at: note.deleted_at.clone(),
+				note: None,
+				notebook: None,
+			}
+		} else {
+			let (portable, notebook) = portable_note(note, &notebooks);
+			ChangeEntry {
+				device: device_id.to_string(),
+				op: ChangeOp::Upsert,
+				id: note.id.clone(),
+				at: at.clone(),
+				updated_at: note.updated_at.clone(),
+				note: Some(portable),
+				notebook,
+			}
+		};
+		changes.push((entry, current));
+	}
+
+	// 剩下的是已彻底删除的笔记
+	for (id, (_, deleted)) in state {
+		let entry = ChangeEntry {
+			device: device_id.to_string(),
+			op: ChangeOp::Delete,
+			id,
+			at: at.clone(),
+			updated_at: Some(at.clone()),
+			note: None,
+			notebook: None,
+		};
+		if deleted {
+			// 移入回收站时已经发出过
+			set_state(conn, category, &entry.id, None).map_err(|e| e.to_string())?;
+		} else {
+			changes.push((entry, None));
+		}
+	}
+
+	if changes.is_empty() {
+		return Ok(());
+	}
+
+	let mut lines = String::new();
+	for (entry, _) in &changes {
+		lines.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+		lines.push('\n');
+	}
+	let log_path = dir.join(format!("{}.jsonl", device_id));
+	OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(&log_path)
+		.and_then(|mut f| f.write_all(lines.as_bytes()))
+		.map_err(|e| format!("无法写入同步日志：{}", e))?;
+
+	for (entry, current) in &changes {
+		set_state(conn, category, &entry.id, current.as_ref()).map_err(|e| e.to_string())?;
+	}
+	report.exported += changes.len();
+	Ok(())
+}
+
+/// 与同步文件夹双向同步：先合并其他设备的变更，再写出本机的变更
+pub fn sync_notes(category: &str, folder: &Path, device_id: &str) -> Result<SyncReport, String> {
+	// 日志中的正文是明文，各设备的密钥也不同，加密的笔记库不能同步
+	if crate::crypto::is_encrypted() {
+		return Err("笔记已加密，加密期间不同步（否则正文会以明文写入同步文件夹）".to_string());
+	}
+	if !folder.is_dir() {
+		return Err(format!("同步文件夹不存在：{}", folder.display()));
+	}
+	let dir = folder.join(SYNC_SUBDIR);
+	fs::create_dir_all(&dir).map_err(|e| format!("无法创建同步目录：{}", e))?;
+
+	let db_path = notes_db_path().ok_or("找不到笔记数据库")?;
+	let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+	ensure_notes_schema(&conn, category).map_err(|e| e.to_string())?;
+	ensure_sync_schema(&conn, category).map_err(|e| e.to_string())?;
+
+	let mut report = SyncReport::default();
+	merge_peer_logs(&conn, category, &dir, device_id, &mut report)?;
+	append_local_changes(&conn, category, &dir, device_id, &mut report)?;
+	Ok(report)
+}
+
+/// 退出时等待同步完成的最长时间，超时就放弃，下次启动再同步
+pub const SYNC_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+//笔记同步
+impl BibleApp {
+	/// 按设置中的同步文件夹在后台同步一次；未设置或已在同步时什么也不做
+	pub fn run_sync(&mut self) {
+		if self.sync_job.is_some() {
+			return;
+		}
+		let Some(folder) = self.settings.sync_folder.clone().filter(|f| !f.trim().is_empty()) else { return };
+		if crate::crypto::is_encrypted() {
+			self.sync_status = Some("笔记已加密，加密期间不同步（否则正文会以明文写入同步文件夹）".to_string());
+			return;
+		}
+		let device_id = self.settings.device_id.clone();
+		let (tx, rx) = mpsc::channel();
+		let spawned = thread::Builder::new().name("sync".to_string()).spawn(move || {
+			let _ = tx.send(sync_notes("notes", Path::new(folder.trim()), &device_id));
+		});
+		match spawned {
+			Ok(_) => {
+				self.sync_job = Some(rx);
+				self.sync_status = Some("正在同步…".to_string());
+			}
+			Err(e) => self.finish_sync(Err(format!("无法启动同步线程：{}", e))),
+		}
+	}
+
+	/// 每帧检查后台同步是否完成
+	pub fn poll_sync(&mut self, ctx: &egui::Context) {
+		let Some(rx) = &self.sync_job else { return };
+		match rx.try_recv() {
+			Ok(result) => {
+				self.sync_job = None;
+				self.finish_sync(result);
+			}
+			Err(TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(200)),
+			Err(TryRecvError::Disconnected) => {
+				self.sync_job = None;
+				self.finish_sync(Err("同步线程意外退出".to_string()));
+			}
+		}
+	}
+
+	/// 等待后台同步完成，超过 timeout 就不再等待
+	pub fn wait_for_sync(&mut self, timeout: Duration) {
+		let Some(rx) = self.sync_job.take() else { return };
+		match rx.recv_timeout(timeout) {
+			Ok(result) => self.finish_sync(result),
+			Err(RecvTimeoutError::Timeout) => eprintln!("同步超过 {} 秒仍未完成，已放弃等待", timeout.as_secs()),
+			Err(RecvTimeoutError::Disconnected) => self.finish_sync(Err("同步线程意外退出".to_string())),
+		}
+	}
+
+	fn finish_sync(&mut self, result: Result<SyncReport, String>) {
+		match result {
+			Ok(report) => {
+				println!("{}", report.summary());
+				if report.applied + report.deleted + report.conflicts > 0 {
+					self.last_appended_notes_chapter = None;
+					if self.show_notes_list_window {
+						self.reload_notes_list();
+					}
+				}
+				self.sync_status = Some(report.summary());
+			}
+			Err(e) => {
+				eprintln!("同步笔记失败: {}", e);
+				self.sync_status = Some(format!("同步失败：{}", e));
+			}
+		}
+	}
+
+	pub fn show_sync_window(&mut self, ctx: &egui::Context, colors: &ThemeColors) {
+		if !self.show_sync_window {
+			return;
+		}
+		let mut open = true;
+		let mut sync_now = false;
+
+		egui::Window::new(egui::RichText::new("🔄 笔记同步").size(14.0))
+			.open(&mut open)
+			.resizable(false)
+			.default_width(440.0)
+			.show(ctx, |ui| {
+				ui.label("选择一个由 Syncthing、Nextcloud 等同步的文件夹。每台设备把自己的笔记变更写入其中，启动时合并其他设备的变更。同步日志是明文，启用笔记加密后不再同步。");
+				ui.add_space(4.0);
+				ui.horizontal(|ui| {
+					ui.label("同步文件夹：");
+					ui.add(
+						egui::TextEdit::singleline(&mut self.sync_folder_input)
+						.hint_text("留空表示不同步")
+						.desired_width(280.0),
+					);
+				});
+				ui.label(
+					egui::RichText::new(format!("本机标识：{}", self.settings.device_id))
+					.size(11.0)
+					.color(colors.comment_text_color),
+				);
+				if let Some(status) = &self.sync_status {
+					ui.label(egui::RichText::new(status).size(12.0).color(colors.comment_text_color));
+				}
+				ui.separator();
+				let syncing = self.sync_job.is_some();
+				if ui.add_enabled(!syncing, egui::Button::new("保存并立即同步")).clicked() {
+					sync_now = true;
+				}
+			});
+
+		if sync_now {
+			let folder = self.sync_folder_input.trim().to_string();
+			self.settings.sync_folder = if folder.is_empty() { None } else { Some(folder) };
+			save_settings(&self.settings);
+			if self.settings.sync_folder.is_some() {
+				self.run_sync();
+			} else {
+				self.sync_status = Some("已关闭同步".to_string());
+			}
+		}
+		if !open {
+			self.show_sync_window = false;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::PathBuf;
+
+	/// 同步文件夹和两台设备各自的笔记库，都在一个临时目录里
+	struct TestSync {
+		root: PathBuf,
+	}
+
+	impl TestSync {
+		fn new() -> Self {
+			let root = std::env::temp_dir().join(format!("sync-test-{}", Uuid::new_v4()));
+			fs::create_dir_all(root.join("folder")).unwrap();
+			TestSync { root }
+		}
+
+		fn use_device(&self, device: &str) {
+			notes::set_test_db_path(Some(self.root.join(format!("{}.db", device))));
+		}
+
+		fn sync(&self, device: &str) -> SyncReport {
+			self.use_device(device);
+			sync_notes("notes", &self.root.join("folder"), device).unwrap()
+		}
+
+		fn save(&self, device: &str, id: &str, title: &str, updated_at: &str) {
+			self.use_device(device);
+			let note = Notedb {
+				id: id.to_string(),
+				verse_start: -1,
+				title: Some(title.to_string()),
+				body: Some(format!("{}的正文", title)),
+				..Default::default()
+			};
+			write_note("notes", &note, updated_at).unwrap();
+		}
+
+		fn delete(&self, device: &str, id: &str) {
+			self.use_device(device);
+			notes::delete_note("notes", id).unwrap();
+		}
+
+		/// 设备上的全部笔记，(标题, 是否在回收站)，按标题排序
+		fn titles(&self, device: &str) -> Vec<(String, bool)> {
+			let conn = Connection::open(self.root.join(format!("{}.db", device))).unwrap();
+			let mut titles: Vec<(String, bool)> = load_all_notes(&conn, "notes").unwrap()
+				.into_iter()
+				.map(|n| (n.title.unwrap_or_default(), n.deleted_at.is_some()))
+				.collect();
+			titles.sort();
+			titles
+		}
+
+		fn log_path(&self, device: &str) -> PathBuf {
+			self.root.join("folder").join(SYNC_SUBDIR).join(format!("{}.jsonl", device))
+		}
+	}
+
+	impl Drop for TestSync {
+		fn drop(&mut self) {
+			notes::set_test_db_path(None);
+			let _ = fs::remove_dir_all(&self.root);
+		}
+	}
+
+	fn live(title: &str) -> (String, bool) {
+		(title.to_string(), false)
+	}
+
+	/// 两台设备都有同一条笔记，并且都已同步过
+	fn shared_note() -> TestSync {
+		let t = TestSync::new();
+		t.save("a", "n1", "原稿", "2024-05-01T10:00:00+00:00");
+		t.sync("a");
+		t.sync("b");
+		t
+	}
+
+	#[test]
+	fn detects_local_changes_against_recorded_state() {
+		let hash = |h: &str, deleted| (h.to_string(), deleted);
+		assert!(!changed_locally(None, None));
+		assert!(changed_locally(Some(&hash("x", false)), None));
+		assert!(!changed_locally(Some(&hash("x", false)), Some(&hash("x", false))));
+		assert!(changed_locally(Some(&hash("y", false)), Some(&hash("x", false))));
+		assert!(changed_locally(Some(&hash("x", true)), Some(&hash("x", false))));
+		// 彻底删除：上次同步时还在就算改动，已在回收站则不算
+		assert!(changed_locally(None, Some(&hash("x", false))));
+		assert!(!changed_locally(None, Some(&hash("x", true))));
+	}
+
+	#[test]
+	fn applies_remote_edit_when_unchanged_locally() {
+		let _encryption = crate::crypto::test_encryption(None);
+		let t = shared_note();
+		assert_eq!(t.titles("b"), vec![live("原稿")]);
+
+		t.save("b", "n1", "修改稿", "2024-05-01T11:00:00+00:00");
+		assert_eq!(t.sync("b").exported, 1);
+		let report = t.sync("a");
+		assert_eq!((report.applied, report.conflicts), (1, 0));
+		assert_eq!(t.titles("a"), vec![live("修改稿")]);
+		// 刚收到的更新不会再发回去
+		assert_eq!(t.sync("a").exported, 0);
+	}
+
+	#[test]
+	fn concurrent_edits_keep_newer_and_conflict_copy() {
+		let _encryption = crate::crypto::test_encryption(None);
+		let t = shared_note();
+		t.save("a", "n1", "甲稿", "2024-05-01T12:00:00+00:00");
+		t.save("b", "n1", "乙稿", "2024-05-01T11:00:00+00:00");
+		t.sync("a");
+
+		// 乙的改动较旧：留下甲的版本，乙的另存为冲突副本
+		let report = t.sync("b");
+		assert_eq!((report.applied, report.conflicts), (1, 1));
+		let expected = vec![live("乙稿（冲突副本）"), live("甲稿")];
+		assert_eq!(t.titles("b"), expected);
+
+		// 冲突副本也同步回甲
+		let report = t.sync("a");
+		assert_eq!(report.conflicts, 0);
+		assert_eq!(t.titles("a"), expected);
+	}
+
+	#[test]
+	fn remote_delete_moves_unchanged_note_to_trash() {
+		let _encryption = crate::crypto::test_encryption(None);
+		let t = shared_note();
+		t.delete("a", "n1");
+		t.sync("a");
+		assert_eq!(t.sync("b").deleted, 1);
+		assert_eq!(t.titles("b"), vec![("原稿".to_string(), true)]);
+	}
+
+	#[test]
+	fn local_edit_survives_remote_delete() {
+		let _encryption = crate::crypto::test_encryption(None);
+		let t = shared_note();
+		t.delete("a", "n1");
+		t.sync("a");
+		t.save("b", "n1", "修改稿", "2024-05-02T10:00:00+00:00");
+
+		// 乙在删除之后又改过：不删除，并把修改发回甲
+		let report = t.sync("b");
+		assert_eq!((report.deleted, report.exported), (0, 1));
+		assert_eq!(t.titles("b"), vec![live("修改稿")]);
+		t.sync("a");
+		assert_eq!(t.titles("a"), vec![live("修改稿")]);
+	}
+
+	#[test]
+	fn resumes_peer_log_after_incomplete_last_line() {
+		let _encryption = crate::crypto::test_encryption(None);
+		let t = TestSync::new();
+		t.save("b", "n1", "第一条", "2024-05-01T10:00:00+00:00");
+		t.save("b", "n2", "第二条", "2024-05-01T10:00:00+00:00");
+		t.sync("b");
+
+		// 模拟第二行还在传输中
+		let full = fs::read_to_string(t.log_path("b")).unwrap();
+		let cut = full.find('\n').unwrap() + 20;
+		fs::write(t.log_path("b"), &full[..cut]).unwrap();
+		assert_eq!(t.sync("a").applied, 1);
+		assert_eq!(t.titles("a"), vec![live("第一条")]);
+
+		// 写完整后只读新增的那一行
+		fs::write(t.log_path("b"), &full).unwrap();
+		assert_eq!(t.sync("a").applied, 1);
+		assert_eq!(t.titles("a"), vec![live("第一条"), live("第二条")]);
+		assert_eq!(t.sync("a").applied, 0);
+	}
+}
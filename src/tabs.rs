@@ -0,0 +1,269 @@
+use serde::{Serialize, Deserialize};
+use crate::theme::ThemeColors;
+use crate::BibleApp;
+use crate::reference::book_full_name;
+use crate::settings::save_settings;
+use crate::shortcuts::ShortcutAction;
+use crate::utils::{load_chapters, load_chapter_content, chapter_number, version_display_name};
+
+/// 一个阅读标签页：各自的译本、位置、滚动位置与前进后退记录
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReaderTab {
+	pub version: String,
+	pub book_num: Option<i32>,
+	pub chapter: Option<String>,
+	pub scroll_offset: f32,
+	pub back_stack: Vec<(String, i32, String)>,
+	pub forward_stack: Vec<(String, i32, String)>,
+}
+
+impl ReaderTab {
+	/// 标签标题，例如「约翰福音 3」
+	pub fn title(&self) -> String {
+		match (self.book_num, self.chapter.as_deref()) {
+			(Some(book), Some(chap)) => format!("{} {}", book_full_name(book), chap),
+			(Some(book), None) => book_full_name(book).to_string(),
+			_ => "新标签页".to_string(),
+		}
+	}
+}
+
+/// 切换 delta 个标签页后的位置，首尾相接
+fn cycled_index(active: usize, delta: isize, len: usize) -> usize {
+	(active as isize + delta).rem_euclid(len as isize) as usize
+}
+
+/// 关闭第 closed 个标签页后当前标签页的位置（还剩 remaining 个）
+fn active_after_close(active: usize, closed: usize, remaining: usize) -> usize {
+	if closed < active { active - 1 } else { active.min(remaining - 1) }
+}
+
+//阅读标签页
+impl BibleApp {
+	/// 当前阅读状态（平铺在 BibleApp 上的字段）
+	fn snapshot_tab(&self) -> ReaderTab {
+		ReaderTab {
+			version: self.current_version.clone(),
+			book_num: self.current_book,
+			chapter: self.current_chapter.clone(),
+			scroll_offset: self.content_scroll_offset,
+			back_stack: self.jump_back_stack.clone(),
+			forward_stack: self.jump_forward_stack.clone(),
+		}
+	}
+
+	/// 把当前阅读状态写回当前标签页
+	pub fn store_active_tab(&mut self) {
+		let tab = self.snapshot_tab();
+		match self.tabs.get_mut(self.active_tab) {
+			Some(slot) => *slot = tab,
+			None => {
+				self.tabs.push(tab);
+				self.active_tab = self.tabs.len() - 1;
+			}
+		}
+	}
+
+	/// 切换到某个标签页，读取它的译本、章节和记录
+	fn load_tab(&mut self, index: usize) {
+		let Some(tab) = self.tabs.get(index).cloned() else { return };
+		self.active_tab = index;
+
+		// 译本已不存在时沿用当前译本
+		let version = if self.versions.contains(&tab.version) { tab.version } else { self.current_version.clone() };
+		let db_path = self.bible_root.join(&version);
+		if version != self.current_version {
+			self.switch_version(version);
+		}
+
+		self.current_book = tab.book_num;
+		self.chapters = match tab.book_num {
+			Some(book) => {
+				let mut chapters = load_chapters(&db_path, book);
+				chapters.sort_by_key(|c| chapter_number(c));
+				chapters
+			}
+			None => Vec::new(),
+		};
+		self.current_chapter = tab.chapter;
+		self.content = match (self.current_book, self.current_chapter.as_deref()) {
+			(Some(book), Some(chap)) => load_chapter_content(&db_path, book, chap.parse().unwrap_or(1)),
+			_ => String::new(),
+		};
+		self.jump_back_stack = tab.back_stack;
+		self.jump_forward_stack = tab.forward_stack;
+		self.pending_scroll = Some(tab.scroll_offset);
+	}
+
+	pub fn switch_tab(&mut self, index: usize) {
+		if index == self.active_tab || index >= self.tabs.len() {
+			return;
+		}
+		self.store_active_tab();
+		self.load_tab(index);
+	}
+
+	/// 切换到下一个（delta = 1）或上一个（delta = -1）标签页
+	pub fn cycle_tab(&mut self, delta: isize) {
+		if self.tabs.len() < 2 {
+			return;
+		}
+		self.switch_tab(cycled_index(self.active_tab, delta, self.tabs.len()));
+	}
+
+	/// 在当前标签页后面新开一个标签页，显示同一章（Ctrl+T）
+	pub fn new_tab(&mut self) {
+		self.store_active_tab();
+		let tab = ReaderTab {
+			back_stack: Vec::new(),
+			forward_stack: Vec::new(),
+			..self.tabs[self.active_tab].clone()
+		};
+		self.tabs.insert(self.active_tab + 1, tab);
+		self.load_tab(self.active_tab + 1);
+		self.save_tabs();
+	}
+
+	/// 在新标签页中打开某一章（中键点击搜索结果）
+	pub fn open_in_new_tab(&mut self, book_num: i32, chapter: String) {
+		self.store_active_tab();
+		let tab = ReaderTab {
+			version: self.current_version.clone(),
+			book_num: Some(book_num),
+			chapter: Some(chapter),
+			..Default::default()
+		};
+		self.tabs.insert(self.active_tab + 1, tab);
+		let highlight = self.show_highlight;
+		self.load_tab(self.active_tab + 1);
+		self.show_highlight = highlight;
+		self.save_tabs();
+	}
+
+	/// 关闭标签页（Ctrl+W）；最后一个标签页不能关闭
+	pub fn close_tab(&mut self, index: usize) {
+		if self.tabs.len() < 2 || index >= self.tabs.len() {
+			return;
+		}
+		self.store_active_tab();
+		self.tabs.remove(index);
+		let active = active_after_close(self.active_tab, index, self.tabs.len());
+		if index == self.active_tab {
+			self.load_tab(active);
+		} else {
+			self.active_tab = active;
+		}
+		self.save_tabs();
+	}
+
+	/// 把标签页写入设置，下次启动时恢复
+	pub fn save_tabs(&mut self) {
+		if self.current_book.is_none() {
+			return;
+		}
+		self.store_active_tab();
+		self.settings.open_tabs = self.tabs.clone();
+		self.settings.active_tab = self.active_tab;
+		save_settings(&self.settings);
+	}
+
+	/// 启动时恢复上次的标签页；没有保存的标签页时以当前位置作为第一个
+	pub fn restore_tabs(&mut self) {
+		self.tabs = self.settings.open_tabs.clone();
+		if self.tabs.is_empty() {
+			self.active_tab = 0;
+			self.store_active_tab();
+		} else {
+			let active = self.settings.active_tab.min(self.tabs.len() - 1);
+			self.load_tab(active);
+		}
+	}
+
+	pub fn ui_tab_bar(&mut self, ui: &mut egui::Ui, colors: &ThemeColors) {
+		let mut switch_to: Option<usize> = None;
+		let mut close: Option<usize> = None;
+		let mut new_tab = false;
+		let close_hint = format!("关闭{}", self.shortcut_hint(ShortcutAction::CloseTab));
+		let new_hint = format!("新标签页{}", self.shortcut_hint(ShortcutAction::NewTab));
+
+		ui.horizontal_wrapped(|ui| {
+			for (i, tab) in self.tabs.iter().enumerate() {
+				let is_active = i == self.active_tab;
+				// 当前标签页的位置以平铺字段为准
+				let (title, version) = if is_active {
+					(self.snapshot_tab().title(), self.current_version.clone())
+				} else {
+					(tab.title(), tab.version.clone())
+				};
+				let resp = ui.selectable_label(is_active, egui::RichText::new(title).size(13.0))
+					.on_hover_text(format!("{}（中键关闭）", version_display_name(&version)));
+				if resp.clicked() {
+					switch_to = Some(i);
+				}
+				if resp.middle_clicked() {
+					close = Some(i);
+				}
+				if self.tabs.len() > 1
+					&& ui.add(egui::Button::new(egui::RichText::new("✖").size(10.0).color(colors.comment_text_color)).frame(false))
+						.on_hover_text(&close_hint)
+						.clicked()
+				{
+					close = Some(i);
+				}
+				ui.add_space(6.0);
+			}
+			if ui.small_button("➕").on_hover_text(&new_hint).clicked() {
+				new_tab = true;
+			}
+		});
+
+		if let Some(i) = switch_to {
+			self.switch_tab(i);
+		}
+		if let Some(i) = close {
+			self.close_tab(i);
+		}
+		if new_tab {
+			self.new_tab();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn titles_tabs_by_location() {
+		let tab = |book_num, chapter: Option<&str>| ReaderTab { book_num, chapter: chapter.map(str::to_string), ..Default::default() };
+		assert_eq!(tab(Some(43), Some("3")).title(), "约翰福音 3");
+		assert_eq!(tab(Some(43), None).title(), "约翰福音");
+		assert_eq!(tab(None, None).title(), "新标签页");
+	}
+
+	#[test]
+	fn restores_tabs_saved_without_newer_fields() {
+		let tab: ReaderTab = serde_json::from_str(r#"{"version":"cuvs.db","book_num":1,"chapter":"2"}"#).unwrap();
+		assert_eq!((tab.book_num, tab.chapter.as_deref(), tab.scroll_offset), (Some(1), Some("2"), 0.0));
+		assert!(tab.back_stack.is_empty() && tab.forward_stack.is_empty());
+	}
+
+	#[test]
+	fn cycles_through_tabs() {
+		assert_eq!(cycled_index(2, 1, 3), 0);
+		assert_eq!(cycled_index(0, -1, 3), 2);
+		assert_eq!(cycled_index(1, 1, 3), 2);
+	}
+
+	#[test]
+	fn keeps_the_active_tab_when_closing_others() {
+		// 关闭前面的标签页，当前标签页前移一位
+		assert_eq!(active_after_close(2, 0, 3), 1);
+		// 关闭后面的标签页，位置不变
+		assert_eq!(active_after_close(1, 2, 2), 1);
+		// 关闭当前标签页：换到后一个，已是最后一个时换到前一个
+		assert_eq!(active_after_close(1, 1, 2), 1);
+		assert_eq!(active_after_close(2, 2, 2), 1);
+	}
+}
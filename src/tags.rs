@@ -0,0 +1,385 @@
+use rusqlite::Connection;
+use crate::theme::ThemeColors;
+use crate::notes::{Notedb, notes_db_path};
+
+/// 关键词字符串中允许的标签分隔符
+const TAG_SEPARATORS: [char; 7] = ['，', ',', '；', ';', '、', ' ', '　'];
+
+/// 标签之间统一使用的连接符（写回 keywords 字段）
+pub const TAG_JOINER: &str = "，";
+
+/// 把旧式关键词字符串拆分成标签（去重、去空白，保持原顺序）
+pub fn split_keywords(keywords: &str) -> Vec<String> {
+	let mut tags: Vec<String> = Vec::new();
+	for part in keywords.split(TAG_SEPARATORS) {
+		let tag = part.trim();
+		if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+			tags.push(tag.to_string());
+		}
+	}
+	tags
+}
+
+fn tags_table(category: &str) -> String {
+	format!("{}_tags", category)
+}
+
+fn links_table(category: &str) -> String {
+	format!("{}_note_tags", category)
+}
+
+/// 建立标签表与笔记-标签关联表
+///
+/// 标签表首次创建时，会把已有笔记的 keywords 拆分成标签写入。
+pub fn ensure_tag_schema(conn: &Connection, category: &str) -> rusqlite::Result<()> {
+	let tags = tags_table(category);
+	let links = links_table(category);
+	let is_new = !crate::notes::table_exists(conn, &tags);
+
+	conn.execute_batch(&format!(
+		"CREATE TABLE IF NOT EXISTS {tags} (
+			id INTEGER PRIMARY KEY AUTOINCREMENT,
+			name TEXT NOT NULL UNIQUE
+		);
+		CREATE TABLE IF NOT EXISTS {links} (
+			note_id TEXT NOT NULL,
+			tag_id INTEGER NOT NULL,
+			PRIMARY KEY (note_id, tag_id)
+		);
+		CREATE INDEX IF NOT EXISTS {links}_tag ON {links} (tag_id);",
+		tags = tags,
+		links = links
+	))?;
+
+	if is_new {
+		let existing: Vec<(String, String)> = {
+			let mut stmt = conn.prepare(&format!(
+				"SELECT id, COALESCE(keywords, '') FROM {} WHERE COALESCE(keywords, '') <> ''",
+				category
+			))?;
+			let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+			rows.flatten().collect()
+		};
+		for (note_id, keywords) in existing {
+			set_note_tags(conn, category, &note_id, &split_keywords(&keywords))?;
+		}
+	}
+
+	Ok(())
+}
+
+/// 覆盖一条笔记的标签
+pub fn set_note_tags(conn: &Connection, category: &str, note_id: &str, tags: &[String]) -> rusqlite::Result<()> {
+	let tags_tbl = tags_table(category);
+	let links = links_table(category);
+
+	conn.execute(&format!("DELETE FROM {} WHERE note_id = ?1", links), [note_id])?;
+	for tag in tags {
+		conn.execute(&format!("INSERT OR IGNORE INTO {} (name) VALUES (?1)", tags_tbl), [tag])?;
+		conn.execute(
+			&format!(
+				"INSERT OR IGNORE INTO {links} (note_id, tag_id)
+				 SELECT ?1, id FROM {tags} WHERE name = ?2",
+				links = links,
+				tags = tags_tbl
+			),
+			[note_id, tag.as_str()],
+		)?;
+	}
+	remove_unused_tags(conn, category)
+}
+
+/// 删除笔记时一并移除关联
+pub fn remove_note_tags(conn: &Connection, category: &str, note_id: &str) -> rusqlite::Result<()> {
+	if !crate::notes::table_exists(conn, &links_table(category)) {
+		return Ok(());
+	}
+	conn.execute(&format!("DELETE FROM {} WHERE note_id = ?1", links_table(category)), [note_id])?;
+	remove_unused_tags(conn, category)
+}
+
+fn remove_unused_tags(conn: &Connection, category: &str) -> rusqlite::Result<()> {
+	conn.execute(
+		&format!(
+			"DELETE FROM {tags} WHERE id NOT IN (SELECT tag_id FROM {links})",
+			tags = tags_table(category),
+			links = links_table(category)
+		),
+		[],
+	)?;
+	Ok(())
+}
+
+/// 给已读取的笔记填上标签
+pub fn attach_tags(conn: &Connection, category: &str, notes: &mut [Notedb]) {
+	if notes.is_empty() || !crate::notes::table_exists(conn, &tags_table(category)) {
+		return;
+	}
+	let sql = format!(
+		"SELECT l.note_id, t.name FROM {links} l JOIN {tags} t ON t.id = l.tag_id ORDER BY t.name",
+		links = links_table(category),
+		tags = tags_table(category)
+	);
+	let mut stmt = match conn.prepare(&sql) {
+		Ok(s) => s,
+		Err(e) => {
+			eprintln!("读取标签失败: {:?}", e);
+			return;
+		}
+	};
+	let rows = match stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))) {
+		Ok(rows) => rows,
+		Err(_) => return,
+	};
+
+	let mut by_note: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+	for (note_id, name) in rows.flatten() {
+		by_note.entry(note_id).or_default().push(name);
+	}
+	for note in notes.iter_mut() {
+		if let Some(tags) = by_note.remove(&note.id) {
+			note.tags = tags;
+		}
+	}
+}
+
+/// 全部标签及其笔记数量（按名称排序）
+pub fn load_tag_counts(category: &str) -> Vec<(String, usize)> {
+	let Some(db_path) = notes_db_path() else { return Vec::new() };
+	let conn = match Connection::open(&db_path) {
+		Ok(c) => c,
+		Err(_) => return Vec::new(),
+	};
+	if !crate::notes::table_exists(&conn, &tags_table(category)) {
+		return Vec::new();
+	}
+	let sql = format!(
+		"SELECT t.name, COUNT(n.id)
+		 FROM {tags} t
+		 JOIN {links} l ON l.tag_id = t.id
+		 JOIN {cat} n ON n.id = l.note_id AND n.deleted_at IS NULL
+		 GROUP BY t.id
+		 ORDER BY t.name",
+		tags = tags_table(category),
+		links = links_table(category),
+		cat = category
+	);
+	let mut stmt = match conn.prepare(&sql) {
+		Ok(s) => s,
+		Err(_) => return Vec::new(),
+	};
+	let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)));
+	match rows {
+		Ok(rows) => rows.flatten().collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
+/// 重命名标签；新名称已存在时合并到该标签
+///
+/// 受影响笔记的 keywords 字段会同步改写，保证全文搜索结果一致。
+pub fn rename_tag(category: &str, old_name: &str, new_name: &str) -> rusqlite::Result<()> {
+	let new_name = new_name.trim();
+	if new_name.is_empty() || new_name == old_name {
+		return Ok(());
+	}
+	let Some(db_path) = notes_db_path() else { return Ok(()) };
+	let mut conn = Connection::open(&db_path)?;
+	crate::notes::ensure_notes_schema(&conn, category)?;
+
+	let tags = tags_table(category);
+	let links = links_table(category);
+	let tx = conn.transaction()?;
+
+	let affected: Vec<String> = {
+		let mut stmt = tx.prepare(&format!(
+			"SELECT l.note_id FROM {links} l JOIN {tags} t ON t.id = l.tag_id WHERE t.name = ?1",
+			links = links,
+			tags = tags
+		))?;
+		let rows = stmt.query_map([old_name], |row| row.get(0))?;
+		rows.flatten().collect()
+	};
+
+	tx.execute(&format!("INSERT OR IGNORE INTO {} (name) VALUES (?1)", tags), [new_name])?;
+	tx.execute(
+		&format!(
+			"INSERT OR IGNORE INTO {links} (note_id, tag_id)
+			 SELECT l.note_id, (SELECT id FROM {tags} WHERE name = ?2)
+			 FROM {links} l JOIN {tags} t ON t.id = l.tag_id WHERE t.name = ?1",
+			links = links,
+			tags = tags
+		),
+		[old_name, new_name],
+	)?;
+	tx.execute(
+		&format!(
+			"DELETE FROM {links} WHERE tag_id = (SELECT id FROM {tags} WHERE name = ?1)",
+			links = links,
+			tags = tags
+		),
+		[old_name],
+	)?;
+	tx.execute(&format!("DELETE FROM {} WHERE name = ?1", tags), [old_name])?;
+
+	// 同步 keywords 字段
+	for note_id in &affected {
+		let names: Vec<String> = {
+			let mut stmt = tx.prepare(&format!(
+				"SELECT t.name FROM {links} l JOIN {tags} t ON t.id = l.tag_id
+				 WHERE l.note_id = ?1 ORDER BY t.name",
+				links = links,
+				tags = tags
+			))?;
+			let rows = stmt.query_map([note_id], |row| row.get(0))?;
+			rows.flatten().collect()
+		};
+		tx.execute(
+			&format!("UPDATE {} SET keywords = ?1 WHERE id = ?2", category),
+			[names.join(TAG_JOINER), note_id.clone()],
+		)?;
+	}
+
+	tx.commit()?;
+	println!("已将标签「{}」改为「{}」（{} 条笔记）", old_name, new_name, affected.len());
+	Ok(())
+}
+
+/// 标签侧栏的操作结果
+pub enum TagAction {
+	None,
+	Filter(Option<String>),
+	Rename(String, String),
+}
+
+/// 标签云 / 过滤侧栏
+///
+/// `rename_state` 保存正在重命名的标签及输入框内容。
+pub fn draw_tag_sidebar(
+	ui: &mut egui::Ui,
+	colors: &ThemeColors,
+	tag_counts: &[(String, usize)],
+	selected: Option<&str>,
+	rename_state: &mut Option<(String, String)>,
+) -> TagAction {
+	let mut action = TagAction::None;
+
+	ui.label(egui::RichText::new("标签").strong());
+	ui.separator();
+
+	if tag_counts.is_empty() {
+		ui.label(egui::RichText::new("暂无标签").size(12.0).color(colors.comment_text_color));
+		return action;
+	}
+
+	if ui.selectable_label(selected.is_none(), "全部笔记").clicked() {
+		action = TagAction::Filter(None);
+	}
+
+	let max_count = tag_counts.iter().map(|(_, c)| *c).max().unwrap_or(1).max(1);
+
+	egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+		ui.horizontal_wrapped(|ui| {
+			for (name, count) in tag_counts {
+				// 笔记越多字号越大
+				let size = 12.0 + 8.0 * (*count as f32 / max_count as f32);
+				let is_selected = selected == Some(name.as_str());
+				let text = egui::RichText::new(format!("{}({})", name, count)).size(size);
+				let resp = ui.selectable_label(is_selected, text);
+
+				if resp.clicked() {
+					action = TagAction::Filter(if is_selected { None } else { Some(name.clone()) });
+				}
+				resp.context_menu(|ui| {
+					if ui.button("✏ 重命名 / 合并").clicked() {
+						*rename_state = Some((name.clone(), name.clone()));
+						ui.close_kind(egui::UiKind::Menu);
+					}
+				});
+			}
+		});
+
+		if let Some((old_name, new_name)) = rename_state {
+			ui.separator();
+			ui.label(egui::RichText::new(format!("重命名「{}」", old_name)).size(12.0));
+			ui.add(egui::TextEdit::singleline(new_name).desired_width(f32::INFINITY));
+			ui.label(
+				egui::RichText::new("改为已有标签即合并")
+				.size(10.0)
+				.color(colors.comment_text_color),
+			);
+			let mut finished = false;
+			ui.horizontal(|ui| {
+				if ui.button("确定").clicked() {
+					action = TagAction::Rename(old_name.clone(), new_name.trim().to_string());
+					finished = true;
+				}
+				if ui.button("取消").clicked() {
+					finished = true;
+				}
+			});
+			if finished {
+				*rename_state = None;
+			}
+		}
+	});
+
+	action
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::notes::{self, write_note};
+
+	fn save(id: &str, tags: &[&str]) {
+		let note = Notedb {
+			id: id.to_string(),
+			verse_start: -1,
+			title: Some(id.to_string()),
+			tags: tags.iter().map(|t| t.to_string()).collect(),
+			..Default::default()
+		};
+		write_note("notes", &note, "2024-05-01T10:00:00+00:00").unwrap();
+	}
+
+	fn note_tags(conn: &Connection, id: &str) -> (Vec<String>, String) {
+		let note = notes::load_note(conn, "notes", id).unwrap().unwrap();
+		(note.tags, note.keywords.unwrap_or_default())
+	}
+
+	#[test]
+	fn splits_keywords_on_any_separator() {
+		assert_eq!(split_keywords("恩典，信心, 盼望；恩典　 祷告、"), vec!["恩典", "信心", "盼望", "祷告"]);
+		assert!(split_keywords(" ，, ").is_empty());
+	}
+
+	#[test]
+	fn replacing_tags_drops_unused_ones() {
+		let _encryption = crate::crypto::test_encryption(None);
+		let db = notes::test_db();
+		let conn = db.open("notes");
+		save("a", &["信心", "恩典"]);
+		save("b", &["恩典"]);
+		assert_eq!(note_tags(&conn, "a").0, vec!["信心", "恩典"]);
+
+		save("a", &["盼望"]);
+		assert_eq!(load_tag_counts("notes"), vec![("恩典".to_string(), 1), ("盼望".to_string(), 1)]);
+		remove_note_tags(&conn, "notes", "b").unwrap();
+		assert_eq!(load_tag_counts("notes"), vec![("盼望".to_string(), 1)]);
+	}
+
+	#[test]
+	fn rename_merges_into_existing_tag_and_rewrites_keywords() {
+		let _encryption = crate::crypto::test_encryption(None);
+		let db = notes::test_db();
+		let conn = db.open("notes");
+		save("a", &["信心", "恩典"]);
+		save("b", &["恩典"]);
+
+		rename_tag("notes", "恩典", "信心").unwrap();
+		assert_eq!(load_tag_counts("notes"), vec![("信心".to_string(), 2)]);
+		assert_eq!(note_tags(&conn, "a"), (vec!["信心".to_string()], "信心".to_string()));
+		assert_eq!(note_tags(&conn, "b"), (vec!["信心".to_string()], "信心".to_string()));
+	}
+}
@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+use rusqlite::Connection;
+
+/// 用户数据库路径（高亮等与笔记无关的个人数据），目录不存在时自动创建
+pub fn user_db_path() -> Option<PathBuf> {
+	#[cfg(test)]
+	if let Some(path) = TEST_USER_DB.with(|p| p.borrow().clone()) {
+		return Some(path);
+	}
+	let dir = dirs::data_dir()?.join("bible_reader/user");
+	if let Err(e) = std::fs::create_dir_all(&dir) {
+		eprintln!("无法创建用户数据目录 {:?}: {:?}", dir, e);
+		return None;
+	}
+	Some(dir.join("user.db"))
+}
+
+/// 打开用户数据库并建立所有表
+pub fn open_user_db() -> rusqlite::Result<Option<Connection>> {
+	let Some(db_path) = user_db_path() else { return Ok(None) };
+	let conn = Connection::open(&db_path)?;
+	crate::highlights::ensure_highlight_schema(&conn)?;
+	crate::bookmarks::ensure_bookmark_schema(&conn)?;
+	crate::reading_plan::ensure_reading_plan_schema(&conn)?;
+	crate::reading_log::ensure_reading_log_schema(&conn)?;
+	Ok(Some(conn))
+}
+
+#[cfg(test)]
+thread_local! {
+	static TEST_USER_DB: std::cell::RefCell<Option<PathBuf>> = const { std::cell::RefCell::new(None) };
+}
+
+/// 测试用：本线程改用临时目录中的用户数据库，守卫释放时删除
+#[cfg(test)]
+pub fn test_user_db() -> TestUserDb {
+	let dir = std::env::temp_dir().join(format!("user-test-{}", uuid::Uuid::new_v4()));
+	std::fs::create_dir_all(&dir).unwrap();
+	TEST_USER_DB.with(|p| *p.borrow_mut() = Some(dir.join("user.db")));
+	TestUserDb { dir }
+}
+
+#[cfg(test)]
+pub struct TestUserDb {
+	dir: PathBuf,
+}
+
+#[cfg(test)]
+impl Drop for TestUserDb {
+	fn drop(&mut self) {
+		TEST_USER_DB.with(|p| *p.borrow_mut() = None);
+		let _ = std::fs::remove_dir_all(&self.dir);
+	}
+}
@@ -50,7 +50,12 @@ pub fn load_chapters(db_path: &Path, book_number: i32) -> Vec<String> {
 
 /// 从 SQLite 读取章节内容
 pub fn load_chapter_content(db_path: &Path, book_number: i32, chapter: i32) -> String {
-	let conn = Connection::open(db_path).unwrap();
+	find_chapter_content(db_path, book_number, chapter).unwrap_or_else(|| "（未找到章节内容）".to_string())
+}
+
+/// 读取一章经文；译本中没有这一章时返回 None
+pub fn find_chapter_content(db_path: &Path, book_number: i32, chapter: i32) -> Option<String> {
+	let conn = Connection::open(db_path).ok()?;
 
 	let osis: String = conn
 		.query_row(
@@ -58,7 +63,7 @@ pub fn load_chapter_content(db_path: &Path, book_number: i32, chapter: i32) -> S
 			[book_number],
 			|row| row.get(0),
 		)
-		.unwrap_or_default();
+		.ok()?;
 
 	let reference = format!("{}.{}", osis, chapter);
 
@@ -66,7 +71,90 @@ pub fn load_chapter_content(db_path: &Path, book_number: i32, chapter: i32) -> S
 		"SELECT content FROM chapters WHERE reference_osis = ?1",
 		[reference],
 		|row| row.get(0),
-	).unwrap_or_else(|_| "（未找到章节内容）".to_string())
+	).ok()
+}
+
+/// 行首的节号，支持「16 神爱世人」「16.」「16、」「[16]」等写法
+fn leading_verse_number(line: &str) -> Option<(i32, &str)> {
+	let line = line.trim_start();
+	let line = line.strip_prefix('[').unwrap_or(line);
+	let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+	if digits == 0 || digits > 3 {
+		return None;
+	}
+	let number = line[..digits].parse().ok()?;
+	let rest = line[digits..].trim_start_matches([']', '.', '、', ':', '：', ' ', '\u{3000}']);
+	Some((number, rest))
+}
+
+/// 每一行所属的节号；整章都没有节号时按非空行依次编号
+fn verse_number_of_lines(content: &str) -> Vec<Option<i32>> {
+	let numbered = content.lines().any(|l| leading_verse_number(l).is_some());
+	let mut current = None;
+	let mut next = 1;
+	content.lines().map(|line| {
+		if numbered {
+			if let Some((n, _)) = leading_verse_number(line) {
+				current = Some(n);
+			}
+		} else if !line.trim().is_empty() {
+			current = Some(next);
+			next += 1;
+		}
+		current
+	}).collect()
+}
+
+/// 把章节内容拆成（节号, 经文）
+pub fn split_verses(content: &str) -> Vec<(i32, String)> {
+	let mut verses: Vec<(i32, String)> = Vec::new();
+	for (line, number) in content.lines().zip(verse_number_of_lines(content)) {
+		let Some(number) = number else { continue };
+		let text = leading_verse_number(line).map(|(_, rest)| rest).unwrap_or(line).trim();
+		if text.is_empty() {
+			continue;
+		}
+		match verses.last_mut() {
+			Some((n, existing)) if *n == number => {
+				existing.push_str(text);
+			}
+			_ => verses.push((number, text.to_string())),
+		}
+	}
+	verses
+}
+
+/// 章节内容中第 char_index 个字符所在的节号
+pub fn verse_at_char(content: &str, char_index: usize) -> Option<i32> {
+	let numbers = verse_number_of_lines(content);
+	let mut start = 0;
+	for (line, number) in content.lines().zip(numbers) {
+		let end = start + line.chars().count();
+		if char_index <= end {
+			return number;
+		}
+		start = end + 1;
+	}
+	None
+}
+
+/// 每一节在章节内容中的字节范围（不含行尾换行）
+pub fn verse_byte_ranges(content: &str) -> Vec<(i32, std::ops::Range<usize>)> {
+	let mut ranges: Vec<(i32, std::ops::Range<usize>)> = Vec::new();
+	let mut start = 0;
+	for (line, number) in content.split('\n').zip(verse_number_of_lines(content)) {
+		let end = start + line.len();
+		if let Some(number) = number
+			&& !line.trim().is_empty()
+		{
+			match ranges.last_mut() {
+				Some((n, range)) if *n == number => range.end = end,
+				_ => ranges.push((number, start..end)),
+			}
+		}
+		start = end + 1;
+	}
+	ranges
 }
 
 /// 章节排序辅助
@@ -180,64 +268,61 @@ pub fn sort_versions_chinese_first(versions: &mut Vec<String>) {
 //	response
 //}
 
+/// 高亮文本中的搜索词（可同时高亮多个），无搜索词时原样输出
 pub fn highlight_search_terms(
-    text: &str,
-    search_terms: &str,
-		colors: &ThemeColors,
-    job: &mut egui::text::LayoutJob, 
-		font_id: &egui::FontId,
+	text: &str,
+	terms: &[impl AsRef<str>],
+	colors: &ThemeColors,
+	job: &mut egui::text::LayoutJob,
+	font_id: &egui::FontId,
 ) {
-    let mut last_index = 0;
-    let lower_text = text.to_lowercase();
-    let lower_query = search_terms.to_lowercase();
-
-    let mut start = 0;
-    while let Some(pos) = lower_text[start..].find(&lower_query) {
-        let match_start = start + pos;
-        let match_end = match_start + search_terms.len();
-
-        // 普通文本
-        if match_start > last_index {
-            job.append(
-                &text[last_index..match_start],
-                0.0,
-                egui::TextFormat {
-									font_id: font_id.clone(),
-                    color: colors.text_color,
-                    ..Default::default()
-                },
-            );
-        }
-
-        // 高亮文本
-        job.append(
-            &text[match_start..match_end],
-            0.0,
-            egui::TextFormat {
-									font_id: font_id.clone(),
-								color: colors.search_hl_fg,            
-								background: colors.search_hl_bg,
-                ..Default::default()
-            },
-        );
-
-        last_index = match_end;
-        start = match_end;
-    }
+	let normal = egui::TextFormat {
+		font_id: font_id.clone(),
+		color: colors.text_color,
+		..Default::default()
+	};
+	let highlighted = egui::TextFormat {
+		font_id: font_id.clone(),
+		color: colors.search_hl_fg,
+		background: colors.search_hl_bg,
+		..Default::default()
+	};
+
+	// 小写后字节长度不变时才能用小写文本定位，否则区分大小写
+	let lower_text = text.to_lowercase();
+	let ignore_case = lower_text.len() == text.len();
+	let haystack = if ignore_case { lower_text.as_str() } else { text };
+	let needles: Vec<String> = terms.iter()
+		.map(|t| t.as_ref().trim())
+		.filter(|t| !t.is_empty())
+		.map(|t| if ignore_case { t.to_lowercase() } else { t.to_string() })
+		.collect();
+
+	let mut last_index = 0;
+	while last_index < text.len() {
+		// 找到下一个最早出现（同位置取最长）的搜索词
+		let next = needles.iter()
+			.filter_map(|n| haystack[last_index..].find(n.as_str()).map(|pos| (last_index + pos, n.len())))
+			.min_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+		let Some((match_start, len)) = next else { break };
+		let match_end = match_start + len;
+		if !text.is_char_boundary(match_end) {
+			break;
+		}
+
+		if match_start > last_index {
+			job.append(&text[last_index..match_start], 0.0, normal.clone());
+		}
+		job.append(&text[match_start..match_end], 0.0, highlighted.clone());
+		last_index = match_end;
+	}
 
-    // 剩余普通文本
-    if last_index < text.len() {
-        job.append(
-            &text[last_index..],
-            0.0,
-            egui::TextFormat {
-							font_id: font_id.clone(),
-                color: colors.text_color,
-                ..Default::default()
-            },
-        );
-    }
+	if last_index < text.len() {
+		job.append(&text[last_index..], 0.0, normal);
+	}
 }
+
 //pub fn readonly_multiline_text(ui: &mut egui::Ui, text: &str) -> egui::Response {
 //    let body_font_id = ui.style().text_styles[&egui::TextStyle::Body].clone();
 //    let mut mutable_content = text.to_owned();
@@ -304,3 +389,129 @@ pub fn draw_hover_button(
 
 
 
+
+/// 当前时间，带本地时区的 RFC 3339 格式，例如 2024-05-01T20:15:03+08:00
+pub fn now_timestamp() -> String {
+	chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false)
+}
+
+/// 解析笔记时间：RFC 3339，或旧版本只记录日期的 2024-05-01（按当地零点）
+pub fn parse_timestamp(s: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+	let s = s.trim();
+	if let Ok(t) = chrono::DateTime::parse_from_rfc3339(s) {
+		return Some(t);
+	}
+	let day = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?;
+	day.and_hms_opt(0, 0, 0)?
+		.and_local_timezone(chrono::Local)
+		.earliest()
+		.map(|t| t.fixed_offset())
+}
+
+/// 笔记时间的排序键：能解析的按时刻比较（不同时区可比），排在无法解析的之后；
+/// 无法解析的按原字符串比较，空值最早。任意一组值之间都是全序
+pub fn timestamp_sort_key(s: Option<&str>) -> (bool, i64, String) {
+	let s = s.unwrap_or("");
+	match parse_timestamp(s) {
+		Some(t) => (true, t.timestamp(), String::new()),
+		None => (false, 0, s.to_string()),
+	}
+}
+
+/// 按 timestamp_sort_key 比较两个笔记时间
+pub fn compare_timestamps(a: Option<&str>, b: Option<&str>) -> Ordering {
+	timestamp_sort_key(a).cmp(&timestamp_sort_key(b))
+}
+
+/// 按本地时区显示笔记时间；旧的只有日期的值原样显示
+pub fn display_timestamp(s: &str) -> String {
+	match chrono::DateTime::parse_from_rfc3339(s.trim()) {
+		Ok(t) => t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string(),
+		Err(_) => s.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_rfc3339_and_date_only_timestamps() {
+		let t = parse_timestamp("2024-05-01T08:30:00+08:00").unwrap();
+		assert_eq!(t.to_rfc3339(), "2024-05-01T08:30:00+08:00");
+
+		let day = parse_timestamp(" 2024-05-01 ").unwrap();
+		assert_eq!(day.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string(), "2024-05-01 00:00:00");
+
+		assert!(parse_timestamp("").is_none());
+		assert!(parse_timestamp("昨天").is_none());
+	}
+
+	#[test]
+	fn now_timestamp_round_trips() {
+		assert!(parse_timestamp(&now_timestamp()).is_some());
+	}
+
+	#[test]
+	fn compares_timestamps_across_time_zones() {
+		// 同一时刻，不同时区
+		assert_eq!(
+			compare_timestamps(Some("2024-05-01T08:00:00+08:00"), Some("2024-05-01T00:00:00+00:00")),
+			Ordering::Equal,
+		);
+		// 字符串上更大，时间上更早
+		assert_eq!(
+			compare_timestamps(Some("2024-05-01T09:00:00+08:00"), Some("2024-05-01T02:00:00+00:00")),
+			Ordering::Less,
+		);
+	}
+
+	#[test]
+	fn compares_date_only_with_full_timestamps() {
+		let day = chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+			.and_hms_opt(12, 0, 0).unwrap()
+			.and_local_timezone(chrono::Local).unwrap()
+			.to_rfc3339();
+		assert_eq!(compare_timestamps(Some("2024-05-01"), Some(&day)), Ordering::Less);
+		assert_eq!(compare_timestamps(Some("2024-05-02"), Some(&day)), Ordering::Greater);
+	}
+
+	#[test]
+	fn mixed_timestamps_sort_consistently() {
+		// 能解析的与无法解析的混在一起时也是全序，排序结果与输入顺序无关
+		let values = [
+			Some("2024-05-01T09:00:00+08:00"),
+			Some("zzz"),
+			Some("2024-05-01T02:00:00+00:00"),
+			None,
+			Some("2024-04-30"),
+			Some("2024-05-01 10:00"),
+			Some("2024-05-02T00:00:00Z"),
+		];
+		let mut forward = values.to_vec();
+		forward.sort_by_cached_key(|v| timestamp_sort_key(*v));
+		let mut backward: Vec<Option<&str>> = values.iter().rev().copied().collect();
+		backward.sort_by(|a, b| compare_timestamps(*a, *b));
+		assert_eq!(forward, backward);
+
+		for a in &values {
+			for b in &values {
+				for c in &values {
+					if compare_timestamps(*a, *b).is_lt() && compare_timestamps(*b, *c).is_lt() {
+						assert!(compare_timestamps(*a, *c).is_lt());
+					}
+				}
+			}
+		}
+		// 无法解析的排在能解析的之前（按时间倒序时排在最后）
+		assert_eq!(forward.first(), Some(&None));
+		assert_eq!(forward.last(), Some(&Some("2024-05-02T00:00:00Z")));
+	}
+
+	#[test]
+	fn empty_timestamps_sort_first() {
+		assert_eq!(compare_timestamps(None, Some("2024-05-01")), Ordering::Less);
+		assert_eq!(compare_timestamps(Some("2024-05-01"), None), Ordering::Greater);
+		assert_eq!(compare_timestamps(None, None), Ordering::Equal);
+	}
+}